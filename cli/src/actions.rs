@@ -12,5 +12,5 @@ pub enum Action {
 pub enum Event {
     #[default]
     None,
-    Goto(AppStates)
-}
\ No newline at end of file
+    Goto(AppStates),
+}
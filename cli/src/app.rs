@@ -1,9 +1,9 @@
 //! This file contains the application log
 
-use futures::{Future, FutureExt};
-use sqlx::SqliteConnection;
 use std::error;
 use std::rc::Rc;
+use taskify::db::workspaces::Workspace;
+use taskify::db::Store;
 
 use crate::states::home::Home;
 use crate::states::project::Project;
@@ -16,8 +16,9 @@ pub type AppResult<T> = Result<T, Box<dyn error::Error>>;
 pub struct App {
     pub running: bool,
     pub state: Box<dyn AppState + Send>,
-    pub db: Option<SqliteConnection>,
-    pub runtime: Rc<tokio::runtime::Runtime>
+    pub store: Option<Store>,
+    pub workspace: Option<Workspace>,
+    pub runtime: Rc<tokio::runtime::Runtime>,
 }
 
 impl Default for App {
@@ -25,11 +26,14 @@ impl Default for App {
         Self {
             running: true,
             state: Box::new(Home::default()),
-            db: None,
-            runtime: Rc::new(tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap())
+            store: None,
+            workspace: None,
+            runtime: Rc::new(
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap(),
+            ),
         }
     }
 }
@@ -37,23 +41,30 @@ impl Default for App {
 impl App {
     pub async fn new() -> Self {
         let mut app: App = Default::default();
-        let (_, conn) = taskify::init().await.unwrap();
+        let (config, store) = taskify::init().await.unwrap();
+        let workspace = Workspace::from_current_db(&config.workspace, store.pool())
+            .await
+            .expect("Error while loading the current workspace");
 
-        app.db = Some(conn);
+        app.store = Some(store);
+        app.workspace = Some(workspace);
         app
     }
 
     pub async fn tick(&mut self) {
-        self.state.tick(self.db.as_mut().unwrap()).await
+        self.state.tick(self.store.as_ref().unwrap()).await
     }
 
     pub async fn set_state(&mut self, event: AppStates) {
         match event {
             AppStates::Home => self.state = Box::new(Home::default()),
-            AppStates::Project => self.state = Box::new(Project::default()),
+            AppStates::Project => {
+                let workspace = self.workspace.as_ref().unwrap();
+                self.state = Box::new(Project::new(*workspace.id()))
+            }
         };
 
-        self.state.init(self.db.as_mut().unwrap()).await
+        self.state.init(self.store.as_ref().unwrap()).await
     }
 
     pub fn quit(&mut self) {
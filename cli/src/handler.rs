@@ -2,24 +2,25 @@
 
 use std::future::IntoFuture;
 
+use crate::actions::{Action, Event};
 use crate::app::{App, AppResult};
 use crossterm::event::{KeyCode, KeyEvent};
-use crate::actions::{Action, Event};
 
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     match key_event.code {
         KeyCode::Char('q') => {
             app.quit();
-        },
-        KeyCode::Esc => { let _ = app.state.action(Action::Escape); },
+        }
+        KeyCode::Esc => {
+            let _ = app.state.action(Action::Escape);
+        }
         _ => {}
     }
 
     app.state.keybindings().iter_mut().for_each(|key| {
         if key_event.code == KeyCode::Char(key.key) {
-            match app.state.action(key.action) {
-                Event::Goto(v) => app.runtime.clone().block_on(app.set_state(v).into_future()),
-                _ => {}
+            if let Event::Goto(v) = app.state.action(key.action) {
+                app.runtime.clone().block_on(app.set_state(v).into_future())
             }
         }
     });
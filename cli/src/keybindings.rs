@@ -1,11 +1,13 @@
 use crate::actions::Action;
 
 pub trait Keybindings {
-    fn keybindings(&self) -> Vec<Keybinding> { vec![] }
+    fn keybindings(&self) -> Vec<Keybinding> {
+        vec![]
+    }
 }
 
 pub struct Keybinding {
     pub key: char,
     pub name: String,
-    pub action: Action
-}
\ No newline at end of file
+    pub action: Action,
+}
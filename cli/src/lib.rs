@@ -1,8 +1,8 @@
+pub mod actions;
 pub mod app;
 pub mod event;
 pub mod handler;
+pub mod keybindings;
+pub mod states;
 pub mod tui;
 pub mod ui;
-pub mod states;
-pub mod keybindings;
-pub mod actions;
\ No newline at end of file
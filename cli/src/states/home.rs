@@ -8,7 +8,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Rect};
 use ratatui::prelude::{Color, Modifier, Span, Style};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Widget};
-use sqlx::SqliteConnection;
+use taskify::db::Store;
 
 #[derive(Debug, Default)]
 pub struct Home {}
@@ -19,9 +19,9 @@ impl AppState for Home {
         "HOME"
     }
 
-    async fn init(&mut self, exec: &mut SqliteConnection) {}
+    async fn init(&mut self, _store: &Store) {}
 
-    async fn tick(&mut self, exec: &mut SqliteConnection) {}
+    async fn tick(&mut self, _store: &Store) {}
 
     fn action(&mut self, action: Action) -> Event {
         match action {
@@ -5,8 +5,8 @@ use crate::keybindings::Keybindings;
 use async_trait::async_trait;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use sqlx::SqliteConnection;
 use std::fmt::Debug;
+use taskify::db::Store;
 
 pub mod home;
 pub mod project;
@@ -23,9 +23,9 @@ pub trait AppState: Keybindings + RenderState + Debug {
         "APP_STATE"
     }
 
-    async fn init(&mut self, exec: &mut SqliteConnection) {}
+    async fn init(&mut self, store: &Store) {}
 
-    async fn tick(&mut self, exec: &mut SqliteConnection) {}
+    async fn tick(&mut self, store: &Store) {}
 
     fn action(&mut self, action: Action) -> Event {
         Event::None
@@ -4,33 +4,50 @@ use crate::states::{AppState, RenderState};
 use async_trait::async_trait;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use sqlx::SqliteConnection;
+use taskify::db::filter::ListSpec;
 use taskify::db::projects::Project as DBProject;
+use taskify::db::Store;
+use uuid::Uuid;
 
 #[derive(Debug, Default)]
 pub struct Project {
+    workspace_id: Uuid,
     projects: Vec<DBProject>,
     db_page: u64,
 }
 
+impl Project {
+    /// The project list of a workspace
+    pub fn new(workspace_id: Uuid) -> Self {
+        Self {
+            workspace_id,
+            ..Default::default()
+        }
+    }
+}
+
 #[async_trait]
 impl AppState for Project {
     fn display_name(&self) -> &str {
         "PROJECTS"
     }
 
-    async fn init(&mut self, exec: &mut SqliteConnection) {
-        self.projects = DBProject::from_list_db(self.db_page, 12, exec)
-            .await
-            .unwrap()
+    async fn init(&mut self, store: &Store) {
+        self.projects = DBProject::from_workspace_list_db(
+            self.workspace_id,
+            &ListSpec::new(),
+            self.db_page,
+            12,
+            store.pool(),
+        )
+        .await
+        .unwrap()
     }
 
-    async fn tick(&mut self, exec: &mut SqliteConnection) {}
+    async fn tick(&mut self, _store: &Store) {}
 
-    fn action(&mut self, action: Action) -> Event {
-        match action {
-            _ => Event::None,
-        }
+    fn action(&mut self, _action: Action) -> Event {
+        Event::None
     }
 }
 
@@ -41,5 +58,5 @@ impl Keybindings for Project {
 }
 
 impl RenderState for Project {
-    fn render(&self, area: Rect, buf: &mut Buffer) {}
+    fn render(&self, _area: Rect, _buf: &mut Buffer) {}
 }
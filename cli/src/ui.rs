@@ -1,12 +1,10 @@
 //! This file contains all the UI components
 
-use ratatui::{
-    Frame,
-};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Style, Stylize};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
+use ratatui::Frame;
 
 use crate::app::App;
 
@@ -32,17 +30,34 @@ pub fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
 }
 
 pub fn render(app: &mut App, frame: &mut Frame) {
-    app.state.render(Rect {height: frame.size().height - 1, ..frame.size()}, frame.buffer_mut());
+    app.state.render(
+        Rect {
+            height: frame.size().height - 1,
+            ..frame.size()
+        },
+        frame.buffer_mut(),
+    );
 
     let mut bottom_bar = vec![
         Span::styled(" TASKIFY ", Style::new().black().on_white()),
-        Span::styled(format!(" {} ", app.state.display_name()), Style::new().white().on_dark_gray()),
-        " q: Quit ".into()
+        Span::styled(
+            format!(" {} ", app.state.display_name()),
+            Style::new().white().on_dark_gray(),
+        ),
+        " q: Quit ".into(),
     ];
 
-    app.state.keybindings().iter().for_each(|key| {
-        bottom_bar.push(format!(" {}: {} ", key.key, key.name).into())
-    });
-
-    frame.render_widget(Paragraph::new(Line::from(bottom_bar)), Rect {height: 1, y: frame.size().height - 1, ..frame.size()});
+    app.state
+        .keybindings()
+        .iter()
+        .for_each(|key| bottom_bar.push(format!(" {}: {} ", key.key, key.name).into()));
+
+    frame.render_widget(
+        Paragraph::new(Line::from(bottom_bar)),
+        Rect {
+            height: 1,
+            y: frame.size().height - 1,
+            ..frame.size()
+        },
+    );
 }
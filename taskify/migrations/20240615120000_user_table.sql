@@ -0,0 +1,37 @@
+CREATE TABLE IF NOT EXISTS user (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    email VARCHAR(254) UNIQUE,
+    handle VARCHAR(64) UNIQUE NOT NULL,
+    avatar TEXT,
+    color VARCHAR(16),
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+-- Every distinct project author becomes a user, the author string is used as both name and handle
+INSERT INTO user (id, name, handle, created, modified)
+SELECT randomblob(16), author, author, MIN(created), MIN(created)
+FROM project
+GROUP BY author;
+
+ALTER TABLE project ADD COLUMN author_id VARCHAR(36) REFERENCES user(id);
+
+UPDATE project SET author_id = (SELECT id FROM user WHERE handle = project.author);
+
+ALTER TABLE project DROP COLUMN author;
+
+CREATE INDEX IF NOT EXISTS project_author_id ON project(author_id);
+
+-- A column added by ALTER TABLE can not be NOT NULL without a default
+CREATE TRIGGER IF NOT EXISTS project_author_insert BEFORE INSERT ON project
+WHEN NEW.author_id IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'project author required');
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_author_update BEFORE UPDATE OF author_id ON project
+WHEN NEW.author_id IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'project author required');
+END;
@@ -0,0 +1,38 @@
+CREATE TABLE IF NOT EXISTS workspace (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    name VARCHAR(32) UNIQUE NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+-- Existing projects move to the default workspace, the one config.toml starts with
+INSERT INTO workspace (id, name, created, modified)
+VALUES (randomblob(16), 'default', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+
+ALTER TABLE project ADD COLUMN workspace_id VARCHAR(36) REFERENCES workspace(id) ON DELETE CASCADE;
+
+UPDATE project SET workspace_id = (SELECT id FROM workspace WHERE name = 'default');
+
+CREATE INDEX IF NOT EXISTS project_workspace_id ON project(workspace_id);
+
+-- A column added by ALTER TABLE can not be NOT NULL without a default
+CREATE TRIGGER IF NOT EXISTS project_workspace_insert BEFORE INSERT ON project
+WHEN NEW.workspace_id IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'project workspace required');
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_workspace_update BEFORE UPDATE OF workspace_id ON project
+WHEN NEW.workspace_id IS NULL
+BEGIN
+    SELECT RAISE(ABORT, 'project workspace required');
+END;
+
+-- Deleting a workspace would cascade to its projects past the trash, the history & the undo journal, so only empty
+-- workspaces can be deleted. Projects in the trash count, they have to be purged first.
+CREATE TRIGGER IF NOT EXISTS workspace_delete_guard BEFORE DELETE ON workspace
+WHEN EXISTS (SELECT 1 FROM project WHERE workspace_id = OLD.id)
+BEGIN
+    SELECT RAISE(ABORT, 'workspace not empty');
+END;
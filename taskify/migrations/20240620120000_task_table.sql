@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS task (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    project_id VARCHAR(36) NOT NULL REFERENCES project(id) ON DELETE CASCADE,
+    title VARCHAR(128),
+    body TEXT,
+    status VARCHAR(32) NOT NULL,
+    author_id VARCHAR(36) NOT NULL REFERENCES user(id),
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS task_project_id ON task(project_id);
+CREATE INDEX IF NOT EXISTS task_author_id ON task(author_id);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS project_workflow (
+    project_id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY REFERENCES project(id) ON DELETE CASCADE,
+    definition TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS task_transition (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    from_status VARCHAR(32) NOT NULL,
+    to_status VARCHAR(32) NOT NULL,
+    transitioned TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS task_transition_task_id ON task_transition(task_id);
@@ -0,0 +1,23 @@
+ALTER TABLE task ADD COLUMN parent_id VARCHAR(36) REFERENCES task(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS task_parent_id ON task(parent_id);
+
+-- A task can never be its own ancestor
+CREATE TRIGGER IF NOT EXISTS task_hierarchy_cycle_insert BEFORE INSERT ON task
+WHEN NEW.parent_id IS NOT NULL AND NEW.parent_id = NEW.id
+BEGIN
+    SELECT RAISE(ABORT, 'task hierarchy cycle');
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_hierarchy_cycle_update BEFORE UPDATE OF parent_id ON task
+WHEN NEW.parent_id IS NOT NULL AND NEW.id IN (
+    WITH RECURSIVE ancestor(id) AS (
+        SELECT NEW.parent_id
+        UNION
+        SELECT task.parent_id FROM task JOIN ancestor ON task.id = ancestor.id WHERE task.parent_id IS NOT NULL
+    )
+    SELECT id FROM ancestor
+)
+BEGIN
+    SELECT RAISE(ABORT, 'task hierarchy cycle');
+END;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS task_dependency (
+    blocker_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    blocked_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    created TEXT NOT NULL,
+    PRIMARY KEY (blocker_id, blocked_id)
+);
+
+CREATE INDEX IF NOT EXISTS task_dependency_blocked_id ON task_dependency(blocked_id);
+
+-- A task can never (transitively) block itself
+CREATE TRIGGER IF NOT EXISTS task_dependency_cycle BEFORE INSERT ON task_dependency
+WHEN NEW.blocker_id = NEW.blocked_id OR NEW.blocker_id IN (
+    WITH RECURSIVE blocked(id) AS (
+        SELECT NEW.blocked_id
+        UNION
+        SELECT task_dependency.blocked_id FROM task_dependency JOIN blocked ON task_dependency.blocker_id = blocked.id
+    )
+    SELECT id FROM blocked
+)
+BEGIN
+    SELECT RAISE(ABORT, 'task dependency cycle');
+END;
@@ -0,0 +1,13 @@
+ALTER TABLE task ADD COLUMN start TEXT;
+ALTER TABLE task ADD COLUMN due TEXT;
+
+CREATE INDEX IF NOT EXISTS task_due ON task(due);
+
+CREATE TABLE IF NOT EXISTS task_reminder (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    offset_minutes INTEGER NOT NULL,
+    created TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS task_reminder_task_id ON task_reminder(task_id);
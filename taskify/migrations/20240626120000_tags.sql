@@ -0,0 +1,42 @@
+-- Tags belong to a workspace and their names are only unique within it
+CREATE TABLE IF NOT EXISTS tag (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    workspace_id VARCHAR(36) NOT NULL REFERENCES workspace(id) ON DELETE CASCADE,
+    name VARCHAR(32) NOT NULL,
+    color VARCHAR(16) NOT NULL,
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL,
+    UNIQUE (workspace_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS project_tag (
+    project_id VARCHAR(36) NOT NULL REFERENCES project(id) ON DELETE CASCADE,
+    tag_id VARCHAR(36) NOT NULL REFERENCES tag(id) ON DELETE CASCADE,
+    PRIMARY KEY (project_id, tag_id)
+);
+
+CREATE TABLE IF NOT EXISTS task_tag (
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    tag_id VARCHAR(36) NOT NULL REFERENCES tag(id) ON DELETE CASCADE,
+    PRIMARY KEY (task_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS project_tag_tag_id ON project_tag(tag_id);
+CREATE INDEX IF NOT EXISTS task_tag_tag_id ON task_tag(tag_id);
+
+-- A tag can only be attached to the projects & tasks of its own workspace
+CREATE TRIGGER IF NOT EXISTS project_tag_workspace BEFORE INSERT ON project_tag
+WHEN (SELECT workspace_id FROM tag WHERE id = NEW.tag_id)
+    IS NOT (SELECT workspace_id FROM project WHERE id = NEW.project_id)
+BEGIN
+    SELECT RAISE(ABORT, 'tag workspace mismatch');
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_tag_workspace BEFORE INSERT ON task_tag
+WHEN (SELECT workspace_id FROM tag WHERE id = NEW.tag_id)
+    IS NOT (SELECT project.workspace_id FROM task
+            INNER JOIN project ON project.id = task.project_id
+            WHERE task.id = NEW.task_id)
+BEGIN
+    SELECT RAISE(ABORT, 'tag workspace mismatch');
+END;
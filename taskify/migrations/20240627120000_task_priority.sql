@@ -0,0 +1,2 @@
+ALTER TABLE task ADD COLUMN priority INTEGER NOT NULL DEFAULT 2 CHECK (priority BETWEEN 0 AND 4);
+ALTER TABLE task ADD COLUMN effort REAL CHECK (effort >= 0);
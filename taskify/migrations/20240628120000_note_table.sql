@@ -0,0 +1,23 @@
+-- A note is standalone in a workspace or attached to a project or task, never more than one of them
+CREATE TABLE IF NOT EXISTS note (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    workspace_id VARCHAR(36) REFERENCES workspace(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) REFERENCES project(id) ON DELETE CASCADE,
+    task_id VARCHAR(36) REFERENCES task(id) ON DELETE CASCADE,
+    title VARCHAR(128) NOT NULL,
+    body TEXT NOT NULL,
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL,
+    CHECK ((workspace_id IS NOT NULL) + (project_id IS NOT NULL) + (task_id IS NOT NULL) = 1)
+);
+
+CREATE INDEX IF NOT EXISTS note_workspace_id ON note(workspace_id);
+CREATE INDEX IF NOT EXISTS note_project_id ON note(project_id);
+CREATE INDEX IF NOT EXISTS note_task_id ON note(task_id);
+
+-- Deleting a workspace would delete its standalone notes, only empty workspaces can be deleted
+CREATE TRIGGER IF NOT EXISTS workspace_delete_note_guard BEFORE DELETE ON workspace
+WHEN EXISTS (SELECT 1 FROM note WHERE workspace_id = OLD.id)
+BEGIN
+    SELECT RAISE(ABORT, 'workspace not empty');
+END;
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS comment (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    author_id VARCHAR(36) NOT NULL REFERENCES user(id),
+    body TEXT NOT NULL,
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS comment_task_id ON comment(task_id, created);
+CREATE INDEX IF NOT EXISTS comment_author_id ON comment(author_id);
+
+CREATE TABLE IF NOT EXISTS comment_revision (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    comment_id VARCHAR(36) NOT NULL REFERENCES comment(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    revised TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS comment_revision_comment_id ON comment_revision(comment_id, revised);
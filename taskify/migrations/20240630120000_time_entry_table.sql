@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS time_entry (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    author_id VARCHAR(36) NOT NULL REFERENCES user(id),
+    start TEXT NOT NULL,
+    "end" TEXT CHECK ("end" IS NULL OR "end" >= start),
+    note TEXT NOT NULL,
+    created TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS time_entry_task_id ON time_entry(task_id);
+CREATE INDEX IF NOT EXISTS time_entry_start ON time_entry(start);
+
+-- An author can only have one running timer
+CREATE UNIQUE INDEX IF NOT EXISTS time_entry_running ON time_entry(author_id) WHERE "end" IS NULL;
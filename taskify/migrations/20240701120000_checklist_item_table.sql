@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS checklist_item (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    text TEXT NOT NULL,
+    checked BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL,
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS checklist_item_task_id ON checklist_item(task_id, position);
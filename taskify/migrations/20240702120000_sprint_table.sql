@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS sprint (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    project_id VARCHAR(36) NOT NULL REFERENCES project(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    goal TEXT NOT NULL,
+    start TEXT NOT NULL,
+    "end" TEXT NOT NULL CHECK ("end" > start),
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sprint_project_id ON sprint(project_id, start);
+
+ALTER TABLE task ADD COLUMN sprint_id VARCHAR(36) REFERENCES sprint(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS task_sprint_id ON task(sprint_id);
+
+-- A task can only be planned into a sprint of its own project
+CREATE TRIGGER IF NOT EXISTS task_sprint_project_insert BEFORE INSERT ON task
+WHEN NEW.sprint_id IS NOT NULL
+    AND NEW.project_id IS NOT (SELECT project_id FROM sprint WHERE id = NEW.sprint_id)
+BEGIN
+    SELECT RAISE(ABORT, 'task sprint project mismatch');
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_sprint_project_update BEFORE UPDATE OF sprint_id, project_id ON task
+WHEN NEW.sprint_id IS NOT NULL
+    AND NEW.project_id IS NOT (SELECT project_id FROM sprint WHERE id = NEW.sprint_id)
+BEGIN
+    SELECT RAISE(ABORT, 'task sprint project mismatch');
+END;
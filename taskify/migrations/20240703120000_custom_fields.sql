@@ -0,0 +1,33 @@
+CREATE TABLE IF NOT EXISTS custom_field (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    project_id VARCHAR(36) NOT NULL REFERENCES project(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    kind VARCHAR(8) NOT NULL CHECK (kind IN ('text', 'number', 'date', 'enum', 'bool')),
+    choices TEXT NOT NULL DEFAULT '',
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL,
+    UNIQUE (project_id, name)
+);
+
+-- One typed column per field kind, enum values are stored as text
+CREATE TABLE IF NOT EXISTS custom_value (
+    field_id VARCHAR(36) NOT NULL REFERENCES custom_field(id) ON DELETE CASCADE,
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    text_value TEXT,
+    number_value REAL,
+    date_value TEXT,
+    bool_value BOOLEAN,
+    PRIMARY KEY (field_id, task_id)
+);
+
+CREATE INDEX IF NOT EXISTS custom_value_task_id ON custom_value(task_id);
+
+-- The value of a custom field on the project itself, e.g. the customer of the whole project. A field belongs to one
+-- project, so the field id is enough to key the value.
+CREATE TABLE IF NOT EXISTS project_custom_value (
+    field_id VARCHAR(36) NOT NULL PRIMARY KEY REFERENCES custom_field(id) ON DELETE CASCADE,
+    text_value TEXT,
+    number_value REAL,
+    date_value TEXT,
+    bool_value BOOLEAN
+);
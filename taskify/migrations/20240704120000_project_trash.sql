@@ -0,0 +1,3 @@
+ALTER TABLE project ADD COLUMN archived TEXT;
+ALTER TABLE project ADD COLUMN deleted TEXT;
+CREATE INDEX IF NOT EXISTS project_deleted ON project (deleted);
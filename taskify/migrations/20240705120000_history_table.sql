@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    entity VARCHAR(32) NOT NULL,
+    entity_id VARCHAR(36) NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('update', 'delete', 'purge')),
+    field VARCHAR(32) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    author_id VARCHAR(36) NOT NULL REFERENCES user(id),
+    changed TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS history_entity ON history(entity, entity_id, changed);
+CREATE INDEX IF NOT EXISTS history_author_id ON history(author_id);
+
+-- The history is append-only, it outlives the entities it records
+CREATE TRIGGER IF NOT EXISTS history_no_update BEFORE UPDATE ON history
+BEGIN
+    SELECT RAISE(ABORT, 'history is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_no_delete BEFORE DELETE ON history
+BEGIN
+    SELECT RAISE(ABORT, 'history is append-only');
+END;
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS journal (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    entity VARCHAR(32) NOT NULL,
+    entity_id VARCHAR(36) NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('insert', 'update', 'delete', 'merge')),
+    before TEXT,
+    after TEXT,
+    author_id VARCHAR(36) NOT NULL REFERENCES user(id),
+    undone BOOLEAN NOT NULL DEFAULT FALSE,
+    created TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS journal_entity ON journal(entity, entity_id);
+CREATE INDEX IF NOT EXISTS journal_author_id ON journal(author_id);
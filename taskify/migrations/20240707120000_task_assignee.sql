@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS task_assignee (
+    task_id VARCHAR(36) NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    user_id VARCHAR(36) NOT NULL REFERENCES user(id) ON DELETE CASCADE,
+    PRIMARY KEY (task_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS task_assignee_user_id ON task_assignee(user_id);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS template (
+    id VARCHAR(36) UNIQUE NOT NULL PRIMARY KEY,
+    name VARCHAR(64) UNIQUE NOT NULL,
+    definition TEXT NOT NULL,
+    created TEXT NOT NULL,
+    modified TEXT NOT NULL
+);
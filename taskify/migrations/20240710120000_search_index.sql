@@ -0,0 +1,75 @@
+-- One full-text index for every searchable entity, so hits of all kinds can be ranked against each other
+CREATE VIRTUAL TABLE IF NOT EXISTS search USING fts5(
+    entity UNINDEXED,
+    entity_id UNINDEXED,
+    title,
+    body,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO search (entity, entity_id, title, body)
+SELECT 'project', id, name, description FROM project;
+
+INSERT INTO search (entity, entity_id, title, body)
+SELECT 'task', id, COALESCE(title, ''), COALESCE(body, '') FROM task;
+
+INSERT INTO search (entity, entity_id, title, body)
+SELECT 'note', id, title, body FROM note;
+
+-- Projects
+CREATE TRIGGER IF NOT EXISTS project_search_insert AFTER INSERT ON project
+BEGIN
+    INSERT INTO search (entity, entity_id, title, body)
+    VALUES ('project', NEW.id, NEW.name, NEW.description);
+END;
+
+-- The entity updates write every column, unchanged text is not indexed again
+CREATE TRIGGER IF NOT EXISTS project_search_update AFTER UPDATE OF name, description ON project
+WHEN OLD.name IS NOT NEW.name OR OLD.description IS NOT NEW.description
+BEGIN
+    UPDATE search SET title = NEW.name, body = NEW.description
+    WHERE entity = 'project' AND entity_id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_search_delete AFTER DELETE ON project
+BEGIN
+    DELETE FROM search WHERE entity = 'project' AND entity_id = OLD.id;
+END;
+
+-- Tasks, the title & body can be NULL
+CREATE TRIGGER IF NOT EXISTS task_search_insert AFTER INSERT ON task
+BEGIN
+    INSERT INTO search (entity, entity_id, title, body)
+    VALUES ('task', NEW.id, COALESCE(NEW.title, ''), COALESCE(NEW.body, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_search_update AFTER UPDATE OF title, body ON task
+WHEN OLD.title IS NOT NEW.title OR OLD.body IS NOT NEW.body
+BEGIN
+    UPDATE search SET title = COALESCE(NEW.title, ''), body = COALESCE(NEW.body, '')
+    WHERE entity = 'task' AND entity_id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_search_delete AFTER DELETE ON task
+BEGIN
+    DELETE FROM search WHERE entity = 'task' AND entity_id = OLD.id;
+END;
+
+-- Notes
+CREATE TRIGGER IF NOT EXISTS note_search_insert AFTER INSERT ON note
+BEGIN
+    INSERT INTO search (entity, entity_id, title, body)
+    VALUES ('note', NEW.id, NEW.title, NEW.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS note_search_update AFTER UPDATE OF title, body ON note
+WHEN OLD.title IS NOT NEW.title OR OLD.body IS NOT NEW.body
+BEGIN
+    UPDATE search SET title = NEW.title, body = NEW.body
+    WHERE entity = 'note' AND entity_id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS note_search_delete AFTER DELETE ON note
+BEGIN
+    DELETE FROM search WHERE entity = 'note' AND entity_id = OLD.id;
+END;
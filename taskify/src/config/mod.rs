@@ -8,11 +8,15 @@
 //! # }
 //! ```
 
+use std::collections::BTreeMap;
 use std::fs::{self, File};
+use std::path::Path;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use simplelog::{CombinedLogger, SharedLogger, TermLogger, WriteLogger};
-use sqlx::{SqliteConnection, Connection};
+use sqlx::{Connection, SqliteConnection};
+
+use crate::db::DbError;
 
 /// The database configuration schema & structure.
 #[derive(Deserialize, Debug)]
@@ -21,6 +25,23 @@ pub struct Database {
     pub path: String,
 }
 
+impl Database {
+    /// Resolves a relative database path against `dir`, absolute paths & in-memory databases are kept as they are
+    ///
+    /// # Examples:
+    /// ```
+    /// # use taskify::config::Database;
+    /// let mut database = Database { path: "./taskify.db".into() };
+    /// database.resolve("../");
+    /// assert_eq!(database.path, "../taskify.db");
+    /// ```
+    pub fn resolve(&mut self, dir: impl AsRef<Path>) {
+        if self.path != ":memory:" {
+            self.path = resolve_path(&self.path, dir);
+        }
+    }
+}
+
 /// The logger configuration schema & structure.
 #[derive(Deserialize, Debug)]
 pub struct Logger {
@@ -37,16 +58,245 @@ pub struct Logger {
     pub log_path: String,
 }
 
+/// The task status workflow configuration schema & structure.
+///
+/// A workflow lists the states a task can be in and which states each of them may move to. The workflow in the
+/// config.toml is the default for every project, projects can override it with a
+/// [`ProjectWorkflow`](crate::db::workflows::ProjectWorkflow).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Workflow {
+    /// All the states a task can be in.
+    pub states: Vec<String>,
+
+    /// The state new tasks start in.
+    pub initial: String,
+
+    /// The states that count as completed.
+    pub completed: Vec<String>,
+
+    /// The states each state is allowed to transition to.
+    pub transitions: BTreeMap<String, Vec<String>>,
+}
+
+impl Workflow {
+    /// Is `state` part of the workflow?
+    pub fn is_state(&self, state: &str) -> bool {
+        self.states.iter().any(|s| s == state)
+    }
+
+    /// Does `state` count as completed?
+    pub fn is_completed(&self, state: &str) -> bool {
+        self.completed.iter().any(|s| s == state)
+    }
+
+    /// Is a task allowed to move from the `from` state to the `to` state?
+    pub fn can_transition(&self, from: &str, to: &str) -> bool {
+        self.transitions
+            .get(from)
+            .is_some_and(|targets| targets.iter().any(|s| s == to))
+    }
+
+    /// Checks that the initial, completed & transition states are all part of the workflow
+    ///
+    /// Returns [`DbError::UnknownWorkflowState`] naming the first state that is not.
+    pub fn validate(&self) -> Result<(), DbError> {
+        let transitions = self
+            .transitions
+            .iter()
+            .flat_map(|(from, targets)| std::iter::once(from).chain(targets));
+
+        match std::iter::once(&self.initial)
+            .chain(&self.completed)
+            .chain(transitions)
+            .find(|state| !self.is_state(state))
+        {
+            Some(state) => Err(DbError::UnknownWorkflowState(state.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Workflow {
+    /// The built-in workflow: todo → in-progress → review → done, plus blocked & cancelled
+    fn default() -> Self {
+        let transitions = [
+            ("todo", vec!["in-progress", "blocked", "cancelled"]),
+            (
+                "in-progress",
+                vec!["todo", "review", "blocked", "cancelled"],
+            ),
+            ("review", vec!["in-progress", "done", "cancelled"]),
+            ("done", vec!["todo"]),
+            ("blocked", vec!["todo", "in-progress", "cancelled"]),
+            ("cancelled", vec!["todo"]),
+        ];
+
+        Self {
+            states: transitions.iter().map(|(s, _)| s.to_string()).collect(),
+            initial: crate::db::tasks::DEFAULT_STATUS.into(),
+            completed: vec!["done".into()],
+            transitions: transitions
+                .into_iter()
+                .map(|(s, t)| (s.into(), t.into_iter().map(String::from).collect()))
+                .collect(),
+        }
+    }
+}
+
+/// The task urgency configuration schema & structure.
+///
+/// Tasks are ranked by a weighted sum of their priority, how close they are to being due and their age, similar to
+/// Taskwarrior's urgency. Each field is the weight of one term, a weight of `0` ignores the term.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Urgency {
+    /// The weight of the task priority.
+    pub priority: f64,
+
+    /// The weight of the due date urgency.
+    pub due: f64,
+
+    /// The weight of the task age.
+    pub age: f64,
+
+    /// The age in days after which a task's age stops adding urgency, must be greater than `0`.
+    pub age_max_days: f64,
+}
+
+impl Urgency {
+    /// Checks that the age term can be computed
+    ///
+    /// Returns [`DbError::InvalidUrgency`] if `age_max_days` is not greater than `0`, the task age is divided by it.
+    pub fn validate(&self) -> Result<(), DbError> {
+        match self.age_max_days > 0.0 {
+            true => Ok(()),
+            false => Err(DbError::InvalidUrgency(self.age_max_days)),
+        }
+    }
+}
+
+impl Default for Urgency {
+    /// The Taskwarrior-like defaults, due dates weigh the most
+    fn default() -> Self {
+        Self {
+            priority: 6.0,
+            due: 12.0,
+            age: 2.0,
+            age_max_days: 365.0,
+        }
+    }
+}
+
+/// The trash bin configuration schema & structure.
+///
+/// Deleted projects stay in the trash bin, where they can be restored, until they are purged.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Trash {
+    /// How many days deleted projects are kept before a purge removes them for good, must not be negative.
+    pub retention_days: i64,
+}
+
+impl Trash {
+    /// Checks that the retention period is not negative
+    ///
+    /// Returns [`DbError::InvalidRetention`] if `retention_days` is negative, a purge would then delete every project in
+    /// the trash bin.
+    pub fn validate(&self) -> Result<(), DbError> {
+        match self.retention_days >= 0 {
+            true => Ok(()),
+            false => Err(DbError::InvalidRetention(self.retention_days)),
+        }
+    }
+}
+
+impl Default for Trash {
+    /// Keep deleted projects for 30 days
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+/// The workspace configuration schema & structure.
+///
+/// List queries are scoped to the current workspace, a database can hold e.g. a personal and a team workspace.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Workspace {
+    /// The name of the workspace used by default.
+    pub current: String,
+}
+
+impl Default for Workspace {
+    /// Use the workspace the migrations create
+    fn default() -> Self {
+        Self {
+            current: "default".into(),
+        }
+    }
+}
+
+/// The template configuration schema & structure.
+///
+/// Besides the templates stored in the database, every `*.toml` file in the template directory is a
+/// [`ProjectTemplate`](crate::db::templates::ProjectTemplate) named after the file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Templates {
+    /// The directory the template files are kept in.
+    pub path: String,
+}
+
+impl Templates {
+    /// Resolves a relative template path against `dir`, absolute paths are kept as they are
+    ///
+    /// # Examples:
+    /// ```
+    /// # use taskify::config::Templates;
+    /// let mut templates = Templates::default();
+    /// templates.resolve("../");
+    /// assert_eq!(templates.path, "../templates");
+    /// ```
+    pub fn resolve(&mut self, dir: impl AsRef<Path>) {
+        self.path = resolve_path(&self.path, dir);
+    }
+}
+
+impl Default for Templates {
+    /// Keep the templates next to the config.toml
+    fn default() -> Self {
+        Self {
+            path: "./templates".into(),
+        }
+    }
+}
+
 /// The configuration structure & schema that it used for the config.toml
 #[derive(Deserialize, Debug)]
 pub struct Config {
     pub database: Database,
     pub logger: Logger,
+    #[serde(default)]
+    pub workflow: Workflow,
+    #[serde(default)]
+    pub urgency: Urgency,
+    #[serde(default)]
+    pub trash: Trash,
+    #[serde(default)]
+    pub workspace: Workspace,
+    #[serde(default)]
+    pub templates: Templates,
 }
 
 impl Config {
     /// Loads the config.toml and parses it into a [`Config`]
     ///
+    /// Fails if the workflow names a state it doesn't list, see [`Workflow::validate`], if the urgency can't be
+    /// computed, see [`Urgency::validate`], or if the trash retention is negative, see [`Trash::validate`].
+    /// The config.toml is looked up in the working directory first and then in its parent, so both the repository
+    /// root and the crate directories can be used as the working directory. The database & template paths are
+    /// relative to the directory the config.toml was loaded from.
+    ///
     /// # Examples:
     /// ```
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -56,13 +306,58 @@ impl Config {
     /// # }
     /// ```
     pub fn load() -> Result<Config, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string("./config.toml");
+        let content = fs::read_to_string("./config.toml")
+            .map(|content| (content, "./"))
+            .or_else(|_| fs::read_to_string("../config.toml").map(|content| (content, "../")));
 
         match content {
-            Ok(content) => Ok(toml::from_str(&content).unwrap()),
+            Ok((content, dir)) => Config::parse(&content, dir),
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Parses the content of a config.toml into a [`Config`]
+    ///
+    /// Fails if the content isn't a valid config.toml or the config doesn't validate, see [`Config::load`].
+    /// # Arguments
+    /// * `content` - The content of the config.toml
+    /// * `dir` - The directory the config.toml was loaded from, the database & template paths are relative to it
+    ///
+    /// # Examples:
+    /// ```
+    /// # use taskify::config::Config;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = Config::parse("[database]\npath = \"./taskify.db\"\n[logger]\nenabled = false\nwrite_logs = false\nprint_logs = false\nlog_path = \"./taskify.log\"", "./")?;
+    /// assert_eq!(config.database.path, "./taskify.db");
+    /// assert!(Config::parse("[database", "./").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(
+        content: &str,
+        dir: impl AsRef<Path>,
+    ) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut config: Config = toml::from_str(content)?;
+        config.workflow.validate()?;
+        config.urgency.validate()?;
+        config.trash.validate()?;
+        config.database.resolve(&dir);
+        config.templates.resolve(&dir);
+        Ok(config)
+    }
+}
+
+/// Joins a relative `path` to `dir`, absolute paths are kept as they are
+fn resolve_path(path: &str, dir: impl AsRef<Path>) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_string_lossy().into_owned();
+    }
+
+    dir.as_ref()
+        .join(path.strip_prefix("./").unwrap_or(path))
+        .to_string_lossy()
+        .into_owned()
 }
 
 /// Initialized the logger using the [`Logger`]
@@ -138,4 +433,4 @@ pub async fn init_memory_db() -> Result<SqliteConnection, sqlx::Error> {
     sqlx::migrate!("./migrations/").run(&mut db).await?;
 
     Ok(db)
-}
\ No newline at end of file
+}
@@ -0,0 +1,486 @@
+//! Checklist Item Database Entity
+//! This file contains the checklist item database entity, the ordered acceptance checklist of a task. Items are
+//! lighter than subtasks, they have no status, dates or workflow, only a text and a checkbox.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Func, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::{DbError, Entity, Repository};
+
+/// The checklist item database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct ChecklistItem {
+    id: Uuid,
+    task_id: Uuid,
+    text: String,
+    checked: bool,
+    position: i64,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+impl ChecklistItem {
+    /// New Checklist Item
+    ///
+    /// Create a new unchecked checklist item entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `task_id` - The id of the task the item belongs to
+    /// * `text` - The item text
+    pub fn new(task_id: Uuid, text: String) -> Self {
+        Self {
+            task_id,
+            text,
+            ..Default::default()
+        }
+    }
+
+    /// List the Checklist of a Task in the DB
+    ///
+    /// Lists the items of a task in checklist order.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<ChecklistItem>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = ChecklistItem::list_task_query(task_id);
+        sqlx::query_as_with::<_, ChecklistItem, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Reorders the Checklist of a Task on DB
+    ///
+    /// Moves the items to the order of `item_ids` atomically. Fails with [`DbError::ChecklistMismatch`] unless
+    /// `item_ids` lists every item of the checklist exactly once and nothing else.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `item_ids` - The ids of the items in their new order
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn reorder_db<'c, A>(task_id: Uuid, item_ids: &[Uuid], conn: A) -> Result<(), DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+
+        let mut current: Vec<Uuid> = ChecklistItem::from_task_list_db(task_id, &mut *tx)
+            .await?
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        let mut requested = item_ids.to_vec();
+        current.sort();
+        requested.sort();
+        if current != requested {
+            return Err(DbError::ChecklistMismatch);
+        }
+
+        for (position, item_id) in item_ids.iter().enumerate() {
+            let query = ChecklistItem::position_query(task_id, *item_id, position as i64);
+            sqlx::query_with(&query.0, query.1)
+                .execute(&mut *tx)
+                .await?;
+        }
+        Ok(tx.commit().await?)
+    }
+
+    /// Checks or unchecks every Checklist Item of a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `checked` - Whether the items should be checked
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn check_all_db<'c, E>(
+        task_id: Uuid,
+        checked: bool,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = ChecklistItem::check_all_query(task_id, checked, Utc::now());
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Get Checklist Item ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Checklist Item Task ID
+    pub fn task_id(&self) -> &Uuid {
+        &self.task_id
+    }
+
+    /// Get Checklist Item Text
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+
+    /// Is the Checklist Item checked?
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Get Checklist Item Position, items are ordered by ascending position
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the checklist item.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the checklist item `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Text
+    ///
+    /// Sets the item text
+    /// # Arguments
+    /// * `text` - The string text to use
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.edited();
+    }
+
+    /// Toggle
+    ///
+    /// Checks the item if it's unchecked and unchecks it otherwise
+    pub fn toggle(&mut self) {
+        self.checked = !self.checked;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Appends the Checklist Item to the end of its Task's checklist on DB
+    ///
+    /// # Arguments
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn append_db<'c, A>(&mut self, conn: A) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let query = ChecklistItem::next_position_query(self.task_id);
+
+        let mut tx = conn.begin().await?;
+        let position: Option<i64> = sqlx::query_scalar_with(&query.0, query.1)
+            .fetch_one(&mut *tx)
+            .await?;
+        let mut item = self.clone();
+        item.position = position.unwrap_or_default();
+        let result = item.insert(&mut *tx).await?;
+        tx.commit().await?;
+
+        *self = item;
+        Ok(result)
+    }
+
+    /// Toggles a Checklist Item on DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn toggle_db<'c, E>(&mut self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let mut item = self.clone();
+        item.toggle();
+
+        let result = item.update(executor).await?;
+        *self = item;
+        Ok(result)
+    }
+
+    /// Generates a sqlx query to List the Checklist of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn list_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        ChecklistItem::select()
+            .and_where(Expr::col(ChecklistItemIden::TaskId).eq(task_id))
+            .order_by(ChecklistItemIden::Position, Order::Asc)
+            .order_by(ChecklistItemIden::Created, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the position after the last Checklist Item of a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn next_position_query(task_id: Uuid) -> (String, SqlxValues) {
+        Query::select()
+            .expr(Func::coalesce([
+                Expr::expr(Func::max(Expr::col(ChecklistItemIden::Position))).add(1),
+                Expr::val(0).into(),
+            ]))
+            .from(ChecklistItemIden::Table)
+            .and_where(Expr::col(ChecklistItemIden::TaskId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Move a Checklist Item of a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `item_id` - The id of the item
+    /// * `position` - The new position of the item
+    pub fn position_query(task_id: Uuid, item_id: Uuid, position: i64) -> (String, SqlxValues) {
+        Query::update()
+            .table(ChecklistItemIden::Table)
+            .values([(ChecklistItemIden::Position, position.into())])
+            .and_where(Expr::col(ChecklistItemIden::Id).eq(item_id))
+            .and_where(Expr::col(ChecklistItemIden::TaskId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Check or uncheck every Checklist Item of a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `checked` - Whether the items should be checked
+    /// * `modified` - The modified datetime of the changed items
+    pub fn check_all_query(
+        task_id: Uuid,
+        checked: bool,
+        modified: DateTime<Utc>,
+    ) -> (String, SqlxValues) {
+        Query::update()
+            .table(ChecklistItemIden::Table)
+            .values([
+                (ChecklistItemIden::Checked, checked.into()),
+                (ChecklistItemIden::Modified, modified.into()),
+            ])
+            .and_where(Expr::col(ChecklistItemIden::TaskId).eq(task_id))
+            .and_where(Expr::col(ChecklistItemIden::Checked).ne(checked))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the checklist item edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for ChecklistItem {
+    type Iden = ChecklistItemIden;
+    type Key = Uuid;
+
+    const TABLE: ChecklistItemIden = ChecklistItemIden::Table;
+    const KEY: &'static [ChecklistItemIden] = &[ChecklistItemIden::Id];
+    const CREATED: Option<ChecklistItemIden> = Some(ChecklistItemIden::Created);
+    const COLUMNS: &'static [ChecklistItemIden] = &[
+        ChecklistItemIden::Id,
+        ChecklistItemIden::TaskId,
+        ChecklistItemIden::Text,
+        ChecklistItemIden::Checked,
+        ChecklistItemIden::Position,
+        ChecklistItemIden::Created,
+        ChecklistItemIden::Modified,
+    ];
+    const ORDER_BY: Option<ChecklistItemIden> = Some(ChecklistItemIden::Position);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.task_id.into(),
+            self.text.clone().into(),
+            self.checked.into(),
+            self.position.into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a checklist item leaves the positions of the other items as they are
+impl Repository for ChecklistItem {}
+
+#[cfg(test)]
+mod tests {
+    use super::ChecklistItem;
+    use crate::config::init_memory_db;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_task(conn: &mut SqliteConnection) -> Task {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut *conn).await.unwrap();
+
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn append(task: &Task, text: &str, conn: &mut SqliteConnection) -> ChecklistItem {
+        let mut item = ChecklistItem::new(*task.id(), text.into());
+        item.assign_id().assign_created();
+        item.append_db(conn).await.unwrap();
+        item
+    }
+
+    async fn texts(task: &Task, conn: &mut SqliteConnection) -> Vec<String> {
+        ChecklistItem::from_task_list_db(*task.id(), conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|item| item.text().clone())
+            .collect()
+    }
+
+    #[test]
+    fn insert() {
+        let query = ChecklistItem::new(Uuid::default(), "TEXT".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"checklist_item\" (\"id\", \"task_id\", \"text\", \"checked\", \"position\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn next_position() {
+        let query = ChecklistItem::next_position_query(Uuid::default());
+
+        assert_eq!(query.0, "SELECT COALESCE(MAX(\"position\") + ?, ?) FROM \"checklist_item\" WHERE \"task_id\" = ?");
+    }
+
+    #[tokio::test]
+    async fn append_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+
+        let first = append(&task, "first", &mut conn).await;
+        let second = append(&task, "second", &mut conn).await;
+        assert_eq!((first.position(), second.position()), (0, 1));
+        assert_eq!(texts(&task, &mut conn).await, ["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn reorder_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+        let a = append(&task, "a", &mut conn).await;
+        let b = append(&task, "b", &mut conn).await;
+        let c = append(&task, "c", &mut conn).await;
+
+        ChecklistItem::reorder_db(*task.id(), &[*c.id(), *a.id(), *b.id()], &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(texts(&task, &mut conn).await, ["c", "a", "b"]);
+
+        ChecklistItem::reorder_db(*task.id(), &[*b.id(), *c.id(), *a.id()], &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(texts(&task, &mut conn).await, ["b", "c", "a"]);
+    }
+
+    #[tokio::test]
+    async fn reorder_rejected_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+        let a = append(&task, "a", &mut conn).await;
+        let b = append(&task, "b", &mut conn).await;
+        let other = create_task(&mut conn).await;
+        let c = append(&other, "c", &mut conn).await;
+
+        let rejected = [
+            // missing item
+            vec![*b.id()],
+            // duplicate item
+            vec![*b.id(), *a.id(), *a.id()],
+            // item of another task
+            vec![*c.id(), *b.id(), *a.id()],
+            // unknown item
+            vec![*b.id(), *a.id(), Uuid::new_v4()],
+        ];
+        for item_ids in rejected {
+            assert!(matches!(
+                ChecklistItem::reorder_db(*task.id(), &item_ids, &mut conn).await,
+                Err(DbError::ChecklistMismatch)
+            ));
+        }
+
+        // nothing moved
+        assert_eq!(texts(&task, &mut conn).await, ["a", "b"]);
+        assert_eq!(
+            ChecklistItem::from_db(*c.id(), &mut conn)
+                .await
+                .unwrap()
+                .position(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn check_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+        let mut a = append(&task, "a", &mut conn).await;
+        append(&task, "b", &mut conn).await;
+
+        a.toggle_db(&mut conn).await.unwrap();
+        assert!(a.checked());
+        assert_eq!(ChecklistItem::from_db(*a.id(), &mut conn).await.unwrap(), a);
+
+        let result = ChecklistItem::check_all_db(*task.id(), true, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), 1);
+        let items = ChecklistItem::from_task_list_db(*task.id(), &mut conn)
+            .await
+            .unwrap();
+        assert!(items.iter().all(ChecklistItem::checked));
+
+        ChecklistItem::check_all_db(*task.id(), false, &mut conn)
+            .await
+            .unwrap();
+        let items = ChecklistItem::from_task_list_db(*task.id(), &mut conn)
+            .await
+            .unwrap();
+        assert!(!items.iter().any(ChecklistItem::checked));
+    }
+}
@@ -0,0 +1,544 @@
+//! Comment Database Entity
+//! This file contains the comment database entity, the discussion thread of a task. Editing a comment keeps the
+//! replaced body as a [`CommentRevision`], so the edit history of every comment is preserved.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Alias, Expr, Func, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::filter::ListSpec;
+use crate::db::{Entity, Repository};
+
+/// The comment database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Comment {
+    id: Uuid,
+    task_id: Uuid,
+    author_id: Uuid,
+    body: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// A previous body of a comment, recorded when the comment is edited
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct CommentRevision {
+    id: Uuid,
+    comment_id: Uuid,
+    body: String,
+    revised: DateTime<Utc>,
+}
+
+/// The number of comments on a task, as returned by [`Comment::from_count_list_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct CommentCount {
+    pub task_id: Uuid,
+    pub comments: i64,
+}
+
+impl Comment {
+    /// New Comment
+    ///
+    /// Create a new comment entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `task_id` - The id of the task the comment is on
+    /// * `author_id` - The id of the [`User`](super::users::User) that writes the comment
+    /// * `body` - The comment body
+    pub fn new(task_id: Uuid, author_id: Uuid, body: String) -> Self {
+        Self {
+            task_id,
+            author_id,
+            body,
+            ..Default::default()
+        }
+    }
+
+    /// List the Comments of a Task in the DB
+    ///
+    /// Lists the comments of a task matching the spec in chronological order, unless it sorts them.
+    /// # Arguements
+    /// * `task_id` - The id of the task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(
+        task_id: Uuid,
+        spec: &ListSpec<CommentIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Comment>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Comment::list_task_query(task_id, spec, page, limit);
+        sqlx::query_as_with::<_, Comment, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Count the Comments of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn count_task_db<'c, E>(task_id: Uuid, executor: E) -> Result<i64, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Comment::count_task_query(task_id);
+        sqlx::query_scalar_with(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Count the Comments of many Tasks in the DB
+    ///
+    /// Tasks without comments are left out of the result.
+    /// # Arguments
+    /// * `task_ids` - The ids of the tasks
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_count_list_db<'c, E>(
+        task_ids: &[Uuid],
+        executor: E,
+    ) -> Result<Vec<CommentCount>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Comment::count_list_query(task_ids);
+        sqlx::query_as_with::<_, CommentCount, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Comment ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Comment Task ID
+    pub fn task_id(&self) -> &Uuid {
+        &self.task_id
+    }
+
+    /// Get Comment Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Get Comment Body
+    pub fn body(&self) -> &String {
+        &self.body
+    }
+
+    /// Get Comment Created Datetime
+    pub fn created(&self) -> &DateTime<Utc> {
+        &self.created
+    }
+
+    /// Get Comment Modified Datetime
+    pub fn modified(&self) -> &DateTime<Utc> {
+        &self.modified
+    }
+
+    /// Was the Comment edited since it was posted?
+    pub fn is_edited(&self) -> bool {
+        self.modified != self.created
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the comment.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the comment `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Edit
+    ///
+    /// Replaces the comment body, returning the revision that keeps the replaced body.
+    /// # Arguments
+    /// * `body` - The new comment body
+    pub fn edit(&mut self, body: String) -> CommentRevision {
+        let revision = CommentRevision::new(self.id, std::mem::replace(&mut self.body, body));
+        self.edited();
+        revision
+    }
+
+    // Database Interactions
+
+    /// Edits a Comment on DB
+    ///
+    /// Updates the comment & records the replaced body atomically.
+    /// # Arguments
+    /// * `body` - The new comment body
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn edit_db<'c, A>(&mut self, body: String, conn: A) -> Result<CommentRevision, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut comment = self.clone();
+        let revision = comment.edit(body);
+
+        let mut tx = conn.begin().await?;
+        comment.update(&mut *tx).await?;
+        revision.insert(&mut *tx).await?;
+        tx.commit().await?;
+
+        *self = comment;
+        Ok(revision)
+    }
+
+    /// Generates a sqlx query to List the Comments of a Task in the DB
+    ///
+    /// # Arguements
+    /// * `task_id` - The id of the task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_task_query(
+        task_id: Uuid,
+        spec: &ListSpec<CommentIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Comment::list_select(spec)
+            .and_where(Expr::col(CommentIden::TaskId).eq(task_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Count the Comments of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn count_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        Query::select()
+            .expr(Func::count(Expr::col(CommentIden::Id)))
+            .from(CommentIden::Table)
+            .and_where(Expr::col(CommentIden::TaskId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Count the Comments of many Tasks in the DB
+    ///
+    /// # Arguments
+    /// * `task_ids` - The ids of the tasks
+    pub fn count_list_query(task_ids: &[Uuid]) -> (String, SqlxValues) {
+        Query::select()
+            .column(CommentIden::TaskId)
+            .expr_as(
+                Func::count(Expr::col(CommentIden::Id)),
+                Alias::new("comments"),
+            )
+            .from(CommentIden::Table)
+            .and_where(Expr::col(CommentIden::TaskId).is_in(task_ids.iter().copied()))
+            .group_by_col(CommentIden::TaskId)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the comment edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl CommentRevision {
+    /// New Comment Revision
+    ///
+    /// Create a new comment revision entity with a random id, revised at the current datetime.
+    /// # Arguments:
+    /// * `comment_id` - The id of the edited comment
+    /// * `body` - The body the comment had before the edit
+    pub fn new(comment_id: Uuid, body: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            comment_id,
+            body,
+            revised: Utc::now(),
+        }
+    }
+
+    /// List all Revisions of a Comment in the DB
+    ///
+    /// Lists the revisions of a comment in chronological order, the first one holds the originally posted body.
+    /// # Arguments
+    /// * `comment_id` - The id of the comment
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_comment_list_db<'c, E>(
+        comment_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<CommentRevision>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = CommentRevision::list_comment_query(comment_id);
+        sqlx::query_as_with::<_, CommentRevision, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Revision Comment ID
+    pub fn comment_id(&self) -> &Uuid {
+        &self.comment_id
+    }
+
+    /// Get the Body the Comment had before the Revision
+    pub fn body(&self) -> &String {
+        &self.body
+    }
+
+    /// Get Revision Datetime
+    pub fn revised(&self) -> &DateTime<Utc> {
+        &self.revised
+    }
+
+    // Database Interactions
+
+    /// Generates a sqlx query to List all Revisions of a Comment in the DB
+    ///
+    /// # Arguments
+    /// * `comment_id` - The id of the comment
+    pub fn list_comment_query(comment_id: Uuid) -> (String, SqlxValues) {
+        CommentRevision::select()
+            .and_where(Expr::col(CommentRevisionIden::CommentId).eq(comment_id))
+            .order_by(CommentRevisionIden::Revised, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+impl Entity for Comment {
+    type Iden = CommentIden;
+    type Key = Uuid;
+
+    const TABLE: CommentIden = CommentIden::Table;
+    const KEY: &'static [CommentIden] = &[CommentIden::Id];
+    const CREATED: Option<CommentIden> = Some(CommentIden::Created);
+    const COLUMNS: &'static [CommentIden] = &[
+        CommentIden::Id,
+        CommentIden::TaskId,
+        CommentIden::AuthorId,
+        CommentIden::Body,
+        CommentIden::Created,
+        CommentIden::Modified,
+    ];
+    const ORDER_BY: Option<CommentIden> = Some(CommentIden::Created);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.task_id.into(),
+            self.author_id.into(),
+            self.body.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Use [`Comment::edit_db`] to change the body, [`Repository::update`] doesn't record a revision
+impl Repository for Comment {}
+
+impl Entity for CommentRevision {
+    type Iden = CommentRevisionIden;
+    type Key = Uuid;
+
+    const TABLE: CommentRevisionIden = CommentRevisionIden::Table;
+    const KEY: &'static [CommentRevisionIden] = &[CommentRevisionIden::Id];
+    const CREATED: Option<CommentRevisionIden> = Some(CommentRevisionIden::Revised);
+    const COLUMNS: &'static [CommentRevisionIden] = &[
+        CommentRevisionIden::Id,
+        CommentRevisionIden::CommentId,
+        CommentRevisionIden::Body,
+        CommentRevisionIden::Revised,
+    ];
+    const ORDER_BY: Option<CommentRevisionIden> = Some(CommentRevisionIden::Revised);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.comment_id.into(),
+            self.body.clone().into(),
+            self.revised.into(),
+        ]
+    }
+}
+
+/// Revisions are written by [`Comment::edit_db`] and deleted with their comment (`ON DELETE CASCADE`)
+impl Repository for CommentRevision {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Comment, CommentCount, CommentRevision};
+    use crate::config::init_memory_db;
+    use crate::db::filter::ListSpec;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{Entity, Repository};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_task(conn: &mut SqliteConnection) -> Task {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut *conn).await.unwrap();
+
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn create_comment(task: &Task, body: &str, conn: &mut SqliteConnection) -> Comment {
+        let mut comment = Comment::new(*task.id(), *task.author_id(), body.into());
+        comment.assign_id().assign_created();
+        comment.insert(conn).await.unwrap();
+        comment
+    }
+
+    #[test]
+    fn insert() {
+        let query = Comment::new(Uuid::default(), Uuid::default(), "BODY".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"comment\" (\"id\", \"task_id\", \"author_id\", \"body\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn revision_insert() {
+        let query = CommentRevision::new(Uuid::default(), "BODY".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"comment_revision\" (\"id\", \"comment_id\", \"body\", \"revised\") VALUES (?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn count_list() {
+        let query = Comment::count_list_query(&[Uuid::default()]);
+
+        assert_eq!(query.0, "SELECT \"task_id\", COUNT(\"id\") AS \"comments\" FROM \"comment\" WHERE \"task_id\" IN (?) GROUP BY \"task_id\"");
+    }
+
+    #[test]
+    fn edit() {
+        let mut comment = Comment::new(Uuid::default(), Uuid::default(), "first".into());
+        comment.assign_created();
+        assert!(!comment.is_edited());
+
+        let revision = comment.edit("second".into());
+        assert_eq!(revision.body(), "first");
+        assert_eq!(comment.body(), "second");
+        assert!(comment.is_edited());
+    }
+
+    #[tokio::test]
+    async fn thread_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+        let other = create_task(&mut conn).await;
+
+        let first = create_comment(&task, "first", &mut conn).await;
+        let second = create_comment(&task, "second", &mut conn).await;
+        let third = create_comment(&task, "third", &mut conn).await;
+        create_comment(&other, "elsewhere", &mut conn).await;
+
+        assert_eq!(
+            Comment::from_task_list_db(*task.id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            vec![first, second.clone(), third.clone()]
+        );
+        assert_eq!(
+            Comment::from_task_list_db(*task.id(), &ListSpec::new(), 1, 2, &mut conn)
+                .await
+                .unwrap(),
+            vec![third]
+        );
+        assert_eq!(
+            Comment::count_task_db(*task.id(), &mut conn).await.unwrap(),
+            3
+        );
+
+        second.delete(&mut conn).await.unwrap();
+        let empty = Uuid::new_v4();
+        let counts = Comment::from_count_list_db(&[*task.id(), *other.id(), empty], &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(counts.len(), 2);
+        assert!(counts.contains(&CommentCount {
+            task_id: *task.id(),
+            comments: 2
+        }));
+        assert!(counts.contains(&CommentCount {
+            task_id: *other.id(),
+            comments: 1
+        }));
+    }
+
+    #[tokio::test]
+    async fn edit_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let task = create_task(&mut conn).await;
+        let mut comment = create_comment(&task, "first", &mut conn).await;
+
+        comment.edit_db("second".into(), &mut conn).await.unwrap();
+        comment.edit_db("third".into(), &mut conn).await.unwrap();
+
+        assert_eq!(
+            Comment::from_db(*comment.id(), &mut conn).await.unwrap(),
+            comment
+        );
+        let history: Vec<_> = CommentRevision::from_comment_list_db(*comment.id(), &mut conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.body().clone())
+            .collect();
+        assert_eq!(history, ["first", "second"]);
+
+        comment.delete(&mut conn).await.unwrap();
+        assert!(
+            CommentRevision::from_comment_list_db(*comment.id(), &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,1015 @@
+//! Custom Field Database Entity
+//! This file contains the custom field database entity, user-defined fields like `customer` or `environment` that a
+//! [`Project`](super::projects::Project) adds to its tasks and to itself. Every field has a kind, values are validated
+//! against it and stored in a typed column so they filter & sort correctly, see
+//! [`Task::from_field_list_db`](super::tasks::Task::from_field_list_db).
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use sea_query::{
+    enum_def, Alias, Condition, Expr, JoinType, OnConflict, Order, Query, SelectStatement,
+    SimpleExpr, SqliteQueryBuilder, Value,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::tasks::{Task, TaskIden};
+use crate::db::{DbError, Entity, Repository};
+
+/// The kind of values a custom field holds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum FieldKind {
+    #[default]
+    Text,
+    Number,
+    Date,
+    /// One of the field's choices
+    Enum,
+    Bool,
+}
+
+impl FieldKind {
+    /// The name the kind is stored as
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Number => "number",
+            FieldKind::Date => "date",
+            FieldKind::Enum => "enum",
+            FieldKind::Bool => "bool",
+        }
+    }
+
+    /// The typed `custom_value` column values of this kind are stored in
+    pub fn column(&self) -> CustomValueIden {
+        match self {
+            FieldKind::Text | FieldKind::Enum => CustomValueIden::TextValue,
+            FieldKind::Number => CustomValueIden::NumberValue,
+            FieldKind::Date => CustomValueIden::DateValue,
+            FieldKind::Bool => CustomValueIden::BoolValue,
+        }
+    }
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The value of a custom field on a task
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Number(f64),
+    Date(DateTime<Utc>),
+    Enum(String),
+    Bool(bool),
+}
+
+impl FieldValue {
+    /// The kind of the value
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            FieldValue::Text(_) => FieldKind::Text,
+            FieldValue::Number(_) => FieldKind::Number,
+            FieldValue::Date(_) => FieldKind::Date,
+            FieldValue::Enum(_) => FieldKind::Enum,
+            FieldValue::Bool(_) => FieldKind::Bool,
+        }
+    }
+}
+
+impl From<FieldValue> for Value {
+    fn from(value: FieldValue) -> Self {
+        match value {
+            FieldValue::Text(text) | FieldValue::Enum(text) => text.into(),
+            FieldValue::Number(number) => number.into(),
+            FieldValue::Date(date) => date.into(),
+            FieldValue::Bool(bool) => bool.into(),
+        }
+    }
+}
+
+/// The custom field database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct CustomField {
+    id: Uuid,
+    project_id: Uuid,
+    name: String,
+    kind: FieldKind,
+    choices: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// The value of a custom field on a task as stored in the DB, one column per [`FieldKind`]
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct CustomValue {
+    pub field_id: Uuid,
+    pub task_id: Uuid,
+    pub text_value: Option<String>,
+    pub number_value: Option<f64>,
+    pub date_value: Option<DateTime<Utc>>,
+    pub bool_value: Option<bool>,
+}
+
+impl CustomValue {
+    /// New Custom Value
+    ///
+    /// # Arguments:
+    /// * `field_id` - The id of the field
+    /// * `task_id` - The id of the task
+    /// * `value` - The value, stored in the column of its kind
+    pub fn new(field_id: Uuid, task_id: Uuid, value: FieldValue) -> Self {
+        let mut stored = Self {
+            field_id,
+            task_id,
+            ..Default::default()
+        };
+        match value {
+            FieldValue::Text(text) | FieldValue::Enum(text) => stored.text_value = Some(text),
+            FieldValue::Number(number) => stored.number_value = Some(number),
+            FieldValue::Date(date) => stored.date_value = Some(date),
+            FieldValue::Bool(bool) => stored.bool_value = Some(bool),
+        }
+        stored
+    }
+
+    /// Read the stored value as a value of `kind`
+    pub fn value(&self, kind: FieldKind) -> Option<FieldValue> {
+        match kind {
+            FieldKind::Text => self.text_value.clone().map(FieldValue::Text),
+            FieldKind::Enum => self.text_value.clone().map(FieldValue::Enum),
+            FieldKind::Number => self.number_value.map(FieldValue::Number),
+            FieldKind::Date => self.date_value.map(FieldValue::Date),
+            FieldKind::Bool => self.bool_value.map(FieldValue::Bool),
+        }
+    }
+}
+
+/// The value of a custom field on the project that defines it as stored in the DB, one column per [`FieldKind`]
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct ProjectCustomValue {
+    pub field_id: Uuid,
+    pub text_value: Option<String>,
+    pub number_value: Option<f64>,
+    pub date_value: Option<DateTime<Utc>>,
+    pub bool_value: Option<bool>,
+}
+
+impl ProjectCustomValue {
+    /// New Project Custom Value
+    ///
+    /// # Arguments:
+    /// * `field_id` - The id of the field
+    /// * `value` - The value, stored in the column of its kind
+    pub fn new(field_id: Uuid, value: FieldValue) -> Self {
+        let mut stored = Self {
+            field_id,
+            ..Default::default()
+        };
+        match value {
+            FieldValue::Text(text) | FieldValue::Enum(text) => stored.text_value = Some(text),
+            FieldValue::Number(number) => stored.number_value = Some(number),
+            FieldValue::Date(date) => stored.date_value = Some(date),
+            FieldValue::Bool(bool) => stored.bool_value = Some(bool),
+        }
+        stored
+    }
+
+    /// Read the stored value as a value of `kind`
+    pub fn value(&self, kind: FieldKind) -> Option<FieldValue> {
+        match kind {
+            FieldKind::Text => self.text_value.clone().map(FieldValue::Text),
+            FieldKind::Enum => self.text_value.clone().map(FieldValue::Enum),
+            FieldKind::Number => self.number_value.map(FieldValue::Number),
+            FieldKind::Date => self.date_value.map(FieldValue::Date),
+            FieldKind::Bool => self.bool_value.map(FieldValue::Bool),
+        }
+    }
+}
+
+impl CustomField {
+    /// New Custom Field
+    ///
+    /// Create a new custom field entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `project_id` - The id of the project whose tasks get the field
+    /// * `name` - The field name, unique per project
+    /// * `kind` - The kind of values the field holds
+    pub fn new(project_id: Uuid, name: String, kind: FieldKind) -> Self {
+        Self {
+            project_id,
+            name,
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// List the Custom Fields of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_project_list_db<'c, E>(
+        project_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<CustomField>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = CustomField::list_project_query(project_id);
+        sqlx::query_as_with::<_, CustomField, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Custom Field ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Custom Field Project ID
+    pub fn project_id(&self) -> &Uuid {
+        &self.project_id
+    }
+
+    /// Get Custom Field Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get Custom Field Kind
+    pub fn kind(&self) -> FieldKind {
+        self.kind
+    }
+
+    /// Get the choices of an enum Custom Field
+    pub fn choices(&self) -> Vec<&str> {
+        self.choices.lines().collect()
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the custom field.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the custom field `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Sets the field name
+    /// # Arguments
+    /// * `name` - The string name to use
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Choices
+    ///
+    /// Sets the values an enum field accepts, tasks keep values that are no longer a choice
+    /// # Arguments
+    /// * `choices` - The accepted values, without line breaks
+    pub fn set_choices(&mut self, choices: &[String]) {
+        self.choices = choices.join("\n");
+        self.edited();
+    }
+
+    /// Validate
+    ///
+    /// Checks that a value is of the field's kind and, for enum fields, one of its choices.
+    /// # Arguments
+    /// * `value` - The value to check
+    pub fn validate(&self, value: &FieldValue) -> Result<(), DbError> {
+        let valid = match value {
+            _ if value.kind() != self.kind => false,
+            FieldValue::Enum(choice) => self.choices.lines().any(|c| c == choice),
+            FieldValue::Number(number) => number.is_finite(),
+            _ => true,
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(DbError::InvalidFieldValue(self.name.clone())),
+        }
+    }
+
+    // Database Interactions
+
+    /// Sets the value of the Custom Field on a Task on DB
+    ///
+    /// Values that don't [validate](CustomField::validate) are rejected with [`DbError::InvalidFieldValue`] and
+    /// tasks of other projects with [`DbError::FieldProjectMismatch`].
+    /// # Arguments
+    /// * `task` - The task to set the value on
+    /// * `value` - The value
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn set_value_db<'c, E>(
+        &self,
+        task: &Task,
+        value: FieldValue,
+        executor: E,
+    ) -> Result<SqliteQueryResult, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        if task.project_id() != &self.project_id {
+            return Err(DbError::FieldProjectMismatch);
+        }
+        self.validate(&value)?;
+
+        let query = self.set_value_query(*task.id(), value);
+        Ok(sqlx::query_with(&query.0, query.1)
+            .execute(executor)
+            .await?)
+    }
+
+    /// Removes the value of the Custom Field from a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn clear_value_db<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.clear_value_query(task_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Moves the Custom Field values of Tasks moved to another Project on DB
+    ///
+    /// The values the tasks of `to_project_id` have on the fields of `project_id` move to the field of `to_project_id`
+    /// with the same name, kind & choices, values without such a field are deleted. Run it after moving the tasks.
+    /// # Arguments
+    /// * `project_id` - The id of the project the tasks moved from
+    /// * `to_project_id` - The id of the project the tasks moved to
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn remap_values_db<'c, A>(
+        project_id: Uuid,
+        to_project_id: Uuid,
+        conn: A,
+    ) -> Result<(), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        for query in [
+            CustomField::remap_values_query(project_id, to_project_id),
+            CustomField::delete_foreign_values_query(to_project_id),
+        ] {
+            sqlx::query_with(&query.0, query.1)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Loads the value of the Custom Field on a Task from the DB
+    ///
+    /// Returns `None` if the task has no value for the field.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn value_db<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Option<FieldValue>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.value_query(task_id);
+
+        let stored = sqlx::query_as_with::<_, CustomValue, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await?;
+        Ok(stored.and_then(|stored| stored.value(self.kind)))
+    }
+
+    /// Sets the value of the Custom Field on its Project on DB
+    ///
+    /// Besides every task, the project that defines the field holds a value of its own, e.g. the customer of the
+    /// whole project. Values that don't [validate](CustomField::validate) are rejected with
+    /// [`DbError::InvalidFieldValue`].
+    /// # Arguments
+    /// * `value` - The value
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn set_project_value_db<'c, E>(
+        &self,
+        value: FieldValue,
+        executor: E,
+    ) -> Result<SqliteQueryResult, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        self.validate(&value)?;
+
+        let query = self.set_project_value_query(value);
+        Ok(sqlx::query_with(&query.0, query.1)
+            .execute(executor)
+            .await?)
+    }
+
+    /// Removes the value of the Custom Field from its Project on DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn clear_project_value_db<'c, E>(
+        &self,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.clear_project_value_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Loads the value of the Custom Field on its Project from the DB
+    ///
+    /// Returns `None` if the project has no value for the field.
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn project_value_db<'c, E>(&self, executor: E) -> Result<Option<FieldValue>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.project_value_query();
+
+        let stored = sqlx::query_as_with::<_, ProjectCustomValue, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await?;
+        Ok(stored.and_then(|stored| stored.value(self.kind)))
+    }
+
+    /// Generates a sqlx query to List the Custom Fields of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn list_project_query(project_id: Uuid) -> (String, SqlxValues) {
+        CustomField::select()
+            .and_where(Expr::col(CustomFieldIden::ProjectId).eq(project_id))
+            .order_by(CustomFieldIden::Name, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Insert or replace the value of the Custom Field on a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `value` - The value
+    pub fn set_value_query(&self, task_id: Uuid, value: FieldValue) -> (String, SqlxValues) {
+        let stored = CustomValue::new(self.id, task_id, value);
+
+        Query::insert()
+            .into_table(CustomValueIden::Table)
+            .columns([
+                CustomValueIden::FieldId,
+                CustomValueIden::TaskId,
+                CustomValueIden::TextValue,
+                CustomValueIden::NumberValue,
+                CustomValueIden::DateValue,
+                CustomValueIden::BoolValue,
+            ])
+            .values([
+                stored.field_id.into(),
+                stored.task_id.into(),
+                stored.text_value.into(),
+                stored.number_value.into(),
+                stored.date_value.into(),
+                stored.bool_value.into(),
+            ])
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([CustomValueIden::FieldId, CustomValueIden::TaskId])
+                    .update_columns([
+                        CustomValueIden::TextValue,
+                        CustomValueIden::NumberValue,
+                        CustomValueIden::DateValue,
+                        CustomValueIden::BoolValue,
+                    ])
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Remove the value of the Custom Field from a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn clear_value_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(CustomValueIden::Table)
+            .and_where(Expr::col(CustomValueIden::FieldId).eq(self.id))
+            .and_where(Expr::col(CustomValueIden::TaskId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the value of the Custom Field on a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn value_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::select()
+            .columns([
+                CustomValueIden::FieldId,
+                CustomValueIden::TaskId,
+                CustomValueIden::TextValue,
+                CustomValueIden::NumberValue,
+                CustomValueIden::DateValue,
+                CustomValueIden::BoolValue,
+            ])
+            .from(CustomValueIden::Table)
+            .and_where(Expr::col(CustomValueIden::FieldId).eq(self.id))
+            .and_where(Expr::col(CustomValueIden::TaskId).eq(task_id))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Insert or replace the value of the Custom Field on its Project on DB
+    ///
+    /// # Arguments
+    /// * `value` - The value
+    pub fn set_project_value_query(&self, value: FieldValue) -> (String, SqlxValues) {
+        let stored = ProjectCustomValue::new(self.id, value);
+
+        Query::insert()
+            .into_table(ProjectCustomValueIden::Table)
+            .columns([
+                ProjectCustomValueIden::FieldId,
+                ProjectCustomValueIden::TextValue,
+                ProjectCustomValueIden::NumberValue,
+                ProjectCustomValueIden::DateValue,
+                ProjectCustomValueIden::BoolValue,
+            ])
+            .values([
+                stored.field_id.into(),
+                stored.text_value.into(),
+                stored.number_value.into(),
+                stored.date_value.into(),
+                stored.bool_value.into(),
+            ])
+            .unwrap()
+            .on_conflict(
+                OnConflict::column(ProjectCustomValueIden::FieldId)
+                    .update_columns([
+                        ProjectCustomValueIden::TextValue,
+                        ProjectCustomValueIden::NumberValue,
+                        ProjectCustomValueIden::DateValue,
+                        ProjectCustomValueIden::BoolValue,
+                    ])
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Remove the value of the Custom Field from its Project on DB
+    pub fn clear_project_value_query(&self) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(ProjectCustomValueIden::Table)
+            .and_where(Expr::col(ProjectCustomValueIden::FieldId).eq(self.id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the value of the Custom Field on its Project on DB
+    pub fn project_value_query(&self) -> (String, SqlxValues) {
+        Query::select()
+            .columns([
+                ProjectCustomValueIden::FieldId,
+                ProjectCustomValueIden::TextValue,
+                ProjectCustomValueIden::NumberValue,
+                ProjectCustomValueIden::DateValue,
+                ProjectCustomValueIden::BoolValue,
+            ])
+            .from(ProjectCustomValueIden::Table)
+            .and_where(Expr::col(ProjectCustomValueIden::FieldId).eq(self.id))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Move the Custom Field values of Tasks moved to another Project on DB
+    ///
+    /// The values the tasks of `to_project_id` have on the fields of `project_id` move to the field of `to_project_id`
+    /// with the same name, kind & choices, values without such a field are left on their field, see
+    /// [`CustomField::delete_foreign_values_query`].
+    /// # Arguments
+    /// * `project_id` - The id of the project to move from
+    /// * `to_project_id` - The id of the project to move to
+    pub fn remap_values_query(project_id: Uuid, to_project_id: Uuid) -> (String, SqlxValues) {
+        let (from, to) = (Alias::new("from_field"), Alias::new("to_field"));
+        let matching = Query::select()
+            .column((to.clone(), CustomFieldIden::Id))
+            .from_as(CustomFieldIden::Table, from.clone())
+            .join_as(
+                JoinType::InnerJoin,
+                CustomFieldIden::Table,
+                to.clone(),
+                Condition::all()
+                    .add(
+                        Expr::col((to.clone(), CustomFieldIden::Name))
+                            .equals((from.clone(), CustomFieldIden::Name)),
+                    )
+                    .add(
+                        Expr::col((to.clone(), CustomFieldIden::Kind))
+                            .equals((from.clone(), CustomFieldIden::Kind)),
+                    )
+                    .add(
+                        Expr::col((to.clone(), CustomFieldIden::Choices))
+                            .equals((from.clone(), CustomFieldIden::Choices)),
+                    ),
+            )
+            .and_where(
+                Expr::col((from.clone(), CustomFieldIden::Id))
+                    .equals((CustomValueIden::Table, CustomValueIden::FieldId)),
+            )
+            .and_where(Expr::col((from, CustomFieldIden::ProjectId)).eq(project_id))
+            .and_where(Expr::col((to, CustomFieldIden::ProjectId)).eq(to_project_id))
+            .to_owned();
+
+        Query::update()
+            .table(CustomValueIden::Table)
+            .value(
+                CustomValueIden::FieldId,
+                SimpleExpr::SubQuery(None, Box::new(matching.clone().into_sub_query_statement())),
+            )
+            .and_where(Expr::exists(matching))
+            .and_where(
+                Expr::col((CustomValueIden::Table, CustomValueIden::TaskId))
+                    .in_subquery(CustomField::project_tasks_subquery(to_project_id)),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Delete the values the Tasks of a Project have on fields of other Projects on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn delete_foreign_values_query(project_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(CustomValueIden::Table)
+            .and_where(
+                Expr::col(CustomValueIden::TaskId)
+                    .in_subquery(CustomField::project_tasks_subquery(project_id)),
+            )
+            .and_where(
+                Expr::col(CustomValueIden::FieldId).not_in_subquery(
+                    Query::select()
+                        .column(CustomFieldIden::Id)
+                        .from(CustomFieldIden::Table)
+                        .and_where(Expr::col(CustomFieldIden::ProjectId).eq(project_id))
+                        .to_owned(),
+                ),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// The ids of the tasks of a project, as a subquery
+    fn project_tasks_subquery(project_id: Uuid) -> SelectStatement {
+        Query::select()
+            .column(TaskIden::Id)
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .to_owned()
+    }
+
+    /// Set Edited
+    ///
+    /// Sets the custom field edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for CustomField {
+    type Iden = CustomFieldIden;
+    type Key = Uuid;
+
+    const TABLE: CustomFieldIden = CustomFieldIden::Table;
+    const KEY: &'static [CustomFieldIden] = &[CustomFieldIden::Id];
+    const CREATED: Option<CustomFieldIden> = Some(CustomFieldIden::Created);
+    const COLUMNS: &'static [CustomFieldIden] = &[
+        CustomFieldIden::Id,
+        CustomFieldIden::ProjectId,
+        CustomFieldIden::Name,
+        CustomFieldIden::Kind,
+        CustomFieldIden::Choices,
+        CustomFieldIden::Created,
+        CustomFieldIden::Modified,
+    ];
+    const ORDER_BY: Option<CustomFieldIden> = Some(CustomFieldIden::Name);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.project_id.into(),
+            self.name.clone().into(),
+            self.kind.as_str().into(),
+            self.choices.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a custom field also deletes its values (`ON DELETE CASCADE`)
+impl Repository for CustomField {}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomField, FieldKind, FieldValue};
+    use crate::config::init_memory_db;
+    use crate::db::filter::{ListSpec, Sort};
+    use crate::db::projects::Project;
+    use crate::db::tasks::{Task, TaskIden};
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use chrono::{TimeZone, Utc};
+    use sea_query::Order;
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    async fn create_task(project: &Project, title: &str, conn: &mut SqliteConnection) -> Task {
+        let mut task = Task::new(
+            *project.id(),
+            title.into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn create_field(
+        project: &Project,
+        name: &str,
+        kind: FieldKind,
+        conn: &mut SqliteConnection,
+    ) -> CustomField {
+        let mut field = CustomField::new(*project.id(), name.into(), kind);
+        field.assign_id().assign_created();
+        if kind == FieldKind::Enum {
+            field.set_choices(&["dev".into(), "staging".into(), "prod".into()]);
+        }
+        field.insert(conn).await.unwrap();
+        field
+    }
+
+    fn titles(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.title().as_str()).collect()
+    }
+
+    #[test]
+    fn insert() {
+        let query =
+            CustomField::new(Uuid::default(), "NAME".into(), FieldKind::Text).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"custom_field\" (\"id\", \"project_id\", \"name\", \"kind\", \"choices\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn validate() {
+        let mut field = CustomField::new(Uuid::default(), "environment".into(), FieldKind::Enum);
+        field.set_choices(&["dev".into(), "prod".into()]);
+
+        assert_eq!(field.choices(), ["dev", "prod"]);
+        assert!(field.validate(&FieldValue::Enum("prod".into())).is_ok());
+        assert!(matches!(
+            field.validate(&FieldValue::Enum("qa".into())),
+            Err(DbError::InvalidFieldValue(name)) if name == "environment"
+        ));
+        assert!(field.validate(&FieldValue::Text("prod".into())).is_err());
+
+        let field = CustomField::new(Uuid::default(), "estimate".into(), FieldKind::Number);
+        assert!(field.validate(&FieldValue::Number(1.5)).is_ok());
+        assert!(field.validate(&FieldValue::Number(f64::NAN)).is_err());
+        assert!(field.validate(&FieldValue::Bool(true)).is_err());
+    }
+
+    #[tokio::test]
+    async fn value_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+        let task = create_task(&project, "TASK", &mut conn).await;
+        let foreign = create_task(&other, "FOREIGN", &mut conn).await;
+        let date = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+
+        for (kind, value) in [
+            (FieldKind::Text, FieldValue::Text("ACME".into())),
+            (FieldKind::Number, FieldValue::Number(42.0)),
+            (FieldKind::Date, FieldValue::Date(date)),
+            (FieldKind::Enum, FieldValue::Enum("prod".into())),
+            (FieldKind::Bool, FieldValue::Bool(true)),
+        ] {
+            let field = create_field(&project, kind.as_str(), kind, &mut conn).await;
+            assert_eq!(field.value_db(*task.id(), &mut conn).await.unwrap(), None);
+
+            field
+                .set_value_db(&task, value.clone(), &mut conn)
+                .await
+                .unwrap();
+            assert_eq!(
+                field.value_db(*task.id(), &mut conn).await.unwrap(),
+                Some(value.clone())
+            );
+            assert!(matches!(
+                field.set_value_db(&foreign, value, &mut conn).await,
+                Err(DbError::FieldProjectMismatch)
+            ));
+
+            field.clear_value_db(*task.id(), &mut conn).await.unwrap();
+            assert_eq!(field.value_db(*task.id(), &mut conn).await.unwrap(), None);
+        }
+
+        let fields = CustomField::from_project_list_db(*project.id(), &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(fields.len(), 5);
+        assert_eq!(
+            CustomField::from_db(*fields[0].id(), &mut conn)
+                .await
+                .unwrap(),
+            fields[0]
+        );
+
+        let duplicate = create_field(&other, "text", FieldKind::Text, &mut conn).await;
+        let mut clash = CustomField::new(*other.id(), "text".into(), FieldKind::Bool);
+        clash.assign_id().assign_created();
+        assert!(clash.insert(&mut conn).await.is_err());
+        duplicate.delete(&mut conn).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn project_value_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project, "TASK", &mut conn).await;
+        let customer = create_field(&project, "customer", FieldKind::Text, &mut conn).await;
+        assert_eq!(customer.project_value_db(&mut conn).await.unwrap(), None);
+
+        for name in ["ACME", "Initech"] {
+            customer
+                .set_project_value_db(FieldValue::Text(name.into()), &mut conn)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            customer.project_value_db(&mut conn).await.unwrap(),
+            Some(FieldValue::Text("Initech".into()))
+        );
+        assert!(matches!(
+            customer
+                .set_project_value_db(FieldValue::Number(1.0), &mut conn)
+                .await,
+            Err(DbError::InvalidFieldValue(_))
+        ));
+
+        // the project value is separate from the task values
+        assert_eq!(
+            customer.value_db(*task.id(), &mut conn).await.unwrap(),
+            None
+        );
+
+        customer.clear_project_value_db(&mut conn).await.unwrap();
+        assert_eq!(customer.project_value_db(&mut conn).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn field_list_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let number = create_field(&project, "ticket", FieldKind::Number, &mut conn).await;
+        let environment = create_field(&project, "environment", FieldKind::Enum, &mut conn).await;
+
+        let a = create_task(&project, "a", &mut conn).await;
+        let b = create_task(&project, "b", &mut conn).await;
+        let c = create_task(&project, "c", &mut conn).await;
+        create_task(&project, "none", &mut conn).await;
+
+        for (task, ticket, env) in [(&a, 30.0, "prod"), (&b, 100.0, "dev"), (&c, 4.0, "prod")] {
+            number
+                .set_value_db(task, FieldValue::Number(ticket), &mut conn)
+                .await
+                .unwrap();
+            environment
+                .set_value_db(task, FieldValue::Enum(env.into()), &mut conn)
+                .await
+                .unwrap();
+        }
+
+        // numbers sort numerically, tasks without a value come last
+        let tasks = Task::from_field_list_db(
+            &number,
+            None,
+            Order::Asc,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(titles(&tasks), ["c", "a", "b", "none"]);
+        let tasks = Task::from_field_list_db(
+            &number,
+            None,
+            Order::Desc,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(titles(&tasks), ["b", "a", "c", "none"]);
+
+        let prod = FieldValue::Enum("prod".into());
+        let tasks = Task::from_field_list_db(
+            &environment,
+            Some(prod),
+            Order::Asc,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(titles(&tasks), ["a", "c"]);
+        // the spec orders the tasks with the same value
+        let spec = ListSpec::new().sort(Sort::Desc(TaskIden::Created));
+        let prod = FieldValue::Enum("prod".into());
+        let tasks = Task::from_field_list_db(
+            &environment,
+            Some(prod),
+            Order::Asc,
+            &spec,
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(titles(&tasks), ["c", "a"]);
+
+        assert!(matches!(
+            Task::from_field_list_db(
+                &number,
+                Some(FieldValue::Bool(true)),
+                Order::Asc,
+                &ListSpec::new(),
+                0,
+                10,
+                &mut conn
+            )
+            .await,
+            Err(DbError::InvalidFieldValue(_))
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+//! Task Dependency Database Entity
+//! This file contains the task dependency database entity, an edge saying that one task blocks another. Edges can
+//! cross projects, the DB refuses edges that would form a cycle.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::tasks::TaskIden;
+use crate::db::{DbError, Entity};
+
+/// The message the DB aborts with when a dependency would form a cycle
+const DEPENDENCY_CYCLE: &str = "task dependency cycle";
+
+/// The task dependency database entity for taskify, `blocker_id` blocks `blocked_id`
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct TaskDependency {
+    blocker_id: Uuid,
+    blocked_id: Uuid,
+    created: DateTime<Utc>,
+}
+
+impl TaskDependency {
+    /// New Task Dependency
+    ///
+    /// Create a new dependency saying that `blocker_id` blocks `blocked_id`.
+    /// # Arguments:
+    /// * `blocker_id` - The id of the task that has to be completed first
+    /// * `blocked_id` - The id of the task that is blocked
+    pub fn new(blocker_id: Uuid, blocked_id: Uuid) -> Self {
+        Self {
+            blocker_id,
+            blocked_id,
+            created: Utc::now(),
+        }
+    }
+
+    /// List the Dependencies blocking a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the blocked task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_blockers_db<'c, E>(
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<TaskDependency>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TaskDependency::blockers_query(task_id);
+        sqlx::query_as_with::<_, TaskDependency, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Dependencies a Task blocks in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the blocker task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_blocking_db<'c, E>(
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<TaskDependency>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TaskDependency::blocking_query(task_id);
+        sqlx::query_as_with::<_, TaskDependency, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Dependencies between the Tasks of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_project_list_db<'c, E>(
+        project_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<TaskDependency>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TaskDependency::list_project_query(project_id);
+        sqlx::query_as_with::<_, TaskDependency, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Blocker Task ID
+    pub fn blocker_id(&self) -> &Uuid {
+        &self.blocker_id
+    }
+
+    /// Get Blocked Task ID
+    pub fn blocked_id(&self) -> &Uuid {
+        &self.blocked_id
+    }
+
+    // Database Interactions
+
+    /// Inserts Task Dependency to DB
+    ///
+    /// The DB rejects dependencies that would form a cycle with [`DbError::DependencyCycle`].
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn insert<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1)
+            .execute(executor)
+            .await
+            .map_err(|err| match &err {
+                Error::Database(db) if db.message() == DEPENDENCY_CYCLE => DbError::DependencyCycle,
+                _ => DbError::Sqlx(err),
+            })
+    }
+
+    /// Deletes a Task Dependency on DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn delete<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.delete_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to List the Dependencies blocking a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the blocked task
+    pub fn blockers_query(task_id: Uuid) -> (String, SqlxValues) {
+        TaskDependency::select()
+            .and_where(Expr::col(TaskDependencyIden::BlockedId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Dependencies a Task blocks in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the blocker task
+    pub fn blocking_query(task_id: Uuid) -> (String, SqlxValues) {
+        TaskDependency::select()
+            .and_where(Expr::col(TaskDependencyIden::BlockerId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Dependencies between the Tasks of a Project in the DB
+    ///
+    /// Only dependencies where both tasks belong to the project are listed.
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn list_project_query(project_id: Uuid) -> (String, SqlxValues) {
+        let project_tasks = Query::select()
+            .column(TaskIden::Id)
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .to_owned();
+
+        TaskDependency::select()
+            .and_where(Expr::col(TaskDependencyIden::BlockerId).in_subquery(project_tasks.clone()))
+            .and_where(Expr::col(TaskDependencyIden::BlockedId).in_subquery(project_tasks))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+/// Inserting maps a cycle to [`DbError::DependencyCycle`], so [`TaskDependency`] keeps its writes
+impl Entity for TaskDependency {
+    type Iden = TaskDependencyIden;
+    type Key = (Uuid, Uuid);
+
+    const TABLE: TaskDependencyIden = TaskDependencyIden::Table;
+    const KEY: &'static [TaskDependencyIden] =
+        &[TaskDependencyIden::BlockerId, TaskDependencyIden::BlockedId];
+    const CREATED: Option<TaskDependencyIden> = Some(TaskDependencyIden::Created);
+    const COLUMNS: &'static [TaskDependencyIden] = &[
+        TaskDependencyIden::BlockerId,
+        TaskDependencyIden::BlockedId,
+        TaskDependencyIden::Created,
+    ];
+    const ORDER_BY: Option<TaskDependencyIden> = Some(TaskDependencyIden::Created);
+
+    fn key(&self) -> (Uuid, Uuid) {
+        (self.blocker_id, self.blocked_id)
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.blocker_id.into(),
+            self.blocked_id.into(),
+            self.created.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaskDependency;
+    use crate::config::init_memory_db;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_task(project: &Project, conn: &mut SqliteConnection) -> Task {
+        let mut task = Task::new(
+            *project.id(),
+            "TASK_TITLE".into(),
+            "TASK_BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    #[test]
+    fn insert() {
+        let query = TaskDependency::new(Uuid::default(), Uuid::default()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"task_dependency\" (\"blocker_id\", \"blocked_id\", \"created\") VALUES (?, ?, ?)");
+    }
+
+    #[test]
+    fn delete() {
+        let query = TaskDependency::new(Uuid::default(), Uuid::default()).delete_query();
+
+        assert_eq!(
+            query.0,
+            "DELETE FROM \"task_dependency\" WHERE \"blocker_id\" = ? AND \"blocked_id\" = ?"
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+        let a = create_task(&project, &mut conn).await;
+        let b = create_task(&other, &mut conn).await;
+
+        let dependency = TaskDependency::new(*a.id(), *b.id());
+        dependency.insert(&mut conn).await.unwrap();
+
+        assert_eq!(
+            TaskDependency::from_blockers_db(*b.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![dependency.clone()]
+        );
+        assert_eq!(
+            TaskDependency::from_blocking_db(*a.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![dependency.clone()]
+        );
+        assert!(
+            TaskDependency::from_project_list_db(*project.id(), &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        dependency.delete(&mut conn).await.unwrap();
+        assert!(TaskDependency::from_blockers_db(*b.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn cycle_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let a = create_task(&project, &mut conn).await;
+        let b = create_task(&project, &mut conn).await;
+        let c = create_task(&project, &mut conn).await;
+
+        TaskDependency::new(*a.id(), *b.id())
+            .insert(&mut conn)
+            .await
+            .unwrap();
+        TaskDependency::new(*b.id(), *c.id())
+            .insert(&mut conn)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            TaskDependency::new(*c.id(), *a.id())
+                .insert(&mut conn)
+                .await,
+            Err(DbError::DependencyCycle)
+        ));
+        assert!(matches!(
+            TaskDependency::new(*a.id(), *a.id())
+                .insert(&mut conn)
+                .await,
+            Err(DbError::DependencyCycle)
+        ));
+        TaskDependency::new(*a.id(), *c.id())
+            .insert(&mut conn)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,353 @@
+//! Generic Database Entity
+//! This file contains the [`Entity`] trait, that builds the queries every database entity needs from the columns of
+//! its `#[enum_def]` iden, and the [`Repository`] trait, that adds the async CRUD & paging helpers on top of them.
+//! Most entities are keyed by a uuid, the [`Key`] trait also covers DB generated ids & composite keys.
+//! Entities whose writes have side effects, e.g. the audited [`Project`](super::projects::Project), keep them in
+//! `_db` helpers next to the plain [`Repository`] ones.
+
+use async_trait::async_trait;
+use sea_query::{
+    Condition, Expr, Iden, Query, SelectStatement, SimpleExpr, SqliteQueryBuilder, Value,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::{SqliteQueryResult, SqliteRow};
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::filter::{ListSpec, Sort};
+
+/// The primary key of an [`Entity`]
+pub trait Key: Copy + Send + Sync {
+    /// The value of every key column, in the order of [`Entity::KEY`]
+    fn values(self) -> Vec<Value>;
+}
+
+impl Key for Uuid {
+    fn values(self) -> Vec<Value> {
+        vec![self.into()]
+    }
+}
+
+/// The id the DB generates, e.g. of the append-only [`History`](super::history::History)
+impl Key for i64 {
+    fn values(self) -> Vec<Value> {
+        vec![self.into()]
+    }
+}
+
+/// A composite key of two uuids, e.g. of a [`TaskDependency`](super::dependencies::TaskDependency)
+impl Key for (Uuid, Uuid) {
+    fn values(self) -> Vec<Value> {
+        vec![self.0.into(), self.1.into()]
+    }
+}
+
+/// A database entity, described by its `#[enum_def]` iden
+pub trait Entity: Sized {
+    /// The iden `#[enum_def]` generates for the entity, e.g. `ProjectIden`
+    type Iden: Iden + Copy + PartialEq + 'static;
+
+    /// The primary key of the entity, usually a uuid
+    type Key: Key;
+
+    /// The table of the entity
+    const TABLE: Self::Iden;
+
+    /// The primary key columns, they are never updated
+    const KEY: &'static [Self::Iden];
+
+    /// The column holding when the entity was created, it is never updated. `None` if the entity has none
+    const CREATED: Option<Self::Iden>;
+
+    /// Does the DB generate the key? Inserts leave the key columns out then
+    const GENERATED_KEY: bool = false;
+
+    /// Every column of the entity, in the order of [`Entity::values`]
+    const COLUMNS: &'static [Self::Iden];
+
+    /// The column the entity lists are ordered by if the [`ListSpec`] has no sorts, `None` keeps the DB order
+    const ORDER_BY: Option<Self::Iden> = None;
+
+    /// The primary key of the entity
+    fn key(&self) -> Self::Key;
+
+    /// The value of every column, in the order of [`Entity::COLUMNS`]
+    fn values(&self) -> Vec<SimpleExpr>;
+
+    /// The select statement of all the entity columns
+    fn select() -> SelectStatement {
+        Query::select()
+            .columns(Self::COLUMNS.iter().copied())
+            .from(Self::TABLE)
+            .to_owned()
+    }
+
+    /// The select statement of all the entity columns qualified with the table, for queries that join other tables
+    fn joined_select() -> SelectStatement {
+        Query::select()
+            .columns(Self::COLUMNS.iter().map(|column| (Self::TABLE, *column)))
+            .from(Self::TABLE)
+            .to_owned()
+    }
+
+    /// The condition matching the key columns to a key
+    ///
+    /// # Arguments
+    /// * `key` - The key to match
+    fn key_condition(key: Self::Key) -> Condition {
+        Self::KEY
+            .iter()
+            .zip(key.values())
+            .fold(Condition::all(), |condition, (column, value)| {
+                condition.add(Expr::col(*column).eq(value))
+            })
+    }
+
+    /// Generates a sqlx query to Insert the Entity to DB
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::test]
+    /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
+    /// use taskify::db::Entity;
+    /// taskify::db::projects::Project::new(uuid::Uuid::new_v4(), "Name".into(), "Desc".into(), uuid::Uuid::new_v4()).insert_query();
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn insert_query(&self) -> (String, SqlxValues) {
+        let (columns, values): (Vec<_>, Vec<_>) = Self::COLUMNS
+            .iter()
+            .copied()
+            .zip(self.values())
+            .filter(|(column, _)| !(Self::GENERATED_KEY && Self::KEY.contains(column)))
+            .unzip();
+
+        Query::insert()
+            .into_table(Self::TABLE)
+            .columns(columns)
+            .values(values)
+            .unwrap()
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Update the Entity on DB
+    ///
+    /// Updates every column but the primary key and the created ones.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::test]
+    /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
+    /// use taskify::db::Entity;
+    /// taskify::db::projects::Project::new(uuid::Uuid::new_v4(), "Name".into(), "Desc".into(), uuid::Uuid::new_v4()).update_query();
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn update_query(&self) -> (String, SqlxValues) {
+        Query::update()
+            .table(Self::TABLE)
+            .values(
+                Self::COLUMNS
+                    .iter()
+                    .copied()
+                    .zip(self.values())
+                    .filter(|(column, _)| {
+                        !Self::KEY.contains(column) && Some(*column) != Self::CREATED
+                    }),
+            )
+            .cond_where(Self::key_condition(self.key()))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Delete the Entity on DB
+    fn delete_query(&self) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(Self::TABLE)
+            .cond_where(Self::key_condition(self.key()))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find an Entity on DB
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for, usually the uuid v4 id
+    fn select_query(key: Self::Key) -> (String, SqlxValues) {
+        Self::select()
+            .cond_where(Self::key_condition(key))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// The select statement of the entity list, filtered & sorted by the spec
+    ///
+    /// Without sorts in the spec, the entities are ordered by [`Entity::ORDER_BY`].
+    /// # Arguments
+    /// * `spec` - The filters & sort order of the list
+    fn list_select(spec: &ListSpec<Self::Iden>) -> SelectStatement {
+        let mut select = Self::select();
+        spec.apply(&mut select, Self::ORDER_BY.map(Sort::Asc));
+        select
+    }
+
+    /// Generates a sqlx query to List all Entities in the DB
+    ///
+    /// # Arguements
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    fn list_query(spec: &ListSpec<Self::Iden>, page: u64, limit: u64) -> (String, SqlxValues) {
+        Self::list_select(spec)
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+/// The async CRUD & paging helpers of an [`Entity`]
+///
+/// Every helper has a default, an entity opts in with an empty `impl Repository for ... {}`. The helpers run a
+/// single statement on any [`Executor`], so the same call works on a connection, inside a
+/// [transaction](super::Store::transaction) or straight on the pool.
+#[async_trait]
+pub trait Repository: Entity + for<'r> FromRow<'r, SqliteRow> + Send + Sync + Unpin {
+    /// Load an entity from the DB
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for, usually the uuid v4 id
+    /// * `executor` - The SQLite database connection, transaction or pool
+    async fn from_db<'c, E>(key: Self::Key, executor: E) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Self::select_query(key);
+
+        sqlx::query_as_with::<_, Self, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// List all Entities in the DB
+    ///
+    /// # Arguements
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    async fn from_list_db<'c, E>(
+        spec: &ListSpec<Self::Iden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Self>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Self::list_query(spec, page, limit);
+
+        sqlx::query_as_with::<_, Self, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Inserts the Entity to DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    async fn insert<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Updates the Entity on DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    async fn update<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.update_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Deletes the Entity on DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    async fn delete<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.delete_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entity, Repository};
+    use crate::config::init_memory_db;
+    use crate::db::filter::ListSpec;
+    use crate::db::workspaces::Workspace;
+    use sqlx::Error;
+
+    #[test]
+    fn list_query() {
+        let query = Workspace::list_query(&ListSpec::new(), 1, 10);
+
+        assert_eq!(query.0, "SELECT \"id\", \"name\", \"description\", \"created\", \"modified\" FROM \"workspace\" ORDER BY \"name\" ASC LIMIT ? OFFSET ?");
+    }
+
+    #[test]
+    fn update_query() {
+        let query = Workspace::new("NAME".into(), "DESC".into()).update_query();
+
+        assert_eq!(query.0, "UPDATE \"workspace\" SET \"name\" = ?, \"description\" = ?, \"modified\" = ? WHERE \"id\" = ?");
+    }
+
+    #[tokio::test]
+    async fn repository_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let mut workspace = Workspace::new("team".into(), "DESC".into());
+        workspace.assign_id().assign_created();
+
+        let query = workspace.insert(&mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+        assert_eq!(
+            Workspace::from_db(*workspace.id(), &mut conn)
+                .await
+                .unwrap(),
+            workspace
+        );
+
+        workspace.set_description("CHANGED".into());
+        let query = workspace.update(&mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+        let names: Vec<_> = Workspace::from_list_db(&ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|workspace| workspace.name().clone())
+            .collect();
+        assert_eq!(names, ["default", "team"]);
+        assert_eq!(
+            Workspace::from_list_db(&ListSpec::new(), 1, 1, &mut conn)
+                .await
+                .unwrap(),
+            [workspace.clone()]
+        );
+
+        let query = workspace.delete(&mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+        assert!(matches!(
+            Workspace::from_db(*workspace.id(), &mut conn).await,
+            Err(Error::RowNotFound)
+        ));
+    }
+}
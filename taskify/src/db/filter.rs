@@ -0,0 +1,233 @@
+//! List Filters & Sorting
+//! This file contains the [`ListSpec`], the filter & sort specification the list functions take. It is typed by the
+//! `#[enum_def]` iden of an entity, e.g. `ListSpec<ProjectIden>`, so only columns of that entity can be filtered or
+//! sorted on, and compiles to `sea_query` conditions that are added to the ones of the list query.
+
+use chrono::{DateTime, Utc};
+use sea_query::{Expr, Iden, LikeExpr, Order, SelectStatement, SimpleExpr, Value};
+
+/// A condition on a column of an entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter<I> {
+    /// The column equals the value
+    Equals(I, Value),
+    /// The text column contains the text, ignoring the case of ASCII letters
+    Contains(I, String),
+    /// The datetime column is in the range, the start is inclusive and the end exclusive, `None` leaves it open
+    Range(I, Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    /// At least one of the filters holds, none does if there are no filters
+    Any(Vec<Filter<I>>),
+}
+
+impl<I: Iden + Copy + 'static> Filter<I> {
+    /// Equals Filter
+    ///
+    /// # Arguments
+    /// * `column` - The column to compare, e.g. `ProjectIden::AuthorId`
+    /// * `value` - The value the column has to equal
+    pub fn equals(column: I, value: impl Into<Value>) -> Self {
+        Filter::Equals(column, value.into())
+    }
+
+    /// Contains Filter
+    ///
+    /// `%` and `_` in the text are matched literally.
+    /// # Arguments
+    /// * `column` - The text column to search, e.g. `ProjectIden::Name`
+    /// * `text` - The text the column has to contain
+    pub fn contains(column: I, text: impl Into<String>) -> Self {
+        Filter::Contains(column, text.into())
+    }
+
+    /// Range Filter
+    ///
+    /// # Arguments
+    /// * `column` - The datetime column to compare, e.g. `ProjectIden::Created`
+    /// * `from` - The inclusive start of the range, `None` for no start
+    /// * `to` - The exclusive end of the range, `None` for no end
+    pub fn range(column: I, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Self {
+        Filter::Range(column, from, to)
+    }
+
+    /// Compiles the filter to a `sea_query` condition
+    pub fn expr(&self) -> SimpleExpr {
+        match self {
+            Filter::Equals(column, value) => Expr::col(*column).eq(value.clone()),
+            Filter::Contains(column, text) => {
+                let text = text
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                Expr::col(*column).like(LikeExpr::new(format!("%{}%", text)).escape('\\'))
+            }
+            Filter::Range(column, from, to) => {
+                let from = from.map(|from| Expr::col(*column).gte(from));
+                let to = to.map(|to| Expr::col(*column).lt(to));
+                match (from, to) {
+                    (Some(from), Some(to)) => from.and(to),
+                    (Some(bound), None) | (None, Some(bound)) => bound,
+                    (None, None) => Expr::value(true),
+                }
+            }
+            Filter::Any(filters) => filters
+                .iter()
+                .map(Filter::expr)
+                .reduce(SimpleExpr::or)
+                .unwrap_or(Expr::value(false)),
+        }
+    }
+}
+
+/// The order of a column of an entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sort<I> {
+    /// Smallest first
+    Asc(I),
+    /// Largest first
+    Desc(I),
+}
+
+/// The filters & sort order of a list query
+///
+/// Every filter has to hold. Rows are ordered by the sorts in turn, or by the default order of the list function if
+/// there are none.
+///
+/// # Examples
+/// ```
+/// use taskify::db::filter::{Filter, ListSpec, Sort};
+/// use taskify::db::projects::ProjectIden;
+///
+/// let spec = ListSpec::new()
+///     .filter(Filter::contains(ProjectIden::Name, "release"))
+///     .filter(Filter::range(ProjectIden::Created, Some(chrono::Utc::now() - chrono::Duration::days(7)), None))
+///     .sort(Sort::Desc(ProjectIden::Modified));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListSpec<I> {
+    filters: Vec<Filter<I>>,
+    sorts: Vec<Sort<I>>,
+}
+
+impl<I> Default for ListSpec<I> {
+    fn default() -> Self {
+        Self {
+            filters: Vec::new(),
+            sorts: Vec::new(),
+        }
+    }
+}
+
+impl<I: Iden + Copy + 'static> ListSpec<I> {
+    /// New List Spec
+    ///
+    /// Create a list spec without filters, that keeps the default order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a filter the listed rows have to match
+    ///
+    /// # Arguments
+    /// * `filter` - The filter to add
+    pub fn filter(mut self, filter: Filter<I>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Adds a sort, rows that are equal in the previous sorts are ordered by it
+    ///
+    /// # Arguments
+    /// * `sort` - The sort to add
+    pub fn sort(mut self, sort: Sort<I>) -> Self {
+        self.sorts.push(sort);
+        self
+    }
+
+    /// Get List Spec Filters
+    pub fn filters(&self) -> &Vec<Filter<I>> {
+        &self.filters
+    }
+
+    /// Get List Spec Sorts
+    pub fn sorts(&self) -> &Vec<Sort<I>> {
+        &self.sorts
+    }
+
+    /// Adds the filters & sorts to a select statement
+    ///
+    /// The columns are not qualified with the table, the statement should select from the entity table only.
+    /// # Arguments
+    /// * `select` - The select statement of the list query
+    /// * `default` - The sort to use if the spec has none
+    pub fn apply<'a>(
+        &self,
+        select: &'a mut SelectStatement,
+        default: Option<Sort<I>>,
+    ) -> &'a mut SelectStatement {
+        for filter in &self.filters {
+            select.and_where(filter.expr());
+        }
+
+        let sorts = match self.sorts.is_empty() {
+            true => default.into_iter().collect(),
+            false => self.sorts.clone(),
+        };
+        for sort in sorts {
+            match sort {
+                Sort::Asc(column) => select.order_by(column, Order::Asc),
+                Sort::Desc(column) => select.order_by(column, Order::Desc),
+            };
+        }
+        select
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, ListSpec, Sort};
+    use crate::db::projects::{Project, ProjectIden};
+    use crate::db::Entity;
+    use sea_query::SqliteQueryBuilder;
+    use uuid::Uuid;
+
+    fn to_string(spec: &ListSpec<ProjectIden>) -> String {
+        spec.apply(&mut Project::select(), Some(Sort::Asc(ProjectIden::Name)))
+            .to_string(SqliteQueryBuilder)
+    }
+
+    #[test]
+    fn filter() {
+        let id = Uuid::nil();
+        let created = "2024-07-10T00:00:00Z".parse().unwrap();
+        let spec = ListSpec::new()
+            .filter(Filter::equals(ProjectIden::AuthorId, id))
+            .filter(Filter::contains(ProjectIden::Name, "50%_off"))
+            .filter(Filter::range(ProjectIden::Created, Some(created), None));
+
+        assert_eq!(to_string(&spec), "SELECT \"id\", \"workspace_id\", \"name\", \"description\", \"author_id\", \"archived\", \"deleted\", \"created\", \"modified\" FROM \"project\" WHERE \"author_id\" = '00000000-0000-0000-0000-000000000000' AND \"name\" LIKE '%50\\%\\_off%' ESCAPE '\\' AND \"created\" >= '2024-07-10 00:00:00 +00:00' ORDER BY \"name\" ASC");
+    }
+
+    #[test]
+    fn any() {
+        let spec = ListSpec::new().filter(Filter::Any(vec![
+            Filter::contains(ProjectIden::Name, "docs"),
+            Filter::contains(ProjectIden::Description, "docs"),
+        ]));
+        assert!(to_string(&spec)
+            .ends_with("WHERE \"name\" LIKE '%docs%' ESCAPE '\\' OR \"description\" LIKE '%docs%' ESCAPE '\\' ORDER BY \"name\" ASC"));
+
+        let spec = ListSpec::new().filter(Filter::Any(vec![]));
+        assert!(to_string(&spec).ends_with("WHERE FALSE ORDER BY \"name\" ASC"));
+    }
+
+    #[test]
+    fn sort() {
+        let spec = ListSpec::new()
+            .sort(Sort::Desc(ProjectIden::Modified))
+            .sort(Sort::Asc(ProjectIden::Id));
+
+        assert!(
+            to_string(&spec).ends_with("FROM \"project\" ORDER BY \"modified\" DESC, \"id\" ASC")
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! History Database Entity
+//! This file contains the history database entity, an append-only log of the field-level changes made to
+//! [`Audited`] entities. Every update or delete writes one row per changed field, the rows outlive the entity so its
+//! timeline can still be viewed, and its state reconstructed, after it was purged.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Order, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::{SqliteQueryResult, SqliteRow};
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::{DbError, Entity};
+
+/// An entity whose changes are recorded in the [`History`]
+pub trait Audited: Default {
+    /// The name the entity's changes are recorded under
+    const ENTITY: &'static str;
+
+    /// The id of the entity
+    fn audit_id(&self) -> Uuid;
+
+    /// Sets the id of an entity rebuilt from its history
+    fn set_audit_id(&mut self, id: Uuid);
+
+    /// When the entity was created, it has no state before
+    fn audit_created(&self) -> DateTime<Utc>;
+
+    /// The recorded fields of the entity and their values as text
+    fn audit_fields(&self) -> Vec<(&'static str, Option<String>)>;
+
+    /// Sets a recorded field from its text value, returns `false` if the field or value is not understood
+    fn restore_field(&mut self, field: &str, value: Option<&str>) -> bool;
+}
+
+/// The kind of change a history entry records
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum HistoryAction {
+    /// The entity was updated
+    #[default]
+    Update,
+    /// The entity was (soft) deleted
+    Delete,
+    /// The entity was permanently deleted, the entry has no new value
+    Purge,
+}
+
+impl HistoryAction {
+    /// The name the action is stored as
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryAction::Update => "update",
+            HistoryAction::Delete => "delete",
+            HistoryAction::Purge => "purge",
+        }
+    }
+}
+
+/// The history database entity for taskify, one changed field of an entity
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct History {
+    id: i64,
+    entity: String,
+    entity_id: Uuid,
+    action: HistoryAction,
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    author_id: Uuid,
+    changed: DateTime<Utc>,
+}
+
+impl History {
+    /// Diff two states of an entity
+    ///
+    /// Creates an entry for every field whose value differs, for every field if there is no new state. The entries
+    /// have no id until they are loaded back from the DB.
+    /// # Arguments
+    /// * `action` - The kind of change
+    /// * `before` - The state before the change
+    /// * `after` - The state after the change, `None` if the entity was purged
+    /// * `author_id` - The id of the [`User`](super::users::User) that made the change
+    /// * `changed` - When the change was made
+    pub fn diff<T: Audited>(
+        action: HistoryAction,
+        before: &T,
+        after: Option<&T>,
+        author_id: Uuid,
+        changed: DateTime<Utc>,
+    ) -> Vec<History> {
+        let new_values = after.map(|after| after.audit_fields());
+
+        before
+            .audit_fields()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (field, old_value))| {
+                let new_value = new_values.as_ref().and_then(|values| values[i].1.clone());
+                if new_values.is_some() && new_value == old_value {
+                    return None;
+                }
+
+                Some(History {
+                    entity: T::ENTITY.into(),
+                    entity_id: before.audit_id(),
+                    action,
+                    field: field.into(),
+                    old_value,
+                    new_value,
+                    author_id,
+                    changed,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Records the change of an entity to the DB
+    ///
+    /// # Arguments
+    /// * `action` - The kind of change
+    /// * `before` - The state before the change
+    /// * `after` - The state after the change, `None` if the entity was purged
+    /// * `author_id` - The id of the [`User`](super::users::User) that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn record_db<'c, T: Audited, A>(
+        action: HistoryAction,
+        before: &T,
+        after: Option<&T>,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<(), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        for entry in History::diff(action, before, after, author_id, Utc::now()) {
+            entry.insert(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Writes the change of an Entity to DB and records it
+    ///
+    /// Updates the entity, or deletes it for a [`HistoryAction::Purge`], and records the changed fields against its
+    /// stored state. Returns the stored state, nothing is recorded if the entity is not in the DB.
+    /// # Arguments
+    /// * `action` - The kind of change
+    /// * `entity` - The changed entity
+    /// * `author_id` - The id of the [`User`](super::users::User) that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn audit_db<'c, T, A>(
+        action: HistoryAction,
+        entity: &T,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<(SqliteQueryResult, Option<T>), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+        T: Audited + Entity + for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        let mut tx = conn.begin().await?;
+        let query = T::select_query(entity.key());
+        let stored = sqlx::query_as_with::<_, T, _>(&query.0, query.1)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let (query, after) = match action {
+            HistoryAction::Purge => (entity.delete_query(), None),
+            _ => (entity.update_query(), Some(entity)),
+        };
+        let result = sqlx::query_with(&query.0, query.1)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(stored) = &stored {
+            History::record_db(action, stored, after, author_id, &mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok((result, stored))
+    }
+
+    /// List the timeline of an Entity in the DB
+    ///
+    /// Lists the entries of one entity, oldest first.
+    /// # Arguements
+    /// * `entity` - The name the entity is recorded under, see [`Audited::ENTITY`]
+    /// * `entity_id` - The id of the entity
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_entity_list_db<'c, E>(
+        entity: &str,
+        entity_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<History>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = History::list_entity_query(entity, entity_id, None);
+        sqlx::query_as_with::<_, History, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Rewinds an Entity to its state at a datetime using the DB
+    ///
+    /// Undoes every change recorded after `at`, starting from the current state, or from nothing if the entity was
+    /// purged. Returns `None` if the entity did not exist at `at`.
+    /// # Arguments
+    /// * `current` - The current state of the entity, `None` if it was purged
+    /// * `entity_id` - The id of the entity
+    /// * `at` - The datetime to rewind to
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn rewind_db<'c, T: Audited, E>(
+        current: Option<T>,
+        entity_id: Uuid,
+        at: DateTime<Utc>,
+        executor: E,
+    ) -> Result<Option<T>, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = History::list_entity_query(T::ENTITY, entity_id, Some(at));
+        let entries = sqlx::query_as_with::<_, History, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await?;
+
+        let mut state = current;
+        for entry in entries.iter().rev() {
+            let entity = state.get_or_insert_with(|| {
+                let mut entity = T::default();
+                entity.set_audit_id(entity_id);
+                entity
+            });
+            if !entity.restore_field(&entry.field, entry.old_value.as_deref()) {
+                return Err(DbError::CorruptHistory(entry.field.clone()));
+            }
+        }
+
+        Ok(state.filter(|entity| entity.audit_created() <= at))
+    }
+
+    /// Get History ID
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Get History Entity
+    pub fn entity(&self) -> &String {
+        &self.entity
+    }
+
+    /// Get History Entity ID
+    pub fn entity_id(&self) -> &Uuid {
+        &self.entity_id
+    }
+
+    /// Get History Action
+    pub fn action(&self) -> HistoryAction {
+        self.action
+    }
+
+    /// Get History Field
+    pub fn field(&self) -> &String {
+        &self.field
+    }
+
+    /// Get History Old Value
+    pub fn old_value(&self) -> &Option<String> {
+        &self.old_value
+    }
+
+    /// Get History New Value
+    pub fn new_value(&self) -> &Option<String> {
+        &self.new_value
+    }
+
+    /// Get History Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Get History Changed
+    pub fn changed(&self) -> &DateTime<Utc> {
+        &self.changed
+    }
+
+    // Database Interactions
+
+    /// Inserts History to DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn insert<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to List the timeline of an Entity in the DB
+    ///
+    /// # Arguements
+    /// * `entity` - The name the entity is recorded under
+    /// * `entity_id` - The id of the entity
+    /// * `after` - Only list the entries changed after this datetime
+    pub fn list_entity_query(
+        entity: &str,
+        entity_id: Uuid,
+        after: Option<DateTime<Utc>>,
+    ) -> (String, SqlxValues) {
+        History::select()
+            .and_where(Expr::col(HistoryIden::Entity).eq(entity))
+            .and_where(Expr::col(HistoryIden::EntityId).eq(entity_id))
+            .and_where_option(after.map(|after| Expr::col(HistoryIden::Changed).gt(after)))
+            .order_by(HistoryIden::Id, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+/// The history is append-only, the DB refuses updates & deletes, so it has no [`Repository`](crate::db::Repository)
+impl Entity for History {
+    type Iden = HistoryIden;
+    type Key = i64;
+
+    const TABLE: HistoryIden = HistoryIden::Table;
+    const KEY: &'static [HistoryIden] = &[HistoryIden::Id];
+    const CREATED: Option<HistoryIden> = Some(HistoryIden::Changed);
+    const GENERATED_KEY: bool = true;
+    const COLUMNS: &'static [HistoryIden] = &[
+        HistoryIden::Id,
+        HistoryIden::Entity,
+        HistoryIden::EntityId,
+        HistoryIden::Action,
+        HistoryIden::Field,
+        HistoryIden::OldValue,
+        HistoryIden::NewValue,
+        HistoryIden::AuthorId,
+        HistoryIden::Changed,
+    ];
+    const ORDER_BY: Option<HistoryIden> = Some(HistoryIden::Id);
+
+    fn key(&self) -> i64 {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.entity.clone().into(),
+            self.entity_id.into(),
+            self.action.as_str().into(),
+            self.field.clone().into(),
+            self.old_value.clone().into(),
+            self.new_value.clone().into(),
+            self.author_id.into(),
+            self.changed.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, HistoryAction};
+    use crate::config::init_memory_db;
+    use crate::db::projects::Project;
+    use crate::db::users::create_test_user;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[test]
+    fn diff() {
+        let before = Project::new(
+            Uuid::default(),
+            "NAME".into(),
+            "DESC".into(),
+            Uuid::default(),
+        );
+        let mut after = before.clone();
+        after.set_description("CHANGED".into());
+
+        let changes = History::diff(
+            HistoryAction::Update,
+            &before,
+            Some(&after),
+            Uuid::default(),
+            Utc::now(),
+        );
+        let fields: Vec<_> = changes.iter().map(|entry| entry.field().as_str()).collect();
+        assert_eq!(fields, ["description", "modified"]);
+        assert_eq!(changes[0].old_value().as_deref(), Some("DESC"));
+        assert_eq!(changes[0].new_value().as_deref(), Some("CHANGED"));
+
+        let changes = History::diff(
+            HistoryAction::Purge,
+            &before,
+            None,
+            Uuid::default(),
+            Utc::now(),
+        );
+        assert_eq!(changes.len(), 8);
+        assert!(changes.iter().all(|entry| entry.new_value().is_none()));
+    }
+
+    #[tokio::test]
+    async fn append_only() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let project = Project::new(
+            Uuid::default(),
+            "NAME".into(),
+            "DESC".into(),
+            Uuid::default(),
+        );
+        History::record_db(HistoryAction::Purge, &project, None, editor, &mut conn)
+            .await
+            .unwrap();
+
+        let result = sqlx::query("DELETE FROM history").execute(&mut conn).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("history is append-only"));
+        let result = sqlx::query("UPDATE history SET author_id = NULL")
+            .execute(&mut conn)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,627 @@
+//! Journal Database Entity
+//! This file contains the undo journal, a log of the inserts, updates and deletes of [`Audited`] entities that
+//! [`undo`] and [`redo`] walk back and forth. The journal lives in the DB, so undo keeps working across restarts and
+//! is shared by every front-end using the same database.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::history::Audited;
+use crate::db::projects::Project;
+use crate::db::{DbError, Entity};
+
+/// The kind of mutation a journal entry records
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum JournalAction {
+    /// The entity was inserted, it has no state before
+    Insert,
+    /// The entity was updated
+    #[default]
+    Update,
+    /// The entity was (soft) deleted
+    Delete,
+    /// The project was merged into another one, the moved tasks are not journaled so undo & redo skip it
+    Merge,
+}
+
+impl JournalAction {
+    /// The name the action is stored as
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalAction::Insert => "insert",
+            JournalAction::Update => "update",
+            JournalAction::Delete => "delete",
+            JournalAction::Merge => "merge",
+        }
+    }
+}
+
+/// The journal database entity for taskify, one undoable mutation of an entity
+#[enum_def(table_name = "journal")]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct JournalEntry {
+    id: i64,
+    entity: String,
+    entity_id: Uuid,
+    action: JournalAction,
+    before: Option<String>,
+    after: Option<String>,
+    author_id: Uuid,
+    undone: bool,
+    created: DateTime<Utc>,
+}
+
+impl JournalEntry {
+    /// New Journal Entry
+    ///
+    /// Snapshots the states of the entity before and after the mutation.
+    /// # Arguments:
+    /// * `action` - The kind of mutation
+    /// * `before` - The state before the mutation, `None` for inserts
+    /// * `after` - The state after the mutation
+    /// * `author_id` - The id of the [`User`](super::users::User) that made the mutation
+    pub fn new<T: Audited>(
+        action: JournalAction,
+        before: Option<&T>,
+        after: &T,
+        author_id: Uuid,
+    ) -> Self {
+        Self {
+            entity: T::ENTITY.into(),
+            entity_id: after.audit_id(),
+            action,
+            before: before.map(JournalEntry::snapshot),
+            after: Some(JournalEntry::snapshot(after)),
+            author_id,
+            created: Utc::now(),
+            ..Default::default()
+        }
+    }
+
+    /// Records a mutation to the DB
+    ///
+    /// Recording a mutation clears the entries that were undone, they can no longer be redone.
+    /// # Arguments
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn record_db<'c, A>(&self, conn: A) -> Result<(), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = JournalEntry::clear_undone_query();
+        sqlx::query_with(&query.0, query.1)
+            .execute(&mut *tx)
+            .await?;
+        self.insert(&mut *tx).await?;
+        tx.commit().await
+    }
+
+    /// Forgets every entry of an Entity on DB
+    ///
+    /// Used when an entity is purged, its mutations can no longer be undone.
+    /// # Arguments
+    /// * `entity` - The name the entity is recorded under, see [`Audited::ENTITY`]
+    /// * `entity_id` - The id of the entity
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn forget_db<'c, E>(
+        entity: &str,
+        entity_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = JournalEntry::forget_query(entity, entity_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// List the Journal in the DB
+    ///
+    /// Lists the entries newest first, undone entries included.
+    /// # Arguements
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_list_db<'c, E>(
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<JournalEntry>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = JournalEntry::list_newest_query(page, limit);
+        sqlx::query_as_with::<_, JournalEntry, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Journal Entry ID
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Get Journal Entry Entity
+    pub fn entity(&self) -> &String {
+        &self.entity
+    }
+
+    /// Get Journal Entry Entity ID
+    pub fn entity_id(&self) -> &Uuid {
+        &self.entity_id
+    }
+
+    /// Get Journal Entry Action
+    pub fn action(&self) -> JournalAction {
+        self.action
+    }
+
+    /// Get Journal Entry Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Has the Journal Entry been undone?
+    pub fn is_undone(&self) -> bool {
+        self.undone
+    }
+
+    /// Get Journal Entry Created
+    pub fn created(&self) -> &DateTime<Utc> {
+        &self.created
+    }
+
+    /// Read a snapshot of the entry as an entity
+    ///
+    /// # Arguments
+    /// * `snapshot` - The snapshot, `None` if the entity did not exist
+    pub fn state<T: Audited>(&self, snapshot: &Option<String>) -> Result<Option<T>, DbError> {
+        let Some(snapshot) = snapshot else {
+            return Ok(None);
+        };
+        let fields: BTreeMap<String, String> =
+            toml::from_str(snapshot).map_err(|_| DbError::CorruptJournal(self.id))?;
+
+        let mut entity = T::default();
+        entity.set_audit_id(self.entity_id);
+        for (field, _) in T::default().audit_fields() {
+            if !entity.restore_field(field, fields.get(field).map(String::as_str)) {
+                return Err(DbError::CorruptJournal(self.id));
+            }
+        }
+        Ok(Some(entity))
+    }
+
+    // Database Interactions
+
+    /// Inserts Journal Entry to DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn insert<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to Mark a Journal Entry as undone or redone on DB
+    ///
+    /// # Arguments
+    /// * `undone` - Is the entry undone?
+    pub fn set_undone_query(&self, undone: bool) -> (String, SqlxValues) {
+        Query::update()
+            .table(JournalEntryIden::Table)
+            .values([(JournalEntryIden::Undone, undone.into())])
+            .and_where(Expr::col(JournalEntryIden::Id).eq(self.id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Delete the undone Journal Entries on DB
+    pub fn clear_undone_query() -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(JournalEntryIden::Table)
+            .and_where(Expr::col(JournalEntryIden::Undone).eq(true))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Delete the Journal Entries of an Entity on DB
+    ///
+    /// # Arguments
+    /// * `entity` - The name the entity is recorded under
+    /// * `entity_id` - The id of the entity
+    pub fn forget_query(entity: &str, entity_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(JournalEntryIden::Table)
+            .and_where(Expr::col(JournalEntryIden::Entity).eq(entity))
+            .and_where(Expr::col(JournalEntryIden::EntityId).eq(entity_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the next Journal Entry to undo or redo on DB
+    ///
+    /// The entry to undo is the newest entry that is not undone, the entry to redo the oldest undone one. Merges
+    /// are never undone, they are skipped.
+    /// # Arguments
+    /// * `undone` - Find the entry to redo?
+    pub fn select_next_query(undone: bool) -> (String, SqlxValues) {
+        JournalEntry::select()
+            .and_where(Expr::col(JournalEntryIden::Undone).eq(undone))
+            .and_where(Expr::col(JournalEntryIden::Action).ne(JournalAction::Merge.as_str()))
+            .order_by(
+                JournalEntryIden::Id,
+                if undone { Order::Asc } else { Order::Desc },
+            )
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Journal in the DB, newest first
+    ///
+    /// # Arguements
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_newest_query(page: u64, limit: u64) -> (String, SqlxValues) {
+        JournalEntry::select()
+            .order_by(JournalEntryIden::Id, Order::Desc)
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Serializes the recorded fields of an entity
+    fn snapshot<T: Audited>(entity: &T) -> String {
+        let fields: BTreeMap<_, _> = entity
+            .audit_fields()
+            .into_iter()
+            .filter_map(|(field, value)| value.map(|value| (field, value)))
+            .collect();
+
+        toml::to_string(&fields).expect("Error while serializing journal snapshot")
+    }
+
+    /// Moves the entity of the entry to one of its snapshots
+    ///
+    /// # Arguments
+    /// * `snapshot` - The snapshot to move to
+    /// * `author_id` - The id of the user that moves the entity
+    /// * `conn` - The SQLite database connection or transaction
+    async fn apply_db<'c, A>(
+        &self,
+        snapshot: &Option<String>,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<(), DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        match self.entity.as_str() {
+            entity if entity == Project::ENTITY => {
+                let state = self.state::<Project>(snapshot)?;
+                Project::restore_state_db(self.entity_id, state, author_id, conn).await?;
+            }
+            entity => return Err(DbError::UnknownEntity(entity.into())),
+        }
+        Ok(())
+    }
+}
+
+/// Undoes the newest mutation in the Journal on DB
+///
+/// Returns the undone entry, `None` if there is nothing to undo. The undo itself is recorded in the
+/// [`History`](super::history::History). [Merges](Project::merge_into_db) can not be undone, undo skips them and
+/// continues with the mutations made before.
+/// # Arguments
+/// * `author_id` - The id of the [`User`](super::users::User) that undoes the mutation
+/// * `conn` - The SQLite database connection or transaction
+pub async fn undo<'c, A>(author_id: Uuid, conn: A) -> Result<Option<JournalEntry>, DbError>
+where
+    A: Acquire<'c, Database = Sqlite>,
+{
+    step(true, author_id, conn).await
+}
+
+/// Redoes the oldest undone mutation in the Journal on DB
+///
+/// Returns the redone entry, `None` if there is nothing to redo.
+/// # Arguments
+/// * `author_id` - The id of the [`User`](super::users::User) that redoes the mutation
+/// * `conn` - The SQLite database connection or transaction
+pub async fn redo<'c, A>(author_id: Uuid, conn: A) -> Result<Option<JournalEntry>, DbError>
+where
+    A: Acquire<'c, Database = Sqlite>,
+{
+    step(false, author_id, conn).await
+}
+
+/// Undoes or redoes one Journal Entry on DB
+///
+/// # Arguments
+/// * `undo` - Undo, or redo?
+/// * `author_id` - The id of the user that undoes or redoes the mutation
+/// * `conn` - The SQLite database connection or transaction
+async fn step<'c, A>(undo: bool, author_id: Uuid, conn: A) -> Result<Option<JournalEntry>, DbError>
+where
+    A: Acquire<'c, Database = Sqlite>,
+{
+    let mut tx = conn.begin().await?;
+    let query = JournalEntry::select_next_query(!undo);
+    let Some(mut entry) = sqlx::query_as_with::<_, JournalEntry, _>(&query.0, query.1)
+        .fetch_optional(&mut *tx)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let snapshot = if undo { &entry.before } else { &entry.after };
+    entry.apply_db(snapshot, author_id, &mut *tx).await?;
+    let query = entry.set_undone_query(undo);
+    sqlx::query_with(&query.0, query.1)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    entry.undone = undo;
+    Ok(Some(entry))
+}
+
+/// Journal entries are only marked undone or forgotten, see [`JournalEntry::set_undone_query`]
+impl Entity for JournalEntry {
+    type Iden = JournalEntryIden;
+    type Key = i64;
+
+    const TABLE: JournalEntryIden = JournalEntryIden::Table;
+    const KEY: &'static [JournalEntryIden] = &[JournalEntryIden::Id];
+    const CREATED: Option<JournalEntryIden> = Some(JournalEntryIden::Created);
+    const GENERATED_KEY: bool = true;
+    const COLUMNS: &'static [JournalEntryIden] = &[
+        JournalEntryIden::Id,
+        JournalEntryIden::Entity,
+        JournalEntryIden::EntityId,
+        JournalEntryIden::Action,
+        JournalEntryIden::Before,
+        JournalEntryIden::After,
+        JournalEntryIden::AuthorId,
+        JournalEntryIden::Undone,
+        JournalEntryIden::Created,
+    ];
+    const ORDER_BY: Option<JournalEntryIden> = Some(JournalEntryIden::Id);
+
+    fn key(&self) -> i64 {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.entity.clone().into(),
+            self.entity_id.into(),
+            self.action.as_str().into(),
+            self.before.clone().into(),
+            self.after.clone().into(),
+            self.author_id.into(),
+            self.undone.into(),
+            self.created.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redo, undo, JournalAction, JournalEntry};
+    use crate::config::{init_db, init_memory_db, Database};
+    use crate::db::history::{History, HistoryAction};
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::Repository;
+    use sqlx::{Connection, SqliteConnection};
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let workspace = create_test_workspace(conn).await;
+        let author = create_test_user(conn).await;
+        let mut project = Project::new(*workspace.id(), "NAME".into(), "DESC".into(), *author.id());
+        project.assign_id().assign_created();
+        project
+    }
+
+    #[tokio::test]
+    async fn undo_redo() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        assert!(undo(editor, &mut conn).await.unwrap().is_none());
+
+        let mut project = create_project(&mut conn).await;
+        project.insert_db(&mut conn).await.unwrap();
+        let inserted = project.clone();
+        project.set_name("RENAMED".into());
+        project.update_db(editor, &mut conn).await.unwrap();
+        let renamed = project.clone();
+        project.delete_db(editor, &mut conn).await.unwrap();
+
+        let entry = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(entry.action(), JournalAction::Delete);
+        assert!(entry.is_undone());
+        assert_eq!(
+            Project::from_db(*project.id(), &mut conn).await.unwrap(),
+            renamed
+        );
+
+        let entry = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(entry.action(), JournalAction::Update);
+        assert_eq!(
+            Project::from_db(*project.id(), &mut conn).await.unwrap(),
+            inserted
+        );
+
+        let entry = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(entry.action(), JournalAction::Insert);
+        assert!(Project::from_db(*project.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_deleted());
+        assert!(undo(editor, &mut conn).await.unwrap().is_none());
+
+        for state in [inserted, renamed, project.clone()] {
+            redo(editor, &mut conn).await.unwrap().unwrap();
+            assert_eq!(
+                Project::from_db(*project.id(), &mut conn).await.unwrap(),
+                state
+            );
+        }
+        assert!(redo(editor, &mut conn).await.unwrap().is_none());
+
+        // undo & redo are part of the project's history
+        let timeline = History::from_entity_list_db("project", *project.id(), &mut conn)
+            .await
+            .unwrap();
+        assert!(timeline
+            .iter()
+            .any(|entry| entry.action() == HistoryAction::Delete));
+    }
+
+    #[tokio::test]
+    async fn undo_insert_keeps_tasks() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        project.insert_db(&mut conn).await.unwrap();
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+
+        let entry = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(entry.action(), JournalAction::Insert);
+        assert!(Project::from_db(*project.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_deleted());
+        assert_eq!(Task::from_db(*task.id(), &mut conn).await.unwrap(), task);
+
+        redo(editor, &mut conn).await.unwrap().unwrap();
+        assert!(!Project::from_db(*project.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_deleted());
+    }
+
+    #[tokio::test]
+    async fn mutation_clears_redo() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let mut project = create_project(&mut conn).await;
+        project.insert_db(&mut conn).await.unwrap();
+        project.set_name("RENAMED".into());
+        project.update_db(editor, &mut conn).await.unwrap();
+
+        undo(editor, &mut conn).await.unwrap().unwrap();
+        create_project(&mut conn)
+            .await
+            .insert_db(&mut conn)
+            .await
+            .unwrap();
+        assert!(redo(editor, &mut conn).await.unwrap().is_none());
+
+        let entries = JournalEntry::from_list_db(0, 10, &mut conn).await.unwrap();
+        let actions: Vec<_> = entries.iter().map(|entry| entry.action()).collect();
+        assert_eq!(actions, [JournalAction::Insert, JournalAction::Insert]);
+
+        project.purge(editor, &mut conn).await.unwrap();
+        assert_eq!(
+            JournalEntry::from_list_db(0, 10, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn undo_merge() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let mut from = create_project(&mut conn).await;
+        from.insert_db(&mut conn).await.unwrap();
+        let mut into = Project::new(
+            *from.workspace_id(),
+            "NAME".into(),
+            "DESC".into(),
+            *from.author_id(),
+        );
+        into.assign_id().assign_created();
+        into.insert_db(&mut conn).await.unwrap();
+        let mut task = Task::new(*from.id(), "TITLE".into(), "BODY".into(), editor);
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+
+        from.merge_into_db(&into, editor, &mut conn).await.unwrap();
+        // The merge is skipped, the insert of `into` made before it is undone
+        let undone = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(undone.action(), JournalAction::Insert);
+        assert_eq!(undone.entity_id(), into.id());
+        assert!(Project::from_db(*into.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_deleted());
+        assert_eq!(Project::from_db(*from.id(), &mut conn).await.unwrap(), from);
+        assert_eq!(
+            Task::from_db(*task.id(), &mut conn)
+                .await
+                .unwrap()
+                .project_id(),
+            into.id()
+        );
+
+        let undone = undo(editor, &mut conn).await.unwrap().unwrap();
+        assert_eq!(undone.entity_id(), from.id());
+        assert!(undo(editor, &mut conn).await.unwrap().is_none());
+
+        let entries = JournalEntry::from_list_db(0, 3, &mut conn).await.unwrap();
+        assert_eq!(entries[0].action(), JournalAction::Merge);
+        assert!(!entries[0].is_undone());
+        assert!(entries[1..].iter().all(|entry| entry.is_undone()));
+    }
+
+    #[tokio::test]
+    async fn undo_after_restart() {
+        let path =
+            std::env::temp_dir().join(format!("taskify-journal-{}.db", uuid::Uuid::new_v4()));
+        let config = Database {
+            path: path.to_string_lossy().into(),
+        };
+
+        let mut conn = init_db(&config).await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        project.insert_db(&mut conn).await.unwrap();
+        conn.close().await.unwrap();
+
+        let mut conn = init_db(&config).await.unwrap();
+        undo(editor, &mut conn).await.unwrap().unwrap();
+        assert!(Project::from_db(*project.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_deleted());
+        conn.close().await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,3 +1,141 @@
 //! Database Module
 //! This file contains all of the declarations for the database files
+use std::fmt::{Display, Formatter};
+
+use crate::recurrence::RecurrenceError;
+
+pub mod checklists;
+pub mod comments;
+pub mod custom_fields;
+pub mod dependencies;
+pub mod entity;
+pub mod filter;
+pub mod history;
+pub mod journal;
+pub mod notes;
 pub mod projects;
+pub mod reminders;
+pub mod search;
+pub mod sprints;
+pub mod store;
+pub mod tags;
+pub mod tasks;
+pub mod templates;
+pub mod time_entries;
+pub mod transitions;
+pub mod users;
+pub mod workflows;
+pub mod workspaces;
+
+pub use entity::{Entity, Key, Repository};
+pub use store::Store;
+
+/// The error returned by database helpers that validate data before writing it to the DB
+#[derive(Debug)]
+pub enum DbError {
+    /// The underlying sqlx error
+    Sqlx(sqlx::Error),
+    /// A stored workflow definition could not be parsed
+    Workflow(toml::de::Error),
+    /// The workflow names a state that is not one of its states
+    UnknownWorkflowState(String),
+    /// The urgency `age_max_days` is not greater than 0
+    InvalidUrgency(f64),
+    /// The trash `retention_days` is negative
+    InvalidRetention(i64),
+    /// The status is not part of the workflow
+    UnknownStatus(String),
+    /// The workflow does not allow moving from one status to the other
+    IllegalTransition { from: String, to: String },
+    /// The change would make a task its own ancestor
+    HierarchyCycle,
+    /// The dependency would make a task (transitively) block itself
+    DependencyCycle,
+    /// The author already has a running timer
+    TimerRunning,
+    /// A task can only be planned into a sprint of its own project
+    SprintProjectMismatch,
+    /// The value does not fit the kind or choices of the named custom field
+    InvalidFieldValue(String),
+    /// A custom field can only be set on tasks of its own project
+    FieldProjectMismatch,
+    /// A recorded change of the named field could not be undone
+    CorruptHistory(String),
+    /// The snapshot of the journal entry with the id could not be read
+    CorruptJournal(i64),
+    /// The journal names an entity that can not be undone
+    UnknownEntity(String),
+    /// A stored template definition could not be parsed
+    Template(toml::de::Error),
+    /// The template uses a variable that was not given a value
+    UnknownVariable(String),
+    /// The ids are not exactly the items of the checklist
+    ChecklistMismatch,
+    /// An entity can not be merged into itself
+    SelfMerge,
+    /// Entities can not be moved into a project in the trash bin
+    ProjectDeleted,
+    /// Only workspaces without projects & standalone notes can be deleted
+    WorkspaceNotEmpty,
+    /// Entities of different workspaces can not be combined
+    WorkspaceMismatch,
+    /// A stored recurrence rule could not be parsed
+    Recurrence(RecurrenceError),
+}
+
+impl Display for DbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbError::Sqlx(err) => write!(f, "{}", err),
+            DbError::Workflow(err) => write!(f, "invalid workflow definition: {}", err),
+            DbError::UnknownWorkflowState(state) => {
+                write!(f, "the workflow does not list the state `{}`", state)
+            }
+            DbError::InvalidUrgency(days) => {
+                write!(f, "urgency age_max_days must be positive, got {}", days)
+            }
+            DbError::InvalidRetention(days) => {
+                write!(f, "trash retention_days must not be negative, got {}", days)
+            }
+            DbError::UnknownStatus(status) => write!(f, "unknown status `{}`", status),
+            DbError::IllegalTransition { from, to } => {
+                write!(f, "illegal transition from `{}` to `{}`", from, to)
+            }
+            DbError::HierarchyCycle => write!(f, "a task can not be its own ancestor"),
+            DbError::DependencyCycle => write!(f, "a task can not block itself"),
+            DbError::TimerRunning => write!(f, "a timer is already running"),
+            DbError::SprintProjectMismatch => {
+                write!(f, "a task can only be planned into a sprint of its project")
+            }
+            DbError::InvalidFieldValue(field) => write!(f, "invalid value for field `{}`", field),
+            DbError::FieldProjectMismatch => {
+                write!(f, "a custom field can only be set on tasks of its project")
+            }
+            DbError::CorruptHistory(field) => {
+                write!(f, "the history of field `{}` is corrupt", field)
+            }
+            DbError::CorruptJournal(id) => write!(f, "the journal entry {} is corrupt", id),
+            DbError::UnknownEntity(entity) => write!(f, "unknown entity `{}`", entity),
+            DbError::Template(err) => write!(f, "invalid template definition: {}", err),
+            DbError::UnknownVariable(name) => write!(f, "unknown template variable `{}`", name),
+            DbError::ChecklistMismatch => {
+                write!(f, "the ids must list every checklist item exactly once")
+            }
+            DbError::SelfMerge => write!(f, "can not merge into itself"),
+            DbError::ProjectDeleted => write!(f, "the project is in the trash bin"),
+            DbError::WorkspaceNotEmpty => write!(f, "only empty workspaces can be deleted"),
+            DbError::WorkspaceMismatch => {
+                write!(f, "can not combine entities of different workspaces")
+            }
+            DbError::Recurrence(err) => write!(f, "invalid recurrence rule: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<sqlx::Error> for DbError {
+    fn from(err: sqlx::Error) -> Self {
+        DbError::Sqlx(err)
+    }
+}
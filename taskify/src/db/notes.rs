@@ -0,0 +1,452 @@
+//! Note Database Entity
+//! This file contains the note database entity, Markdown notes that are either attached to a
+//! [`Project`](super::projects::Project) or a [`Task`](super::tasks::Task), or standalone in a
+//! [`Workspace`](super::workspaces::Workspace). Attached notes are deleted together with their owner.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Order, Query, SelectStatement, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::filter::{ListSpec, Sort};
+use crate::db::projects::Project;
+use crate::db::tasks::TaskIden;
+use crate::db::{Entity, Repository};
+
+/// What a note is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteOwner {
+    /// The note isn't attached to a project or task, it belongs to the workspace with the id
+    Standalone(Uuid),
+    /// The note is attached to the project with the id
+    Project(Uuid),
+    /// The note is attached to the task with the id
+    Task(Uuid),
+}
+
+/// The note database entity for taskify, the body is Markdown
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Note {
+    id: Uuid,
+    project_id: Option<Uuid>,
+    task_id: Option<Uuid>,
+    workspace_id: Option<Uuid>,
+    title: String,
+    body: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+impl Note {
+    /// New Note
+    ///
+    /// Create a new note entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `owner` - What the note is attached to
+    /// * `title` - The note title
+    /// * `body` - The Markdown note body
+    pub fn new(owner: NoteOwner, title: String, body: String) -> Self {
+        let mut note = Self {
+            title,
+            body,
+            ..Default::default()
+        };
+        note.assign_owner(owner);
+        note
+    }
+
+    /// List all Notes in the DB
+    ///
+    /// Lists the standalone notes of a workspace and the notes of its projects & tasks matching the spec, most
+    /// recently modified first unless it sorts them. The notes of deleted projects are left out.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the notes of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_workspace_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<NoteIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Note>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Note::list_workspace_query(workspace_id, spec, page, limit);
+        sqlx::query_as_with::<_, Note, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Notes of an owner in the DB
+    ///
+    /// Lists the notes attached to a project or task, or the standalone notes of a workspace, most recently modified
+    /// first.
+    /// # Arguements
+    /// * `owner` - The owner of the notes
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_owner_list_db<'c, E>(
+        owner: NoteOwner,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Note>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Note::list_owner_query(owner, page, limit);
+        sqlx::query_as_with::<_, Note, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Note ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Note Owner
+    pub fn owner(&self) -> NoteOwner {
+        match (self.project_id, self.task_id) {
+            (Some(project_id), _) => NoteOwner::Project(project_id),
+            (_, Some(task_id)) => NoteOwner::Task(task_id),
+            (None, None) => NoteOwner::Standalone(self.workspace_id.unwrap_or_default()),
+        }
+    }
+
+    /// Get Note Title
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    /// Get Note Body
+    pub fn body(&self) -> &String {
+        &self.body
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the note.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the note `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Owner
+    ///
+    /// Attaches the note to a project or task, or detaches it
+    /// # Arguments
+    /// * `owner` - What the note is attached to
+    pub fn set_owner(&mut self, owner: NoteOwner) {
+        self.assign_owner(owner);
+        self.edited();
+    }
+
+    /// Set Title
+    ///
+    /// Sets the note title
+    /// # Arguments
+    /// * `title` - The string title to use
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+        self.edited();
+    }
+
+    /// Set Body
+    ///
+    /// Sets the note body
+    /// # Arguments
+    /// * `body` - The Markdown body to use
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Generates a sqlx query to List all Notes in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the notes of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_workspace_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<NoteIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let mut select = Note::select();
+        select
+            .and_where(Expr::col(NoteIden::Id).in_subquery(Note::workspace_subquery(workspace_id)));
+
+        spec.apply(&mut select, Some(Sort::Desc(NoteIden::Modified)))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Notes of an owner in the DB
+    ///
+    /// # Arguements
+    /// * `owner` - The owner of the notes
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_owner_query(owner: NoteOwner, page: u64, limit: u64) -> (String, SqlxValues) {
+        let mut query = Note::select();
+
+        match owner {
+            NoteOwner::Standalone(workspace_id) => {
+                query.and_where(Expr::col(NoteIden::WorkspaceId).eq(workspace_id))
+            }
+            NoteOwner::Project(project_id) => {
+                query.and_where(Expr::col(NoteIden::ProjectId).eq(project_id))
+            }
+            NoteOwner::Task(task_id) => query.and_where(Expr::col(NoteIden::TaskId).eq(task_id)),
+        };
+
+        query
+            .order_by(NoteIden::Modified, Order::Desc)
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// The sub query selecting the ids of the notes in a workspace
+    ///
+    /// Selects the standalone notes of the workspace and the notes of its projects & tasks, the notes of deleted
+    /// projects are left out.
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace
+    pub(crate) fn workspace_subquery(workspace_id: Uuid) -> SelectStatement {
+        let projects = Project::workspace_subquery(workspace_id);
+        let tasks = Query::select()
+            .column(TaskIden::Id)
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::ProjectId).in_subquery(projects.clone()))
+            .to_owned();
+
+        Query::select()
+            .column(NoteIden::Id)
+            .from(NoteIden::Table)
+            .and_where(
+                Expr::col(NoteIden::WorkspaceId)
+                    .eq(workspace_id)
+                    .or(Expr::col(NoteIden::ProjectId).in_subquery(projects))
+                    .or(Expr::col(NoteIden::TaskId).in_subquery(tasks)),
+            )
+            .to_owned()
+    }
+
+    // Private Functions
+
+    /// Sets the owner columns from a [`NoteOwner`]
+    fn assign_owner(&mut self, owner: NoteOwner) {
+        (self.project_id, self.task_id, self.workspace_id) = match owner {
+            NoteOwner::Standalone(workspace_id) => (None, None, Some(workspace_id)),
+            NoteOwner::Project(project_id) => (Some(project_id), None, None),
+            NoteOwner::Task(task_id) => (None, Some(task_id), None),
+        };
+    }
+
+    /// Set Edited
+    ///
+    /// Sets the note edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for Note {
+    type Iden = NoteIden;
+    type Key = Uuid;
+
+    const TABLE: NoteIden = NoteIden::Table;
+    const KEY: &'static [NoteIden] = &[NoteIden::Id];
+    const CREATED: Option<NoteIden> = Some(NoteIden::Created);
+    const COLUMNS: &'static [NoteIden] = &[
+        NoteIden::Id,
+        NoteIden::ProjectId,
+        NoteIden::TaskId,
+        NoteIden::WorkspaceId,
+        NoteIden::Title,
+        NoteIden::Body,
+        NoteIden::Created,
+        NoteIden::Modified,
+    ];
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.project_id.into(),
+            self.task_id.into(),
+            self.workspace_id.into(),
+            self.title.clone().into(),
+            self.body.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Notes are kept in the search index by triggers, inserting, updating & deleting one also updates the index
+impl Repository for Note {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Note, NoteOwner};
+    use crate::config::init_memory_db;
+    use crate::db::filter::ListSpec;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{Entity, Repository};
+    use sqlx::{Error, SqliteConnection};
+    use uuid::Uuid;
+
+    async fn create_note(owner: NoteOwner, conn: &mut SqliteConnection) -> Note {
+        let mut note = Note::new(owner, "TITLE".into(), "# Body".into());
+        note.assign_id().assign_created();
+        note.insert(conn).await.unwrap();
+        note
+    }
+
+    #[test]
+    fn insert() {
+        let query = Note::new(
+            NoteOwner::Standalone(Uuid::default()),
+            "TITLE".into(),
+            "BODY".into(),
+        )
+        .insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"note\" (\"id\", \"project_id\", \"task_id\", \"workspace_id\", \"title\", \"body\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn list_owner() {
+        let query = Note::list_owner_query(NoteOwner::Standalone(Uuid::default()), 0, 10);
+
+        assert_eq!(query.0, "SELECT \"id\", \"project_id\", \"task_id\", \"workspace_id\", \"title\", \"body\", \"created\", \"modified\" FROM \"note\" WHERE \"workspace_id\" = ? ORDER BY \"modified\" DESC LIMIT ? OFFSET ?");
+    }
+
+    #[test]
+    fn owner() {
+        let id = Uuid::new_v4();
+        let mut note = Note::new(NoteOwner::Task(id), "TITLE".into(), "BODY".into());
+        assert_eq!(note.owner(), NoteOwner::Task(id));
+
+        note.set_owner(NoteOwner::Project(id));
+        assert_eq!(note.owner(), NoteOwner::Project(id));
+
+        note.set_owner(NoteOwner::Standalone(id));
+        assert_eq!(note.owner(), NoteOwner::Standalone(id));
+    }
+
+    #[tokio::test]
+    async fn owner_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let mut project = Project::new(
+            workspace,
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(&mut conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+
+        let standalone = create_note(NoteOwner::Standalone(workspace), &mut conn).await;
+        let project_note = create_note(NoteOwner::Project(*project.id()), &mut conn).await;
+        let task_note = create_note(NoteOwner::Task(*task.id()), &mut conn).await;
+
+        for (owner, note) in [
+            (NoteOwner::Standalone(workspace), &standalone),
+            (NoteOwner::Project(*project.id()), &project_note),
+            (NoteOwner::Task(*task.id()), &task_note),
+        ] {
+            let notes = Note::from_owner_list_db(owner, 0, 10, &mut conn)
+                .await
+                .unwrap();
+            assert_eq!(notes, vec![note.clone()]);
+        }
+        assert_eq!(
+            Note::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+
+        // the notes of other workspaces are left out
+        let other = *create_test_workspace(&mut conn).await.id();
+        create_note(NoteOwner::Standalone(other), &mut conn).await;
+        assert!(
+            Note::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .iter()
+                .all(|note| note.owner() != NoteOwner::Standalone(other))
+        );
+
+        task.delete_db(*task.author_id(), &mut conn).await.unwrap();
+        assert!(matches!(
+            Note::from_db(*task_note.id(), &mut conn).await,
+            Err(Error::RowNotFound)
+        ));
+
+        project.purge(editor, &mut conn).await.unwrap();
+        assert!(matches!(
+            Note::from_db(*project_note.id(), &mut conn).await,
+            Err(Error::RowNotFound)
+        ));
+        assert_eq!(
+            Note::from_db(*standalone.id(), &mut conn).await.unwrap(),
+            standalone
+        );
+    }
+
+    #[tokio::test]
+    async fn update_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let mut note = create_note(NoteOwner::Standalone(workspace), &mut conn).await;
+
+        note.set_title("NEW_TITLE".into());
+        note.set_body("- [ ] item".into());
+        note.update(&mut conn).await.unwrap();
+
+        assert_eq!(Note::from_db(*note.id(), &mut conn).await.unwrap(), note);
+    }
+}
@@ -1,21 +1,33 @@
 //! Project Database Entity
 //! This file contains the database entity for taskify.
 
-use chrono::{DateTime, Utc};
-use sea_query::{enum_def, Expr, Query, SqliteQueryBuilder};
+use chrono::{DateTime, Duration, Utc};
+use sea_query::{enum_def, Expr, Query, SelectStatement, SimpleExpr, SqliteQueryBuilder};
 use sea_query_binder::{SqlxBinder, SqlxValues};
 use sqlx::sqlite::SqliteQueryResult;
-use sqlx::{Error, FromRow, SqliteConnection};
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
 use uuid::Uuid;
 
+use crate::config::Trash;
+use crate::db::custom_fields::CustomField;
+use crate::db::filter::{ListSpec, Sort};
+use crate::db::history::{Audited, History, HistoryAction};
+use crate::db::journal::{JournalAction, JournalEntry};
+use crate::db::tags::{ProjectTagIden, Tag};
+use crate::db::tasks::Task;
+use crate::db::{DbError, Entity, Repository};
+
 /// The database entity for taskify
 #[enum_def]
 #[derive(Debug, Default, Clone, PartialEq, FromRow)]
 pub struct Project {
     id: Uuid,
+    workspace_id: Uuid,
     name: String,
     description: String,
-    author: String,
+    author_id: Uuid,
+    archived: Option<DateTime<Utc>>,
+    deleted: Option<DateTime<Utc>>,
     created: DateTime<Utc>,
     modified: DateTime<Utc>,
 }
@@ -25,49 +37,159 @@ impl Project {
     ///
     /// Create a new project entity with a default id, created and edited.
     /// # Arguments:
+    /// * `workspace_id` - The id of the [`Workspace`](super::workspaces::Workspace) the project belongs to
     /// * `name` - The project name
     /// * `description` - The project description
-    pub fn new(name: String, description: String, author: String) -> Self {
+    /// * `author_id` - The id of the user that authors the project
+    pub fn new(workspace_id: Uuid, name: String, description: String, author_id: Uuid) -> Self {
         Self {
+            workspace_id,
             name,
             description,
-            author,
+            author_id,
             ..Default::default()
         }
     }
 
-    /// Load a project from the DB
+    /// Generates a sqlx query to List all Projects in the DB
     ///
-    /// Finds a project in the DB by providing the id (uuid) value and wraps it in a Project{} struct.
-    /// # Arguments
-    /// * `id` - The uuid v4 id to search for
-    /// * `conn` - The SQLite database connection
-    pub async fn from_db(id: Uuid, conn: &mut SqliteConnection) -> Result<Self, Error> {
-        let query = Project::select_query(id);
+    /// Lists the projects matching the spec by providing a page and limit count, archived and deleted projects are
+    /// left out. The projects are ordered by name unless the spec sorts them.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_workspace_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Project>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Project::list_workspace_query(workspace_id, spec, page, limit);
+        sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
 
+    /// List the Projects tagged with every one of the Tags in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `tag_ids` - The ids of the tags, a project must have all of them, no tags list every project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_tagged_list_db<'c, E>(
+        workspace_id: Uuid,
+        tag_ids: &[Uuid],
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Project>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Project::list_tagged_query(workspace_id, tag_ids, spec, page, limit);
         sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
-            .fetch_one(conn)
+            .fetch_all(executor)
             .await
     }
 
-    /// Generates a sqlx query to List all Projects in the DB
+    /// List the archived Projects in the DB
     ///
-    /// Lists all projects in the DB by providing a page and limit count.
+    /// Deleted projects are left out, even when they were archived first.
     /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
     /// * `page` - The query page
     /// * `limit` - The row per page
-    /// * `conn` - The SQLite database connection
-    pub async fn from_list_db(
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_archived_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
         page: u64,
         limit: u64,
-        conn: &mut SqliteConnection,
-    ) -> Result<Vec<Project>, Error> {
-        let query = Project::list_query(page, limit);
+        executor: E,
+    ) -> Result<Vec<Project>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Project::list_archived_query(workspace_id, spec, page, limit);
         sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
-            .fetch_all(conn)
+            .fetch_all(executor)
             .await
     }
 
+    /// List the deleted Projects in the DB, the trash bin
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_trash_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Project>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Project::list_trash_query(workspace_id, spec, page, limit);
+        sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Load a project as it was at a datetime from the DB
+    ///
+    /// Rewinds the project using its [`History`], this also works for purged projects. Returns `None` if the
+    /// project did not exist at that time.
+    /// # Arguments
+    /// * `id` - The uuid v4 id to search for
+    /// * `at` - The datetime to load the project at
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn from_history_db<'c, A>(
+        id: Uuid,
+        at: DateTime<Utc>,
+        conn: A,
+    ) -> Result<Option<Self>, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Project::select_query(id);
+        let current = sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let state = History::rewind_db(current, id, at, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(state)
+    }
+
+    /// Get Project ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Project Workspace ID
+    pub fn workspace_id(&self) -> &Uuid {
+        &self.workspace_id
+    }
+
     /// Get Project Name
     pub fn name(&self) -> &String {
         &self.name
@@ -78,9 +200,29 @@ impl Project {
         &self.description
     }
 
-    /// Get Project Author
-    pub fn author(&self) -> &String {
-        &self.author
+    /// Get Project Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Get when the Project was archived
+    pub fn archived(&self) -> &Option<DateTime<Utc>> {
+        &self.archived
+    }
+
+    /// Get when the Project was deleted
+    pub fn deleted(&self) -> &Option<DateTime<Utc>> {
+        &self.deleted
+    }
+
+    /// Is the Project archived?
+    pub fn is_archived(&self) -> bool {
+        self.archived.is_some()
+    }
+
+    /// Is the Project in the trash bin?
+    pub fn is_deleted(&self) -> bool {
+        self.deleted.is_some()
     }
 
     /// ID Assignment
@@ -110,6 +252,16 @@ impl Project {
         self
     }
 
+    /// Set Workspace ID
+    ///
+    /// Moves the project, with its tasks, to another workspace
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace to move to
+    pub fn set_workspace_id(&mut self, workspace_id: Uuid) {
+        self.workspace_id = workspace_id;
+        self.edited();
+    }
+
     /// Set Name
     ///
     /// Sets the project display name
@@ -134,125 +286,282 @@ impl Project {
 
     /// Inserts Project to DB
     ///
+    /// The insert is recorded in the [`JournalEntry`] journal as made by the project author, so it can be undone.
     /// # Arguments
-    /// * `conn` - The SQLite database connection
-    pub async fn insert(&self, conn: &mut SqliteConnection) -> Result<SqliteQueryResult, Error> {
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn insert_db<'c, A>(&self, conn: A) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
         let query = self.insert_query();
+        let result = sqlx::query_with(&query.0, query.1)
+            .execute(&mut *tx)
+            .await?;
+        JournalEntry::new(JournalAction::Insert, None, self, self.author_id)
+            .record_db(&mut *tx)
+            .await?;
+        tx.commit().await?;
 
-        sqlx::query_with(&query.0, query.1).execute(conn).await
+        Ok(result)
     }
 
-    /// Generates a sqlx query to Insert Project to DB
+    /// Updates a Project on DB
     ///
-    /// # Examples
-    /// ```
-    /// # #[tokio::test]
-    /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
-    /// taskify::db::projects::Project::new("Name".into(), "Desc".into(), "Author".into()).insert_query();
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn insert_query(&self) -> (String, SqlxValues) {
-        Query::insert()
-            .into_table(ProjectIden::Table)
-            .columns([
-                ProjectIden::Id,
-                ProjectIden::Name,
-                ProjectIden::Description,
-                ProjectIden::Author,
-                ProjectIden::Created,
-                ProjectIden::Modified,
-            ])
-            .values([
-                self.id.into(),
-                self.name.clone().into(),
-                self.description.clone().into(),
-                self.author.clone().into(),
-                self.created.into(),
-                self.modified.into(),
-            ])
-            .unwrap()
-            .build_sqlx(SqliteQueryBuilder)
+    /// Records the changed fields in the [`History`].
+    /// # Arguments
+    /// * `author_id` - The id of the user that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn update_db<'c, A>(
+        &self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        self.audit_db(JournalAction::Update, author_id, conn).await
     }
 
-    /// Updates a Project on DB
+    /// Archives a Project on DB
     ///
+    /// Archived projects are left out of the project list until they are unarchived.
     /// # Arguments
-    /// * `conn` - The SQLite database connection
-    pub async fn update(&self, conn: &mut SqliteConnection) -> Result<SqliteQueryResult, Error> {
-        let query = self.update_query();
+    /// * `author_id` - The id of the user that archives the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn archive_db<'c, A>(
+        &mut self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut project = self.clone();
+        project.archived = Some(Utc::now());
+        project.edited();
 
-        sqlx::query_with(&query.0, query.1).execute(conn).await
+        let result = project.update_db(author_id, conn).await?;
+        *self = project;
+        Ok(result)
     }
 
-    /// Generates a sqlx query to Update Project on DB
+    /// Unarchives a Project on DB
     ///
-    /// # Examples
-    /// ```
-    /// # #[tokio::test]
-    /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
-    /// taskify::db::projects::Project::new("Name".into(), "Desc".into(), "Author".into()).update_query();
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn update_query(&self) -> (String, SqlxValues) {
-        Query::update()
-            .table(ProjectIden::Table)
-            .values([
-                (ProjectIden::Name, self.name.clone().into()),
-                (ProjectIden::Description, self.description.clone().into()),
-                (ProjectIden::Author, self.author.clone().into()),
-                (ProjectIden::Modified, self.modified.into()),
-            ])
-            .and_where(Expr::col(ProjectIden::Id).eq(self.id.clone()))
-            .build_sqlx(SqliteQueryBuilder)
+    /// # Arguments
+    /// * `author_id` - The id of the user that unarchives the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn unarchive_db<'c, A>(
+        &mut self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut project = self.clone();
+        project.archived = None;
+        project.edited();
+
+        let result = project.update_db(author_id, conn).await?;
+        *self = project;
+        Ok(result)
+    }
+
+    /// Merges a Project into another one on DB
+    ///
+    /// Moves every task of the project to the other project, taking them out of their sprints, then archives the
+    /// project. The custom field values of the tasks move to the field of the other project with the same name, kind
+    /// & choices, values without such a field are deleted. The project's own field values stay with the archived
+    /// project. Everything happens in one transaction, if any step fails nothing is moved or archived. Returns how many
+    /// tasks were moved. The merge is journaled but can not be [undone](super::journal::undo), undo skips it.
+    ///
+    /// Both projects are reloaded in the transaction, the stored project replaces `self`. Merging into itself fails
+    /// with [`DbError::SelfMerge`], from or into a deleted project with [`DbError::ProjectDeleted`] and into a project
+    /// of another workspace with [`DbError::WorkspaceMismatch`].
+    /// # Arguments
+    /// * `into` - The project to move the tasks to
+    /// * `author_id` - The id of the user that merges the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn merge_into_db<'c, A>(
+        &mut self,
+        into: &Project,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        if into.id == self.id {
+            return Err(DbError::SelfMerge);
+        }
+        let mut tx = conn.begin().await?;
+        let mut project = Project::from_db(self.id, &mut *tx).await?;
+        let into = Project::from_db(into.id, &mut *tx).await?;
+        project.check_move(&into)?;
+
+        let moved = Task::move_project_db(self.id, into.id, author_id, &mut *tx).await?;
+        CustomField::remap_values_db(self.id, into.id, &mut *tx).await?;
+        project.archived = Some(Utc::now());
+        project.edited();
+        project
+            .audit_db(JournalAction::Merge, author_id, &mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        *self = project;
+        Ok(moved)
+    }
+
+    /// Checks that tasks can move from the project to `into`
+    ///
+    /// Fails with [`DbError::ProjectDeleted`] if either project is in the trash bin and with
+    /// [`DbError::WorkspaceMismatch`] if they belong to different workspaces.
+    /// # Arguments
+    /// * `into` - The project the tasks move to
+    pub(crate) fn check_move(&self, into: &Project) -> Result<(), DbError> {
+        if self.is_deleted() || into.is_deleted() {
+            return Err(DbError::ProjectDeleted);
+        }
+        if into.workspace_id != self.workspace_id {
+            return Err(DbError::WorkspaceMismatch);
+        }
+        Ok(())
     }
 
     /// Deletes a Project on DB
     ///
+    /// Moves the project to the trash bin, it is left out of the project lists until it is
+    /// [restored](Project::restore_db) or [purged](Project::purge_db). Use [`Project::purge`] to delete it right away.
+    /// # Arguments
+    /// * `author_id` - The id of the user that deletes the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn delete_db<'c, A>(
+        &mut self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut project = self.clone();
+        project.deleted = Some(Utc::now());
+        project.edited();
+
+        let result = project
+            .audit_db(JournalAction::Delete, author_id, conn)
+            .await?;
+        *self = project;
+        Ok(result)
+    }
+
+    /// Restores a deleted Project on DB
+    ///
+    /// The project keeps being archived if it was archived before it was deleted.
     /// # Arguments
-    /// * `conn` - The SQLite database connection
-    pub async fn delete(&self, conn: &mut SqliteConnection) -> Result<SqliteQueryResult, Error> {
-        let query = self.delete_query();
+    /// * `author_id` - The id of the user that restores the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn restore_db<'c, A>(
+        &mut self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut project = self.clone();
+        project.deleted = None;
+        project.edited();
 
-        sqlx::query_with(&query.0, query.1).execute(conn).await
+        let result = project.update_db(author_id, conn).await?;
+        *self = project;
+        Ok(result)
     }
 
-    /// Generates a sqlx query to Delete Project on DB
+    /// Permanently deletes a Project on DB
+    ///
+    /// Purging a project also deletes all of its tasks, its last state is kept in the [`History`]. A purge can not be
+    /// undone, the project is removed from the undo journal.
+    /// # Arguments
+    /// * `author_id` - The id of the user that purges the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn purge<'c, A>(&self, author_id: Uuid, conn: A) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        // The tasks are deleted with the project (`ON DELETE CASCADE`), their last state is kept in the history too
+        let query = Task::select_project_query(self.id);
+        let tasks = sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(&mut *tx)
+            .await?;
+        for task in &tasks {
+            History::record_db(HistoryAction::Purge, task, None, author_id, &mut *tx).await?;
+        }
+        let (result, _) =
+            History::audit_db(HistoryAction::Purge, self, author_id, &mut *tx).await?;
+        JournalEntry::forget_db(Project::ENTITY, self.id, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Generates a sqlx query to Permanently delete Project on DB
+    ///
+    /// The [`Entity::delete_query`], named after the purge as [`Project::delete_db`] only moves to the trash bin.
     ///
     /// # Examples
     /// ```
     /// # #[tokio::test]
     /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
-    /// taskify::db::projects::Project::new("Name".into(), "Desc".into(), "Author".into()).delete_query();
+    /// taskify::db::projects::Project::new(uuid::Uuid::new_v4(), "Name".into(), "Desc".into(), uuid::Uuid::new_v4()).purge_query();
     /// # Ok(())
     /// # }
     /// ```
-    pub fn delete_query(&self) -> (String, SqlxValues) {
-        Query::delete()
-            .from_table(ProjectIden::Table)
-            .and_where(Expr::col(ProjectIden::Id).eq(self.id.clone()))
-            .build_sqlx(SqliteQueryBuilder)
+    pub fn purge_query(&self) -> (String, SqlxValues) {
+        self.delete_query()
     }
 
-    /// Generates a sqlx query to Find a Project on DB
+    /// Empties the trash bin on DB
     ///
-    /// Finds a project in the DB by providing the id (uuid) value.
+    /// Permanently deletes every project that has been in the trash bin for longer than the retention period and
+    /// returns how many were deleted.
     /// # Arguments
-    /// * `id` - The uuid v4 id to search for
-    pub fn select_query(id: Uuid) -> (String, SqlxValues) {
-        Query::select()
-            .columns([
-                ProjectIden::Id,
-                ProjectIden::Name,
-                ProjectIden::Description,
-                ProjectIden::Author,
-                ProjectIden::Created,
-                ProjectIden::Modified,
-            ])
-            .from(ProjectIden::Table)
-            .and_where(Expr::col(ProjectIden::Id).eq(id))
-            .limit(1)
+    /// * `now` - The current datetime
+    /// * `trash` - The trash bin configuration
+    /// * `author_id` - The id of the user that empties the trash bin
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn purge_db<'c, A>(
+        now: DateTime<Utc>,
+        trash: &Trash,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Project::list_expired_query(now - Duration::days(trash.retention_days));
+        let expired = sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut purged = 0;
+        for project in expired {
+            purged += project.purge(author_id, &mut *tx).await?.rows_affected();
+        }
+        tx.commit().await?;
+
+        Ok(purged)
+    }
+
+    /// Generates a sqlx query to List the Projects deleted before a datetime in the DB
+    ///
+    /// # Arguments
+    /// * `before` - Projects deleted at or before this datetime are listed
+    pub fn list_expired_query(before: DateTime<Utc>) -> (String, SqlxValues) {
+        Project::select()
+            .and_where(Expr::col(ProjectIden::Deleted).lte(before))
             .build_sqlx(SqliteQueryBuilder)
     }
 
@@ -260,24 +569,197 @@ impl Project {
     ///
     /// Lists all projects in the DB by providing a page and limit count.
     /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
     /// * `page` - The query page
     /// * `limit` - The row per page
-    pub fn list_query(page: u64, limit: u64) -> (String, SqlxValues) {
-        Query::select()
-            .columns([
-                ProjectIden::Id,
-                ProjectIden::Name,
-                ProjectIden::Description,
-                ProjectIden::Author,
-                ProjectIden::Created,
-                ProjectIden::Modified,
-            ])
-            .from(ProjectIden::Table)
+    pub fn list_workspace_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Project::list_select(spec)
+            .and_where(Expr::col(ProjectIden::WorkspaceId).eq(workspace_id))
+            .and_where(Expr::col(ProjectIden::Archived).is_null())
+            .and_where(Expr::col(ProjectIden::Deleted).is_null())
             .limit(limit)
             .offset(page * limit)
             .build_sqlx(SqliteQueryBuilder)
     }
 
+    /// Generates a sqlx query to List the archived Projects in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_archived_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        spec.apply(
+            &mut Project::select(),
+            Some(Sort::Desc(ProjectIden::Archived)),
+        )
+        .and_where(Expr::col(ProjectIden::WorkspaceId).eq(workspace_id))
+        .and_where(Expr::col(ProjectIden::Archived).is_not_null())
+        .and_where(Expr::col(ProjectIden::Deleted).is_null())
+        .limit(limit)
+        .offset(page * limit)
+        .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the deleted Projects in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_trash_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        spec.apply(
+            &mut Project::select(),
+            Some(Sort::Desc(ProjectIden::Deleted)),
+        )
+        .and_where(Expr::col(ProjectIden::WorkspaceId).eq(workspace_id))
+        .and_where(Expr::col(ProjectIden::Deleted).is_not_null())
+        .limit(limit)
+        .offset(page * limit)
+        .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Projects tagged with every one of the Tags in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the projects of
+    /// * `tag_ids` - The ids of the tags, a project must have all of them, no tags list every project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_tagged_query(
+        workspace_id: Uuid,
+        tag_ids: &[Uuid],
+        spec: &ListSpec<ProjectIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Project::list_select(spec)
+            .and_where(Expr::col(ProjectIden::WorkspaceId).eq(workspace_id))
+            .and_where_option(
+                Tag::tagged_subquery(ProjectTagIden::Table, ProjectTagIden::ProjectId, tag_ids)
+                    .map(|tagged| Expr::col(ProjectIden::Id).in_subquery(tagged)),
+            )
+            .and_where(Expr::col(ProjectIden::Archived).is_null())
+            .and_where(Expr::col(ProjectIden::Deleted).is_null())
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// The sub query selecting the ids of the projects in a workspace, projects in the trash are left out
+    ///
+    /// Scopes the lists of items that belong to projects, e.g. tasks, to a workspace.
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace
+    pub(crate) fn workspace_subquery(workspace_id: Uuid) -> SelectStatement {
+        Query::select()
+            .column(ProjectIden::Id)
+            .from(ProjectIden::Table)
+            .and_where(Expr::col(ProjectIden::WorkspaceId).eq(workspace_id))
+            .and_where(Expr::col(ProjectIden::Deleted).is_null())
+            .to_owned()
+    }
+
+    /// The sub query selecting the ids of the active projects in a workspace, i.e. neither archived nor deleted
+    ///
+    /// Scopes the default lists of items that belong to projects, e.g. the due tasks, like [`Project::list_workspace_query`].
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace
+    pub(crate) fn active_subquery(workspace_id: Uuid) -> SelectStatement {
+        Project::workspace_subquery(workspace_id)
+            .and_where(Expr::col(ProjectIden::Archived).is_null())
+            .to_owned()
+    }
+
+    /// Moves a Project to a state from the undo journal on DB
+    ///
+    /// Inserts or updates the project to match the state and records the change in the [`History`], but not in the
+    /// journal. A project that should not exist is moved to the trash rather than purged, so undoing its insert
+    /// keeps the items added to it since.
+    /// # Arguments
+    /// * `id` - The uuid v4 id of the project
+    /// * `state` - The state to move to, `None` if the project should not exist
+    /// * `author_id` - The id of the user that moves the project
+    /// * `conn` - The SQLite database connection or transaction
+    pub(crate) async fn restore_state_db<'c, A>(
+        id: Uuid,
+        state: Option<Project>,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<(), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Project::select_query(id);
+        let stored = sqlx::query_as_with::<_, Project, _>(&query.0, query.1)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match (stored, state) {
+            (None, None) => {}
+            (None, Some(state)) => {
+                let query = state.insert_query();
+                sqlx::query_with(&query.0, query.1)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            (Some(stored), Some(state)) => {
+                let query = state.update_query();
+                sqlx::query_with(&query.0, query.1)
+                    .execute(&mut *tx)
+                    .await?;
+                History::record_db(
+                    HistoryAction::Update,
+                    &stored,
+                    Some(&state),
+                    author_id,
+                    &mut *tx,
+                )
+                .await?;
+            }
+            (Some(stored), None) => {
+                // Purging would cascade to the tasks added since, the project goes to the trash instead
+                let mut deleted = stored.clone();
+                deleted.deleted = Some(Utc::now());
+                deleted.edited();
+                let query = deleted.update_query();
+                sqlx::query_with(&query.0, query.1)
+                    .execute(&mut *tx)
+                    .await?;
+                History::record_db(
+                    HistoryAction::Delete,
+                    &stored,
+                    Some(&deleted),
+                    author_id,
+                    &mut *tx,
+                )
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     // Private Functions
 
     /// Set Edited
@@ -286,39 +768,174 @@ impl Project {
     fn edited(&mut self) {
         self.modified = Utc::now();
     }
+
+    /// Writes the Project to DB and records the change in the [`History`] and the undo journal
+    ///
+    /// # Arguments
+    /// * `action` - The kind of change
+    /// * `author_id` - The id of the user that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    async fn audit_db<'c, A>(
+        &self,
+        action: JournalAction,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let change = match action {
+            JournalAction::Delete => HistoryAction::Delete,
+            _ => HistoryAction::Update,
+        };
+        let mut tx = conn.begin().await?;
+        let (result, stored) = History::audit_db(change, self, author_id, &mut *tx).await?;
+        if let Some(stored) = stored {
+            JournalEntry::new(action, Some(&stored), self, author_id)
+                .record_db(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+impl Entity for Project {
+    type Iden = ProjectIden;
+    type Key = Uuid;
+
+    const TABLE: ProjectIden = ProjectIden::Table;
+    const KEY: &'static [ProjectIden] = &[ProjectIden::Id];
+    const CREATED: Option<ProjectIden> = Some(ProjectIden::Created);
+    const COLUMNS: &'static [ProjectIden] = &[
+        ProjectIden::Id,
+        ProjectIden::WorkspaceId,
+        ProjectIden::Name,
+        ProjectIden::Description,
+        ProjectIden::AuthorId,
+        ProjectIden::Archived,
+        ProjectIden::Deleted,
+        ProjectIden::Created,
+        ProjectIden::Modified,
+    ];
+    const ORDER_BY: Option<ProjectIden> = Some(ProjectIden::Name);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.workspace_id.into(),
+            self.name.clone().into(),
+            self.description.clone().into(),
+            self.author_id.into(),
+            self.archived.into(),
+            self.deleted.into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// The plain writes skip the [`JournalEntry`] journal & the [`History`], edit projects with [`Project::insert_db`],
+/// [`Project::update_db`] & [`Project::delete_db`]
+impl Repository for Project {}
+
+impl Audited for Project {
+    const ENTITY: &'static str = "project";
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn set_audit_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    fn audit_created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    fn audit_fields(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("workspace_id", Some(self.workspace_id.to_string())),
+            ("name", Some(self.name.clone())),
+            ("description", Some(self.description.clone())),
+            ("author_id", Some(self.author_id.to_string())),
+            ("archived", self.archived.map(|date| date.to_rfc3339())),
+            ("deleted", self.deleted.map(|date| date.to_rfc3339())),
+            ("created", Some(self.created.to_rfc3339())),
+            ("modified", Some(self.modified.to_rfc3339())),
+        ]
+    }
+
+    fn restore_field(&mut self, field: &str, value: Option<&str>) -> bool {
+        let date = match value.map(DateTime::parse_from_rfc3339) {
+            Some(Ok(date)) => Some(date.with_timezone(&Utc)),
+            _ => None,
+        };
+        let id = value.and_then(|value| Uuid::parse_str(value).ok());
+
+        match (field, value, date) {
+            ("name", Some(value), _) => self.name = value.into(),
+            ("description", Some(value), _) => self.description = value.into(),
+            ("workspace_id", _, _) if id.is_some() => self.workspace_id = id.unwrap(),
+            ("author_id", _, _) if id.is_some() => self.author_id = id.unwrap(),
+            ("archived", None, _) => self.archived = None,
+            ("archived", _, Some(date)) => self.archived = Some(date),
+            ("deleted", None, _) => self.deleted = None,
+            ("deleted", _, Some(date)) => self.deleted = Some(date),
+            ("created", _, Some(date)) => self.created = date,
+            ("modified", _, Some(date)) => self.modified = date,
+            _ => return false,
+        }
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Project;
-    use crate::config::init_memory_db;
-    use sqlx::Error;
+    use super::{Project, ProjectIden};
+    use crate::config::{init_memory_db, Trash};
+    use crate::db::custom_fields::{CustomField, FieldKind, FieldValue};
+    use crate::db::filter::{Filter, ListSpec, Sort};
+    use crate::db::history::{Audited, History, HistoryAction};
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use chrono::Duration;
+    use sqlx::{Connection, Error, SqliteConnection};
     use uuid::Uuid;
-    fn create_project() -> Project {
+    fn create_project(workspace_id: Uuid, author_id: Uuid) -> Project {
         Project::new(
+            workspace_id,
             "PROJECT_NAME".into(),
             "PROJECT_DESCRIPTION".into(),
-            "PROJECT_AUTHOR".into(),
+            author_id,
         )
     }
 
     #[test]
     fn insert() {
-        let query = create_project().insert_query();
+        let query = create_project(Uuid::default(), Uuid::default()).insert_query();
 
-        assert_eq!(query.0, "INSERT INTO \"project\" (\"id\", \"name\", \"description\", \"author\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?)");
+        assert_eq!(query.0, "INSERT INTO \"project\" (\"id\", \"workspace_id\", \"name\", \"description\", \"author_id\", \"archived\", \"deleted\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)");
     }
 
     #[test]
     fn update() {
-        let query = create_project().update_query();
+        let query = create_project(Uuid::default(), Uuid::default()).update_query();
 
-        assert_eq!(query.0, "UPDATE \"project\" SET \"name\" = ?, \"description\" = ?, \"author\" = ?, \"modified\" = ? WHERE \"id\" = ?")
+        assert_eq!(query.0, "UPDATE \"project\" SET \"workspace_id\" = ?, \"name\" = ?, \"description\" = ?, \"author_id\" = ?, \"archived\" = ?, \"deleted\" = ?, \"modified\" = ? WHERE \"id\" = ?")
     }
 
     #[test]
-    fn delete() {
-        let query = create_project().delete_query();
+    fn purge() {
+        let query = create_project(Uuid::default(), Uuid::default()).purge_query();
 
         assert_eq!(query.0, "DELETE FROM \"project\" WHERE \"id\" = ?");
     }
@@ -327,15 +944,19 @@ mod tests {
     fn query() {
         let query = Project::select_query(Uuid::default());
 
-        assert_eq!(query.0, "SELECT \"name\", \"description\", \"author\", \"created\", \"modified\" FROM \"project\" WHERE \"id\" = ? LIMIT ?")
+        assert_eq!(query.0, "SELECT \"id\", \"workspace_id\", \"name\", \"description\", \"author_id\", \"archived\", \"deleted\", \"created\", \"modified\" FROM \"project\" WHERE \"id\" = ? LIMIT ?")
     }
 
     #[tokio::test]
     async fn insert_db() {
-        let query = create_project()
-            .insert(&mut init_memory_db().await.unwrap())
-            .await
-            .unwrap();
+        let mut conn = init_memory_db().await.unwrap();
+        let query = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        )
+        .insert_db(&mut conn)
+        .await
+        .unwrap();
 
         assert_eq!(query.rows_affected(), 1);
         assert_eq!(query.last_insert_rowid(), 1);
@@ -344,43 +965,207 @@ mod tests {
     #[tokio::test]
     async fn update_db() {
         let mut conn = init_memory_db().await.unwrap();
-        let project = create_project();
+        let editor = *create_test_user(&mut conn).await.id();
+        let project = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        );
 
-        let query = project.update(&mut conn).await.unwrap();
+        // last_insert_rowid() is the last insert of the whole connection, updates & deletes don't set it. Migrations
+        // and the search index triggers insert too, so it is only checked right after an insert.
+        let query = project.update_db(editor, &mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 0);
-        assert_eq!(query.last_insert_rowid(), 1);
 
-        let query = project.insert(&mut conn).await.unwrap();
+        let query = project.insert_db(&mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 1);
         assert_eq!(query.last_insert_rowid(), 1);
 
-        let query = project.update(&mut conn).await.unwrap();
+        let query = project.update_db(editor, &mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 1);
-        assert_eq!(query.last_insert_rowid(), 1)
     }
 
     #[tokio::test]
     async fn delete_db() {
         let mut conn = init_memory_db().await.unwrap();
-        let project = create_project();
+        let editor = *create_test_user(&mut conn).await.id();
+        let mut project = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        );
 
-        let query = project.delete(&mut conn).await.unwrap();
+        let query = project.delete_db(editor, &mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 0);
-        assert_eq!(query.last_insert_rowid(), 1);
 
-        let query = project.insert(&mut conn).await.unwrap();
+        let query = project.insert_db(&mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 1);
         assert_eq!(query.last_insert_rowid(), 1);
 
-        let query = project.delete(&mut conn).await.unwrap();
+        let query = project.delete_db(editor, &mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+        assert!(project.is_deleted());
+
+        let query = project.purge(editor, &mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 1);
-        assert_eq!(query.last_insert_rowid(), 1)
+    }
+
+    #[test]
+    fn trash_validate() {
+        assert!(Trash::default().validate().is_ok());
+        assert!(Trash { retention_days: 0 }.validate().is_ok());
+        assert!(matches!(
+            Trash { retention_days: -1 }.validate(),
+            Err(DbError::InvalidRetention(-1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn trash_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let mut projects = vec![];
+        for _ in 0..3 {
+            let mut project = create_project(workspace, *create_test_user(&mut conn).await.id());
+            project.assign_id().assign_created();
+            project.insert_db(&mut conn).await.unwrap();
+            projects.push(project);
+        }
+
+        projects[1].archive_db(editor, &mut conn).await.unwrap();
+        projects[2].delete_db(editor, &mut conn).await.unwrap();
+        assert_eq!(
+            Project::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [projects[0].clone()]
+        );
+        assert_eq!(
+            Project::from_archived_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [projects[1].clone()]
+        );
+        assert_eq!(
+            Project::from_trash_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [projects[2].clone()]
+        );
+
+        projects[1].unarchive_db(editor, &mut conn).await.unwrap();
+        projects[2].restore_db(editor, &mut conn).await.unwrap();
+        assert_eq!(
+            Project::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(
+            Project::from_trash_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            Project::from_db(projects[2].id, &mut conn).await.unwrap(),
+            projects[2]
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let trash = Trash { retention_days: 30 };
+        let mut project = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+        project.delete_db(editor, &mut conn).await.unwrap();
+
+        let deleted = project.deleted().unwrap();
+        let purged = Project::purge_db(deleted + Duration::days(29), &trash, editor, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(purged, 0);
+        assert!(Project::from_db(project.id, &mut conn).await.is_ok());
+
+        let purged = Project::purge_db(deleted + Duration::days(30), &trash, editor, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert!(Project::from_db(project.id, &mut conn).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn history_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let admin = *create_test_user(&mut conn).await.id();
+        let mut project = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+        let created = project.clone();
+
+        project.set_name("RENAMED".into());
+        project.update_db(editor, &mut conn).await.unwrap();
+        let renamed = project.clone();
+        project.delete_db(admin, &mut conn).await.unwrap();
+        let deleted = project.clone();
+        project.purge(admin, &mut conn).await.unwrap();
+
+        let timeline = History::from_entity_list_db(Project::ENTITY, project.id, &mut conn)
+            .await
+            .unwrap();
+        let changes: Vec<_> = timeline
+            .iter()
+            .map(|entry| (entry.action(), entry.field().as_str(), *entry.author_id()))
+            .collect();
+        assert_eq!(
+            changes[..4],
+            [
+                (HistoryAction::Update, "name", editor),
+                (HistoryAction::Update, "modified", editor),
+                (HistoryAction::Delete, "deleted", admin),
+                (HistoryAction::Delete, "modified", admin),
+            ]
+        );
+        assert_eq!(timeline[0].old_value().as_deref(), Some("PROJECT_NAME"));
+        assert_eq!(timeline[0].new_value().as_deref(), Some("RENAMED"));
+        assert!(timeline[4..]
+            .iter()
+            .all(|entry| entry.action() == HistoryAction::Purge && entry.new_value().is_none()));
+
+        let purged = *timeline[4].changed();
+        for (at, state) in [
+            (created.created - Duration::seconds(1), None),
+            (created.created, Some(created)),
+            (*timeline[0].changed(), Some(renamed)),
+            (*timeline[2].changed(), Some(deleted)),
+            (purged, None),
+        ] {
+            // the purge may be recorded within the same instant as the delete
+            let state = state.filter(|_| at < purged);
+            let rewound = Project::from_history_db(project.id, at, &mut conn)
+                .await
+                .unwrap();
+            assert_eq!(rewound, state);
+        }
     }
 
     #[tokio::test]
     async fn query_db() {
         let mut conn = init_memory_db().await.unwrap();
-        let mut project = create_project();
+        let mut project = create_project(
+            *create_test_workspace(&mut conn).await.id(),
+            *create_test_user(&mut conn).await.id(),
+        );
         project.assign_id().assign_created();
 
         let query = Project::from_db(project.id, &mut conn).await;
@@ -389,11 +1174,310 @@ mod tests {
             Error::RowNotFound.to_string()
         );
 
-        let query = project.insert(&mut conn).await.unwrap();
+        let query = project.insert_db(&mut conn).await.unwrap();
         assert_eq!(query.rows_affected(), 1);
         assert_eq!(query.last_insert_rowid(), 1);
 
         let query = Project::from_db(project.id, &mut conn).await.unwrap();
         assert_eq!(project, query);
     }
+
+    #[tokio::test]
+    async fn workspace_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let author = *create_test_user(&mut conn).await.id();
+        let work = *create_test_workspace(&mut conn).await.id();
+        let personal = *create_test_workspace(&mut conn).await.id();
+        let mut project = create_project(work, author);
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+
+        assert_eq!(
+            Project::from_workspace_list_db(work, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [project.clone()]
+        );
+        assert!(
+            Project::from_workspace_list_db(personal, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        project.set_workspace_id(personal);
+        project.update_db(editor, &mut conn).await.unwrap();
+        assert!(
+            Project::from_workspace_list_db(work, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            Project::from_workspace_list_db(personal, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [project]
+        );
+    }
+
+    async fn create_merge(conn: &mut SqliteConnection) -> (Project, Project, Vec<Task>) {
+        let workspace = *create_test_workspace(conn).await.id();
+        let author = *create_test_user(conn).await.id();
+        let mut from = create_project(workspace, author);
+        from.assign_id().assign_created();
+        from.insert_db(&mut *conn).await.unwrap();
+        let mut into = create_project(workspace, author);
+        into.assign_id().assign_created();
+        into.insert_db(&mut *conn).await.unwrap();
+
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let mut task = Task::new(from.id, "TITLE".into(), "BODY".into(), author);
+            task.assign_id().assign_created();
+            task.insert(&mut *conn).await.unwrap();
+            tasks.push(task);
+        }
+        (from, into, tasks)
+    }
+
+    async fn project_ids(tasks: &[Task], conn: &mut SqliteConnection) -> Vec<Uuid> {
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.push(
+                *Task::from_db(*task.id(), &mut *conn)
+                    .await
+                    .unwrap()
+                    .project_id(),
+            );
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn merge_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, into, tasks) = create_merge(&mut conn).await;
+
+        let moved = from.merge_into_db(&into, editor, &mut conn).await.unwrap();
+        assert_eq!(moved, 2);
+        assert!(from.is_archived());
+        assert_eq!(Project::from_db(from.id, &mut conn).await.unwrap(), from);
+        assert_eq!(project_ids(&tasks, &mut conn).await, [into.id, into.id]);
+    }
+
+    #[tokio::test]
+    async fn merge_rollback_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, into, tasks) = create_merge(&mut conn).await;
+
+        // Fail the archive, after the tasks have been moved
+        sqlx::query(
+            "CREATE TEMP TRIGGER fail_archive BEFORE UPDATE OF archived ON project
+            BEGIN SELECT RAISE(ABORT, 'injected failure'); END;",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        let stored = from.clone();
+        assert!(matches!(
+            from.merge_into_db(&into, editor, &mut conn).await,
+            Err(DbError::Sqlx(Error::Database(err))) if err.message() == "injected failure"
+        ));
+        assert_eq!(from, stored);
+        assert_eq!(Project::from_db(from.id, &mut conn).await.unwrap(), stored);
+        assert_eq!(project_ids(&tasks, &mut conn).await, [from.id, from.id]);
+        assert!(
+            History::from_entity_list_db(Project::ENTITY, from.id, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_transaction_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, into, tasks) = create_merge(&mut conn).await;
+
+        // The merge succeeds, but the transaction it is part of is rolled back
+        let mut tx = conn.begin().await.unwrap();
+        from.merge_into_db(&into, editor, &mut tx).await.unwrap();
+        assert_eq!(project_ids(&tasks, &mut tx).await, [into.id, into.id]);
+        tx.rollback().await.unwrap();
+
+        assert!(!Project::from_db(from.id, &mut conn)
+            .await
+            .unwrap()
+            .is_archived());
+        assert_eq!(project_ids(&tasks, &mut conn).await, [from.id, from.id]);
+    }
+
+    #[tokio::test]
+    async fn merge_rejected_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, mut into, tasks) = create_merge(&mut conn).await;
+
+        let stored = from.clone();
+        assert!(matches!(
+            from.merge_into_db(&stored, editor, &mut conn).await,
+            Err(DbError::SelfMerge)
+        ));
+
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let mut foreign = create_project(workspace, into.author_id);
+        foreign.assign_id().assign_created();
+        foreign.insert_db(&mut conn).await.unwrap();
+        assert!(matches!(
+            from.merge_into_db(&foreign, editor, &mut conn).await,
+            Err(DbError::WorkspaceMismatch)
+        ));
+
+        into.delete_db(editor, &mut conn).await.unwrap();
+        assert!(matches!(
+            from.merge_into_db(&into, editor, &mut conn).await,
+            Err(DbError::ProjectDeleted)
+        ));
+
+        assert_eq!(from, stored);
+        assert_eq!(Project::from_db(from.id, &mut conn).await.unwrap(), stored);
+        assert_eq!(project_ids(&tasks, &mut conn).await, [from.id, from.id]);
+    }
+
+    #[tokio::test]
+    async fn merge_stale_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, into, _) = create_merge(&mut conn).await;
+
+        // Another connection renamed the project, the merge archives the stored project
+        let mut renamed = from.clone();
+        renamed.set_name("RENAMED".into());
+        renamed.update_db(editor, &mut conn).await.unwrap();
+        from.merge_into_db(&into, editor, &mut conn).await.unwrap();
+        assert_eq!(from.name(), "RENAMED");
+        assert!(from.is_archived());
+        assert_eq!(Project::from_db(from.id, &mut conn).await.unwrap(), from);
+
+        // Another connection deleted the project
+        let (mut from, into, tasks) = create_merge(&mut conn).await;
+        let stored = from.clone();
+        from.clone().delete_db(editor, &mut conn).await.unwrap();
+        assert!(matches!(
+            from.merge_into_db(&into, editor, &mut conn).await,
+            Err(DbError::ProjectDeleted)
+        ));
+        assert_eq!(from, stored);
+        assert_eq!(project_ids(&tasks, &mut conn).await, [from.id, from.id]);
+    }
+
+    #[tokio::test]
+    async fn merge_custom_values_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let (mut from, into, tasks) = create_merge(&mut conn).await;
+
+        // `customer` has a match in the other project, `estimate` only a field of another kind
+        let mut fields = Vec::new();
+        for (project_id, name, kind) in [
+            (from.id, "customer", FieldKind::Text),
+            (from.id, "estimate", FieldKind::Number),
+            (into.id, "customer", FieldKind::Text),
+            (into.id, "estimate", FieldKind::Text),
+        ] {
+            let mut field = CustomField::new(project_id, name.into(), kind);
+            field.assign_id().assign_created();
+            field.insert(&mut conn).await.unwrap();
+            fields.push(field);
+        }
+        let customer = FieldValue::Text("ACME".into());
+        fields[0]
+            .set_value_db(&tasks[0], customer.clone(), &mut conn)
+            .await
+            .unwrap();
+        fields[1]
+            .set_value_db(&tasks[0], FieldValue::Number(3.0), &mut conn)
+            .await
+            .unwrap();
+
+        from.merge_into_db(&into, editor, &mut conn).await.unwrap();
+        let task = *tasks[0].id();
+        assert_eq!(
+            fields[2].value_db(task, &mut conn).await.unwrap(),
+            Some(customer)
+        );
+        assert_eq!(fields[3].value_db(task, &mut conn).await.unwrap(), None);
+        for field in &fields[..2] {
+            assert_eq!(field.value_db(task, &mut conn).await.unwrap(), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn list_spec_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let alice = *create_test_user(&mut conn).await.id();
+        let bob = *create_test_user(&mut conn).await.id();
+        let now = chrono::Utc::now();
+
+        let mut projects = Vec::new();
+        for (name, description, author, age) in [
+            ("Website", "Marketing site", alice, 30),
+            ("Release 1.0", "Ship the docs", bob, 10),
+            ("docs", "User guide", alice, 1),
+            ("Legacy", "Old docs", bob, 60),
+        ] {
+            let mut project = Project::new(workspace, name.into(), description.into(), author);
+            project.assign_id().assign_created();
+            project.created = now - Duration::days(age);
+            project.insert_db(&mut conn).await.unwrap();
+            projects.push(project);
+        }
+        let names = |projects: Vec<Project>| -> Vec<String> {
+            projects.into_iter().map(|project| project.name).collect()
+        };
+
+        let all = Project::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(names(all), ["Legacy", "Release 1.0", "Website", "docs"]);
+
+        let spec = ListSpec::new()
+            .filter(Filter::equals(ProjectIden::AuthorId, alice))
+            .sort(Sort::Desc(ProjectIden::Created));
+        let authored = Project::from_workspace_list_db(workspace, &spec, 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(names(authored), ["docs", "Website"]);
+
+        let spec = ListSpec::new()
+            .filter(Filter::range(
+                ProjectIden::Created,
+                Some(now - Duration::days(20)),
+                Some(now),
+            ))
+            .filter(Filter::Any(vec![
+                Filter::contains(ProjectIden::Name, "DOCS"),
+                Filter::contains(ProjectIden::Description, "docs"),
+            ]))
+            .sort(Sort::Asc(ProjectIden::Created));
+        let recent = Project::from_workspace_list_db(workspace, &spec, 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(names(recent), ["Release 1.0", "docs"]);
+
+        let spec = ListSpec::new().filter(Filter::contains(ProjectIden::Name, "%"));
+        assert!(
+            Project::from_workspace_list_db(workspace, &spec, 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
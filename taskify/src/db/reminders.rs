@@ -0,0 +1,310 @@
+//! Task Reminder Database Entity
+//! This file contains the task reminder database entity. A reminder fires a fixed offset before the due datetime of
+//! its task, e.g. "1 day before", so moving the due datetime moves the reminder with it.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_query::{enum_def, Expr, Order, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config::Workflow;
+use crate::db::projects::ProjectIden;
+use crate::db::tasks::TaskIden;
+use crate::db::{Entity, Repository};
+
+/// The task reminder database entity for taskify
+#[enum_def(table_name = "task_reminder")]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Reminder {
+    id: Uuid,
+    task_id: Uuid,
+    offset_minutes: i64,
+    created: DateTime<Utc>,
+}
+
+/// A reminder together with the due datetime of its task, as returned by [`Reminder::from_firing_list_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct FiringReminder {
+    #[sqlx(flatten)]
+    pub reminder: Reminder,
+    pub due: DateTime<Utc>,
+}
+
+impl FiringReminder {
+    /// The datetime the reminder fires at
+    pub fn fires(&self) -> DateTime<Utc> {
+        self.reminder.fires(self.due)
+    }
+}
+
+impl Reminder {
+    /// New Reminder
+    ///
+    /// Create a new reminder entity with a random id, firing `before` the task is due.
+    /// # Arguments:
+    /// * `task_id` - The id of the task to remind about
+    /// * `before` - How long before the due datetime the reminder fires, truncated to minutes
+    pub fn new(task_id: Uuid, before: Duration) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            offset_minutes: before.num_minutes(),
+            created: Utc::now(),
+        }
+    }
+
+    /// List all Reminders of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<Reminder>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Reminder::list_task_query(task_id);
+        sqlx::query_as_with::<_, Reminder, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Reminders firing in a time range in the DB
+    ///
+    /// Lists the reminders of not completed tasks that fire in `[after, before)`, in firing order. A scheduler can
+    /// call this with the end of its previous window as `after` so no reminder is skipped or fired twice. Tasks of
+    /// archived or deleted projects don't remind.
+    /// # Arguments
+    /// * `after` - The inclusive lower bound
+    /// * `before` - The exclusive upper bound
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_firing_list_db<'c, E>(
+        after: DateTime<Utc>,
+        before: DateTime<Utc>,
+        workflow: &Workflow,
+        executor: E,
+    ) -> Result<Vec<FiringReminder>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Reminder::list_firing_query(after, before, &workflow.completed);
+        sqlx::query_as_with::<_, FiringReminder, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Reminder ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Reminder Task ID
+    pub fn task_id(&self) -> &Uuid {
+        &self.task_id
+    }
+
+    /// Get how long before the due datetime the Reminder fires
+    pub fn before(&self) -> Duration {
+        Duration::minutes(self.offset_minutes)
+    }
+
+    /// The datetime the reminder fires at for a task `due` at the given datetime
+    pub fn fires(&self, due: DateTime<Utc>) -> DateTime<Utc> {
+        due - self.before()
+    }
+
+    // Database Interactions
+
+    /// Generates a sqlx query to List all Reminders of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn list_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        Reminder::select()
+            .and_where(Expr::col(ReminderIden::TaskId).eq(task_id))
+            .order_by(ReminderIden::OffsetMinutes, Order::Desc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Reminders firing in a time range in the DB
+    ///
+    /// # Arguments
+    /// * `after` - The inclusive lower bound
+    /// * `before` - The exclusive upper bound
+    /// * `completed` - The task statuses that count as completed
+    pub fn list_firing_query(
+        after: DateTime<Utc>,
+        before: DateTime<Utc>,
+        completed: &[String],
+    ) -> (String, SqlxValues) {
+        let fires = Expr::cust(
+            "CAST(strftime('%s', \"task\".\"due\") AS INTEGER) - \"task_reminder\".\"offset_minutes\" * 60",
+        );
+
+        Reminder::joined_select()
+            .column((TaskIden::Table, TaskIden::Due))
+            .inner_join(
+                TaskIden::Table,
+                Expr::col((TaskIden::Table, TaskIden::Id))
+                    .equals((ReminderIden::Table, ReminderIden::TaskId)),
+            )
+            .inner_join(
+                ProjectIden::Table,
+                Expr::col((ProjectIden::Table, ProjectIden::Id))
+                    .equals((TaskIden::Table, TaskIden::ProjectId)),
+            )
+            .and_where(Expr::col((ProjectIden::Table, ProjectIden::Archived)).is_null())
+            .and_where(Expr::col((ProjectIden::Table, ProjectIden::Deleted)).is_null())
+            .and_where(Expr::col((TaskIden::Table, TaskIden::Due)).is_not_null())
+            .and_where(
+                Expr::col((TaskIden::Table, TaskIden::Status)).is_not_in(completed.iter().cloned()),
+            )
+            .and_where(Expr::expr(fires.clone()).gte(after.timestamp()))
+            .and_where(Expr::expr(fires.clone()).lt(before.timestamp()))
+            .order_by_expr(fires, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+impl Entity for Reminder {
+    type Iden = ReminderIden;
+    type Key = Uuid;
+
+    const TABLE: ReminderIden = ReminderIden::Table;
+    const KEY: &'static [ReminderIden] = &[ReminderIden::Id];
+    const CREATED: Option<ReminderIden> = Some(ReminderIden::Created);
+    const COLUMNS: &'static [ReminderIden] = &[
+        ReminderIden::Id,
+        ReminderIden::TaskId,
+        ReminderIden::OffsetMinutes,
+        ReminderIden::Created,
+    ];
+    const ORDER_BY: Option<ReminderIden> = Some(ReminderIden::OffsetMinutes);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.task_id.into(),
+            self.offset_minutes.into(),
+            self.created.into(),
+        ]
+    }
+}
+
+/// Deleting a reminder only stops it from firing, its task is unchanged
+impl Repository for Reminder {}
+
+#[cfg(test)]
+mod tests {
+    use super::Reminder;
+    use crate::config::{init_memory_db, Workflow};
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{Entity, Repository};
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    #[test]
+    fn insert() {
+        let query = Reminder::new(Uuid::default(), Duration::days(1)).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"task_reminder\" (\"id\", \"task_id\", \"offset_minutes\", \"created\") VALUES (?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn fires() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 20, 12, 0, 0).unwrap();
+        let reminder = Reminder::new(Uuid::default(), Duration::days(1));
+
+        assert_eq!(reminder.before(), Duration::minutes(24 * 60));
+        assert_eq!(
+            reminder.fires(due),
+            Utc.with_ymd_and_hms(2024, 6, 19, 12, 0, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn firing_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workflow = Workflow::default();
+        let mut project = Project::new(
+            *create_test_workspace(&mut conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(&mut conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+
+        let due = Utc.with_ymd_and_hms(2024, 6, 20, 12, 0, 0).unwrap();
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.set_due(Some(due));
+        task.insert(&mut conn).await.unwrap();
+
+        let day_before = Reminder::new(*task.id(), Duration::days(1));
+        let hour_before = Reminder::new(*task.id(), Duration::hours(1));
+        day_before.insert(&mut conn).await.unwrap();
+        hour_before.insert(&mut conn).await.unwrap();
+        assert_eq!(
+            Reminder::from_task_list_db(*task.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![day_before.clone(), hour_before.clone()]
+        );
+
+        let window = |from: Duration, to: Duration| (due - from, due - to);
+
+        let (after, before) = window(Duration::days(2), Duration::minutes(0));
+        let firing = Reminder::from_firing_list_db(after, before, &workflow, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(firing.len(), 2);
+        assert_eq!(firing[0].reminder, day_before);
+        assert_eq!(firing[0].fires(), due - Duration::days(1));
+        assert_eq!(firing[1].reminder, hour_before);
+
+        // the lower bound is inclusive, the upper bound exclusive
+        let (after, before) = window(Duration::days(1), Duration::hours(1));
+        let firing = Reminder::from_firing_list_db(after, before, &workflow, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(firing.len(), 1);
+        assert_eq!(firing[0].reminder, day_before);
+
+        let (after, before) = window(Duration::days(2), Duration::minutes(0));
+        project.delete_db(editor, &mut conn).await.unwrap();
+        assert!(
+            Reminder::from_firing_list_db(after, before, &workflow, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        project.restore_db(editor, &mut conn).await.unwrap();
+
+        task.set_status("done".into());
+        task.update_db(editor, &mut conn).await.unwrap();
+        assert!(
+            Reminder::from_firing_list_db(after, before, &workflow, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
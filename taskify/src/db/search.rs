@@ -0,0 +1,362 @@
+//! Full-Text Search
+//! This file contains the [`SearchHit`], a match of a full-text search over projects, tasks and notes. They are all
+//! indexed in the one `search` FTS5 table, that triggers keep in sync with the entity tables, so hits of every kind
+//! are ranked against each other. Matches in titles weigh more than matches in bodies.
+
+use sea_query::{
+    enum_def, Expr, Order, Query, SelectStatement, SimpleExpr, SqliteQueryBuilder, Value,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::notes::Note;
+use crate::db::projects::Project;
+use crate::db::tasks::TaskIden;
+
+/// The text inserted before a matched term in the highlighted title & snippet
+pub const MATCH_START: &str = "[";
+
+/// The text inserted after a matched term in the highlighted title & snippet
+pub const MATCH_END: &str = "]";
+
+/// The most tokens a snippet of the body has
+const SNIPPET_TOKENS: i64 = 12;
+
+/// How much more a match in the title weighs than one in the body
+const TITLE_WEIGHT: f64 = 10.0;
+
+/// The kind of entity a search hit is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum SearchEntity {
+    /// A [`Project`], matched on its name & description
+    #[default]
+    Project,
+    /// A [`Task`](super::tasks::Task), matched on its title & body
+    Task,
+    /// A [`Note`](super::notes::Note), matched on its title & body
+    Note,
+}
+
+impl SearchEntity {
+    /// The name the entity is indexed as
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchEntity::Project => "project",
+            SearchEntity::Task => "task",
+            SearchEntity::Note => "note",
+        }
+    }
+}
+
+/// A full-text search hit
+///
+/// The title and snippet mark the matched terms with [`MATCH_START`] and [`MATCH_END`].
+#[enum_def(table_name = "search")]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct SearchHit {
+    entity: SearchEntity,
+    entity_id: Uuid,
+    title: String,
+    snippet: String,
+    rank: f64,
+}
+
+impl SearchHit {
+    /// Search the projects, tasks & notes of a Workspace in the DB
+    ///
+    /// Every word of the text has to match, as a word or the start of one, e.g. "stag clust" finds "the staging
+    /// cluster". The hits are ordered best match first. Deleted projects and their tasks & notes are left out.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to search
+    /// * `text` - The words to search for
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_search_db<'c, E>(
+        workspace_id: Uuid,
+        text: &str,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<SearchHit>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let Some(pattern) = SearchHit::pattern(text) else {
+            return Ok(Vec::new());
+        };
+
+        let query = SearchHit::search_query(workspace_id, &pattern, page, limit);
+        sqlx::query_as_with::<_, SearchHit, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Search Hit Entity
+    pub fn entity(&self) -> &SearchEntity {
+        &self.entity
+    }
+
+    /// Get Search Hit Entity ID
+    pub fn entity_id(&self) -> &Uuid {
+        &self.entity_id
+    }
+
+    /// Get Search Hit Title, with the matched terms marked
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    /// Get Search Hit Snippet, the part of the body around the matched terms with them marked
+    pub fn snippet(&self) -> &String {
+        &self.snippet
+    }
+
+    /// Get Search Hit Rank, the lower the better the match
+    pub fn rank(&self) -> &f64 {
+        &self.rank
+    }
+
+    /// FTS5 Pattern
+    ///
+    /// Quotes every word of the text, so the FTS5 query syntax in it is matched literally, and matches the words as
+    /// prefixes. Returns `None` if the text has no words.
+    /// # Arguments
+    /// * `text` - The words to search for
+    pub fn pattern(text: &str) -> Option<String> {
+        let words: Vec<_> = text
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect();
+
+        match words.is_empty() {
+            true => None,
+            false => Some(words.join(" ")),
+        }
+    }
+
+    // Database Interactions
+
+    /// Generates a sqlx query to Search the projects, tasks & notes of a Workspace in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to search
+    /// * `pattern` - The FTS5 query, see [`SearchHit::pattern`]
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn search_query(
+        workspace_id: Uuid,
+        pattern: &str,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Query::select()
+            .columns([SearchHitIden::Entity, SearchHitIden::EntityId])
+            .expr_as(
+                Expr::cust_with_values("highlight(\"search\", 2, ?, ?)", [MATCH_START, MATCH_END]),
+                SearchHitIden::Title,
+            )
+            .expr_as(
+                Expr::cust_with_values(
+                    "snippet(\"search\", 3, ?, ?, '…', ?)",
+                    [
+                        Value::from(MATCH_START),
+                        Value::from(MATCH_END),
+                        Value::from(SNIPPET_TOKENS),
+                    ],
+                ),
+                SearchHitIden::Snippet,
+            )
+            .expr_as(
+                Expr::cust_with_values("bm25(\"search\", 0.0, 0.0, ?, 1.0)", [TITLE_WEIGHT]),
+                SearchHitIden::Rank,
+            )
+            .from(SearchHitIden::Table)
+            .and_where(Expr::cust_with_values("\"search\" MATCH ?", [pattern]))
+            .and_where(SearchHit::workspace_expr(workspace_id))
+            .order_by(SearchHitIden::Rank, Order::Asc)
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// The condition keeping the hits of a workspace, deleted projects and their tasks & notes are left out
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace
+    fn workspace_expr(workspace_id: Uuid) -> SimpleExpr {
+        let projects = Project::workspace_subquery(workspace_id);
+        let tasks = Query::select()
+            .column(TaskIden::Id)
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::ProjectId).in_subquery(projects.clone()))
+            .to_owned();
+        let notes = Note::workspace_subquery(workspace_id);
+
+        SearchHit::entity_expr(SearchEntity::Project, projects)
+            .or(SearchHit::entity_expr(SearchEntity::Task, tasks))
+            .or(SearchHit::entity_expr(SearchEntity::Note, notes))
+    }
+
+    /// The condition keeping the hits of one kind of entity whose id the sub query selects
+    ///
+    /// # Arguments
+    /// * `entity` - The kind of entity
+    /// * `ids` - The sub query selecting the ids to keep
+    fn entity_expr(entity: SearchEntity, ids: SelectStatement) -> SimpleExpr {
+        Expr::col((SearchHitIden::Table, SearchHitIden::Entity))
+            .eq(entity.as_str())
+            .and(Expr::col((SearchHitIden::Table, SearchHitIden::EntityId)).in_subquery(ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchEntity, SearchHit};
+    use crate::config::init_memory_db;
+    use crate::db::notes::{Note, NoteOwner};
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::Repository;
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_project(
+        workspace_id: Uuid,
+        name: &str,
+        description: &str,
+        conn: &mut SqliteConnection,
+    ) -> Project {
+        let author = create_test_user(conn).await;
+        let mut project = Project::new(workspace_id, name.into(), description.into(), *author.id());
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    async fn search(workspace_id: Uuid, text: &str, conn: &mut SqliteConnection) -> Vec<SearchHit> {
+        SearchHit::from_search_db(workspace_id, text, 0, 10, conn)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn pattern() {
+        assert_eq!(
+            SearchHit::pattern(" staging  \"cluster\" "),
+            Some("\"staging\"* \"\"\"cluster\"\"\"*".into())
+        );
+        assert_eq!(SearchHit::pattern("  "), None);
+    }
+
+    #[tokio::test]
+    async fn search_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let infra = create_project(
+            workspace,
+            "Infrastructure",
+            "Move the services to the staging cluster before the release, so the load tests can run against it",
+            &mut conn,
+        )
+        .await;
+        let cluster =
+            create_project(workspace, "Cluster upgrade", "Kubernetes 1.30", &mut conn).await;
+        let mut task = Task::new(
+            *infra.id(),
+            "Staging DNS".into(),
+            "Point it at the cluster".into(),
+            *infra.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+        let mut note = Note::new(
+            NoteOwner::Standalone(workspace),
+            "Ideas".into(),
+            "A bigger cluster".into(),
+        );
+        note.assign_id().assign_created();
+        note.insert(&mut conn).await.unwrap();
+
+        let hits = search(workspace, "stag clust", &mut conn).await;
+        let found: Vec<_> = hits
+            .iter()
+            .map(|hit| (*hit.entity(), *hit.entity_id()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (SearchEntity::Task, *task.id()),
+                (SearchEntity::Project, *infra.id())
+            ]
+        );
+        assert_eq!(hits[0].title(), "[Staging] DNS");
+        let snippet = hits[1].snippet();
+        assert!(snippet.contains("[staging] [cluster]"));
+        assert!(snippet.starts_with('…') || snippet.ends_with('…'));
+        assert!(hits[0].rank() < hits[1].rank());
+
+        let hits = search(workspace, "cluster", &mut conn).await;
+        assert_eq!(hits.len(), 4);
+        assert_eq!(hits[0].entity_id(), cluster.id());
+        assert!(search(workspace, "\"unterminated OR", &mut conn)
+            .await
+            .is_empty());
+        assert!(search(workspace, " ", &mut conn).await.is_empty());
+
+        // the standalone notes of other workspaces are left out
+        let other = *create_test_workspace(&mut conn).await.id();
+        let mut note = Note::new(
+            NoteOwner::Standalone(other),
+            "Elsewhere".into(),
+            "Another cluster".into(),
+        );
+        note.assign_id().assign_created();
+        note.insert(&mut conn).await.unwrap();
+        assert_eq!(search(workspace, "cluster", &mut conn).await.len(), 4);
+        assert_eq!(search(other, "cluster", &mut conn).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sync_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let other = *create_test_workspace(&mut conn).await.id();
+        let mut project = create_project(workspace, "Website", "Landing page", &mut conn).await;
+        let mut task = Task::new(
+            *project.id(),
+            "Copy".into(),
+            "Write the landing copy".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+        assert_eq!(search(workspace, "landing", &mut conn).await.len(), 2);
+        assert!(search(other, "landing", &mut conn).await.is_empty());
+
+        project.set_description("Signup page".into());
+        project.update_db(editor, &mut conn).await.unwrap();
+        task.set_title("Signup copy".into());
+        task.update_db(editor, &mut conn).await.unwrap();
+        let hits = search(workspace, "signup", &mut conn).await;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(search(workspace, "landing", &mut conn).await.len(), 1);
+
+        // Deleted projects are left out of the search, purged ones are removed from the index
+        project.delete_db(editor, &mut conn).await.unwrap();
+        assert!(search(workspace, "signup", &mut conn).await.is_empty());
+        project.purge(editor, &mut conn).await.unwrap();
+        let indexed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM search")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
+}
@@ -0,0 +1,639 @@
+//! Sprint Database Entity
+//! This file contains the sprint database entity, a time-boxed container of the tasks of a
+//! [`Project`](super::projects::Project), used both for iterations and milestones. Tasks are planned into a sprint
+//! with [`Task::set_sprint_db`](super::tasks::Task::set_sprint_db) and their effort estimate counts as their points.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Alias, Expr, Func, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config::Workflow;
+use crate::db::filter::ListSpec;
+use crate::db::tasks::{Task, TaskIden};
+use crate::db::{DbError, Entity, Repository};
+
+/// The sprint database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Sprint {
+    id: Uuid,
+    project_id: Uuid,
+    name: String,
+    goal: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// The completed & remaining work of a sprint, as returned by [`Sprint::points_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct SprintPoints {
+    pub completed_points: f64,
+    pub remaining_points: f64,
+    pub completed_tasks: i64,
+    pub remaining_tasks: i64,
+}
+
+impl SprintPoints {
+    /// The points of every task of the sprint
+    pub fn total_points(&self) -> f64 {
+        self.completed_points + self.remaining_points
+    }
+}
+
+impl Sprint {
+    /// New Sprint
+    ///
+    /// Create a new sprint entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `project_id` - The id of the project the sprint plans
+    /// * `name` - The sprint name, e.g. `Sprint 12` or `v1.0`
+    /// * `goal` - What the sprint should achieve
+    /// * `start` - When the sprint starts
+    /// * `end` - When the sprint ends, after `start`
+    pub fn new(
+        project_id: Uuid,
+        name: String,
+        goal: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            project_id,
+            name,
+            goal,
+            start,
+            end,
+            ..Default::default()
+        }
+    }
+
+    /// Load the running sprint of a Project from the DB
+    ///
+    /// Returns `None` if no sprint of the project runs at `now`.
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `now` - The current datetime
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_current_db<'c, E>(
+        project_id: Uuid,
+        now: DateTime<Utc>,
+        executor: E,
+    ) -> Result<Option<Self>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Sprint::current_query(project_id, now);
+
+        sqlx::query_as_with::<_, Sprint, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Load the Sprint after this one from the DB
+    ///
+    /// Returns the first sprint of the project starting at or after this sprint's end, or `None`.
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn next_db<'c, E>(&self, executor: E) -> Result<Option<Self>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Sprint::next_query(self.project_id, self.end);
+
+        sqlx::query_as_with::<_, Sprint, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// List the Sprints of a Project in the DB
+    ///
+    /// Lists the sprints of a project matching the spec in chronological order, unless it sorts them.
+    /// # Arguements
+    /// * `project_id` - The id of the project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_project_list_db<'c, E>(
+        project_id: Uuid,
+        spec: &ListSpec<SprintIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Sprint>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Sprint::list_project_query(project_id, spec, page, limit);
+        sqlx::query_as_with::<_, Sprint, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Sprint ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Sprint Project ID
+    pub fn project_id(&self) -> &Uuid {
+        &self.project_id
+    }
+
+    /// Get Sprint Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get Sprint Goal
+    pub fn goal(&self) -> &String {
+        &self.goal
+    }
+
+    /// Get Sprint Start Datetime
+    pub fn start(&self) -> &DateTime<Utc> {
+        &self.start
+    }
+
+    /// Get Sprint End Datetime
+    pub fn end(&self) -> &DateTime<Utc> {
+        &self.end
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the sprint.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the sprint `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Sets the sprint name
+    /// # Arguments
+    /// * `name` - The string name to use
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Goal
+    ///
+    /// Sets what the sprint should achieve
+    /// # Arguments
+    /// * `goal` - The string goal to use
+    pub fn set_goal(&mut self, goal: String) {
+        self.goal = goal;
+        self.edited();
+    }
+
+    /// Set Dates
+    ///
+    /// Moves or resizes the sprint
+    /// # Arguments
+    /// * `start` - When the sprint starts
+    /// * `end` - When the sprint ends, after `start`
+    pub fn set_dates(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) {
+        self.start = start;
+        self.end = end;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Sums the completed & remaining points of the Sprint on DB
+    ///
+    /// Tasks without an effort estimate count as zero points.
+    /// # Arguments
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn points_db<'c, E>(
+        &self,
+        workflow: &Workflow,
+        executor: E,
+    ) -> Result<SprintPoints, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Sprint::points_query(self.id, &workflow.completed);
+
+        sqlx::query_as_with::<_, SprintPoints, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Carries the unfinished Tasks of the Sprint over into another Sprint on DB
+    ///
+    /// Returns the number of carried over tasks, the change of every task is recorded in the
+    /// [`History`](super::history::History). Sprints of other projects are rejected with
+    /// [`DbError::SprintProjectMismatch`].
+    /// # Arguments
+    /// * `next` - The sprint to carry the tasks into, usually [`Sprint::next_db`]
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `author_id` - The id of the user that carries the tasks over
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn carry_over_db<'c, A>(
+        &self,
+        next: &Sprint,
+        workflow: &Workflow,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        if next.project_id != self.project_id {
+            return Err(DbError::SprintProjectMismatch);
+        }
+
+        let mut tx = conn.begin().await?;
+        let query = self.list_unfinished_query(&workflow.completed);
+        let tasks = sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(&mut *tx)
+            .await?;
+        for mut task in tasks.iter().cloned() {
+            task.set_sprint_db(Some(next.id), author_id, &mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(tasks.len() as u64)
+    }
+
+    /// Generates a sqlx query to Find the running Sprint of a Project on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `now` - The current datetime
+    pub fn current_query(project_id: Uuid, now: DateTime<Utc>) -> (String, SqlxValues) {
+        Sprint::select()
+            .and_where(Expr::col(SprintIden::ProjectId).eq(project_id))
+            .and_where(Expr::col(SprintIden::Start).lte(now))
+            .and_where(Expr::col(SprintIden::End).gt(now))
+            .order_by(SprintIden::Start, Order::Asc)
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the first Sprint of a Project starting at or after a datetime on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `after` - The inclusive lower bound of the sprint start
+    pub fn next_query(project_id: Uuid, after: DateTime<Utc>) -> (String, SqlxValues) {
+        Sprint::select()
+            .and_where(Expr::col(SprintIden::ProjectId).eq(project_id))
+            .and_where(Expr::col(SprintIden::Start).gte(after))
+            .order_by(SprintIden::Start, Order::Asc)
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Sprints of a Project in the DB
+    ///
+    /// # Arguements
+    /// * `project_id` - The id of the project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_project_query(
+        project_id: Uuid,
+        spec: &ListSpec<SprintIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Sprint::list_select(spec)
+            .and_where(Expr::col(SprintIden::ProjectId).eq(project_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Sum the completed & remaining points of a Sprint on DB
+    ///
+    /// # Arguments
+    /// * `id` - The id of the sprint
+    /// * `completed` - The task statuses that count as completed
+    pub fn points_query(id: Uuid, completed: &[String]) -> (String, SqlxValues) {
+        let done = || Expr::col(TaskIden::Status).is_in(completed.iter().cloned());
+        let not_done = || Expr::col(TaskIden::Status).is_not_in(completed.iter().cloned());
+        let points = |when: SimpleExpr| {
+            Func::cust(Alias::new("TOTAL")).arg(Expr::case(
+                when,
+                Func::coalesce([Expr::col(TaskIden::Effort).into(), Expr::val(0.0).into()]),
+            ))
+        };
+        let tasks = |when: SimpleExpr| Func::count(Expr::case(when, 1));
+
+        Query::select()
+            .expr_as(points(done()), Alias::new("completed_points"))
+            .expr_as(points(not_done()), Alias::new("remaining_points"))
+            .expr_as(tasks(done()), Alias::new("completed_tasks"))
+            .expr_as(tasks(not_done()), Alias::new("remaining_tasks"))
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::SprintId).eq(id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the unfinished Tasks of the Sprint on DB
+    ///
+    /// # Arguments
+    /// * `completed` - The task statuses that count as completed
+    pub fn list_unfinished_query(&self, completed: &[String]) -> (String, SqlxValues) {
+        Task::select()
+            .and_where(Expr::col(TaskIden::SprintId).eq(self.id))
+            .and_where(Expr::col(TaskIden::Status).is_not_in(completed.iter().cloned()))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the sprint edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for Sprint {
+    type Iden = SprintIden;
+    type Key = Uuid;
+
+    const TABLE: SprintIden = SprintIden::Table;
+    const KEY: &'static [SprintIden] = &[SprintIden::Id];
+    const CREATED: Option<SprintIden> = Some(SprintIden::Created);
+    const COLUMNS: &'static [SprintIden] = &[
+        SprintIden::Id,
+        SprintIden::ProjectId,
+        SprintIden::Name,
+        SprintIden::Goal,
+        SprintIden::Start,
+        SprintIden::End,
+        SprintIden::Created,
+        SprintIden::Modified,
+    ];
+    const ORDER_BY: Option<SprintIden> = Some(SprintIden::Start);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.project_id.into(),
+            self.name.clone().into(),
+            self.goal.clone().into(),
+            self.start.into(),
+            self.end.into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a sprint keeps its tasks, they are no longer planned into a sprint
+impl Repository for Sprint {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sprint, SprintPoints};
+    use crate::config::{init_memory_db, Workflow};
+    use crate::db::filter::{Filter, ListSpec};
+    use crate::db::projects::Project;
+    use crate::db::tasks::{Task, TaskIden};
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    async fn create_sprint(
+        project: &Project,
+        start: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Sprint {
+        let mut sprint = Sprint::new(
+            *project.id(),
+            "SPRINT".into(),
+            "GOAL".into(),
+            start,
+            start + Duration::weeks(2),
+        );
+        sprint.assign_id().assign_created();
+        sprint.insert(conn).await.unwrap();
+        sprint
+    }
+
+    async fn create_task(
+        sprint: &Sprint,
+        effort: Option<f64>,
+        status: &str,
+        conn: &mut SqliteConnection,
+    ) -> Task {
+        let project = Project::from_db(*sprint.project_id(), &mut *conn)
+            .await
+            .unwrap();
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.set_effort(effort);
+        task.set_status(status.into());
+        task.insert(&mut *conn).await.unwrap();
+        task.set_sprint_db(Some(*sprint.id()), *project.author_id(), conn)
+            .await
+            .unwrap();
+        task
+    }
+
+    fn monday() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn insert() {
+        let query = Sprint::new(
+            Uuid::default(),
+            "NAME".into(),
+            "GOAL".into(),
+            monday(),
+            monday(),
+        )
+        .insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"sprint\" (\"id\", \"project_id\", \"name\", \"goal\", \"start\", \"end\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[tokio::test]
+    async fn dates_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let first = create_sprint(&project, monday(), &mut conn).await;
+        let second = create_sprint(&project, monday() + Duration::weeks(2), &mut conn).await;
+
+        let mut backwards = Sprint::new(
+            *project.id(),
+            "NAME".into(),
+            "GOAL".into(),
+            monday(),
+            monday(),
+        );
+        backwards.assign_id().assign_created();
+        assert!(backwards.insert(&mut conn).await.is_err());
+
+        let current =
+            Sprint::from_current_db(*project.id(), monday() + Duration::days(15), &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(current, Some(second.clone()));
+        assert_eq!(
+            first.next_db(&mut conn).await.unwrap(),
+            Some(second.clone())
+        );
+        assert_eq!(second.next_db(&mut conn).await.unwrap(), None);
+        assert_eq!(
+            Sprint::from_project_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            vec![first, second]
+        );
+    }
+
+    #[tokio::test]
+    async fn scope_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut other = Project::new(
+            *project.workspace_id(),
+            "NAME".into(),
+            "DESC".into(),
+            *project.author_id(),
+        );
+        other.assign_id().assign_created();
+        other.insert_db(&mut conn).await.unwrap();
+        let sprint = create_sprint(&project, monday(), &mut conn).await;
+        let foreign = create_sprint(&other, monday(), &mut conn).await;
+
+        let mut task = create_task(&sprint, Some(3.0), "todo", &mut conn).await;
+        assert_eq!(
+            Task::from_sprint_list_db(*sprint.id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            vec![task.clone()]
+        );
+        let spec = ListSpec::new().filter(Filter::equals(TaskIden::Status, "done"));
+        assert!(
+            Task::from_sprint_list_db(*sprint.id(), &spec, 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(matches!(
+            task.set_sprint_db(Some(*foreign.id()), *task.author_id(), &mut conn)
+                .await,
+            Err(DbError::SprintProjectMismatch)
+        ));
+        assert_eq!(task.sprint_id(), &Some(*sprint.id()));
+
+        // moving a task to another project takes it out of its sprint
+        task.move_subtree_db(*other.id(), *task.author_id(), &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(task.sprint_id(), &None);
+        assert!(
+            Task::from_sprint_list_db(*sprint.id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn points_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let sprint = create_sprint(&project, monday(), &mut conn).await;
+        let next = create_sprint(&project, monday() + Duration::weeks(2), &mut conn).await;
+
+        assert_eq!(
+            sprint.points_db(&workflow, &mut conn).await.unwrap(),
+            SprintPoints {
+                completed_points: 0.0,
+                remaining_points: 0.0,
+                completed_tasks: 0,
+                remaining_tasks: 0,
+            }
+        );
+
+        create_task(&sprint, Some(5.0), "done", &mut conn).await;
+        create_task(&sprint, Some(3.0), "todo", &mut conn).await;
+        create_task(&sprint, Some(2.0), "review", &mut conn).await;
+        create_task(&sprint, None, "todo", &mut conn).await;
+
+        let points = sprint.points_db(&workflow, &mut conn).await.unwrap();
+        assert_eq!(
+            points,
+            SprintPoints {
+                completed_points: 5.0,
+                remaining_points: 5.0,
+                completed_tasks: 1,
+                remaining_tasks: 3,
+            }
+        );
+        assert_eq!(points.total_points(), 10.0);
+
+        assert_eq!(
+            sprint
+                .carry_over_db(&next, &workflow, *project.author_id(), &mut conn)
+                .await
+                .unwrap(),
+            3
+        );
+        let points = sprint.points_db(&workflow, &mut conn).await.unwrap();
+        assert_eq!((points.completed_tasks, points.remaining_tasks), (1, 0));
+        let points = next.points_db(&workflow, &mut conn).await.unwrap();
+        assert_eq!((points.remaining_points, points.remaining_tasks), (5.0, 3));
+
+        let other = create_project(&mut conn).await;
+        let foreign = create_sprint(&other, monday(), &mut conn).await;
+        assert!(matches!(
+            next.carry_over_db(&foreign, &workflow, *project.author_id(), &mut conn)
+                .await,
+            Err(DbError::SprintProjectMismatch)
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+//! Database Store
+//! This file contains the [`Store`], the shared handle to the taskify database. It is backed by a connection pool,
+//! so clones of it can query concurrently, e.g. from background jobs and the TUI, instead of serializing on one
+//! [`SqliteConnection`](sqlx::SqliteConnection). The database helpers take any [`Executor`](sqlx::Executor), e.g. the
+//! [`Store::pool`], helpers running several queries take an [`Acquire`](sqlx::Acquire) and run them in a transaction
+//! of their own. Work spanning several helpers that has to succeed or fail as a whole runs in a
+//! [`Store::transaction`].
+
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
+
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::{Error, Sqlite, Transaction};
+
+use crate::config::Database;
+
+/// The most connections a file database store opens at once
+const MAX_CONNECTIONS: u32 = 8;
+
+/// How long waiting for a connection may take before it fails
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The future of a [`Store::transaction`] callback, it borrows the transaction it runs in
+pub type TransactionFuture<'c, R, E> = Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>;
+
+/// The shared, cloneable handle to the taskify database
+#[derive(Debug, Clone)]
+pub struct Store {
+    pool: SqlitePool,
+}
+
+impl Store {
+    /// Opens the database store & applies migrations
+    ///
+    /// The database file is created if it is missing and uses write-ahead logging, so readers don't block the
+    /// writer.
+    /// # Arguments
+    /// * `config` - The database configuration
+    ///
+    /// # Examples:
+    /// ```
+    /// # use taskify::config::Database;
+    /// # use taskify::db::Store;
+    /// # #[tokio::test]
+    /// # async fn test() -> Result<(), sqlx::Error> {
+    /// let store = Store::open(&Database { path: "taskify.db".into() }).await?;
+    /// let mut conn = store.acquire().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn open(config: &Database) -> Result<Self, Error> {
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", config.path))?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .acquire_timeout(ACQUIRE_TIMEOUT)
+            .connect_with(options)
+            .await?;
+
+        Store::migrate(pool).await
+    }
+
+    /// Opens an in-memory database store & applies migrations
+    ///
+    /// Every connection to `:memory:` is a database of its own, so the store keeps exactly one connection open for
+    /// as long as it lives. Its clones still share it, but take turns.
+    pub async fn open_memory() -> Result<Self, Error> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .acquire_timeout(ACQUIRE_TIMEOUT)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?)
+            .await?;
+
+        Store::migrate(pool).await
+    }
+
+    /// Acquires a connection from the store
+    ///
+    /// The connection dereferences to a [`SqliteConnection`](sqlx::SqliteConnection) and goes back to the store when it is dropped.
+    pub async fn acquire(&self) -> Result<PoolConnection<Sqlite>, Error> {
+        self.pool.acquire().await
+    }
+
+    /// Begins a transaction on a connection from the store
+    ///
+    /// The transaction dereferences to a [`SqliteConnection`](sqlx::SqliteConnection) and is rolled back unless it is committed.
+    pub async fn begin(&self) -> Result<Transaction<'static, Sqlite>, Error> {
+        self.pool.begin().await
+    }
+
+    /// Runs a callback in a transaction of the store
+    ///
+    /// The transaction is committed if the callback returns `Ok` and rolled back if it returns `Err`, so the
+    /// database helpers called in it either all take effect or none does. Helpers that begin transactions of their
+    /// own nest them as savepoints.
+    /// # Arguments
+    /// * `callback` - The work to do atomically, it gets the transaction and returns a boxed future
+    ///
+    /// # Examples:
+    /// ```
+    /// # use taskify::db::{DbError, Repository, Store};
+    /// # use taskify::db::workspaces::Workspace;
+    /// # #[tokio::test]
+    /// # async fn test() -> Result<(), DbError> {
+    /// let store = Store::open_memory().await?;
+    /// let mut workspace = Workspace::new("team".into(), "DESC".into());
+    /// workspace.assign_id().assign_created();
+    /// store
+    ///     .transaction(|tx| {
+    ///         Box::pin(async move {
+    ///             workspace.insert(&mut **tx).await?;
+    ///             Ok::<_, DbError>(())
+    ///         })
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transaction<F, R, E>(&self, callback: F) -> Result<R, E>
+    where
+        F: for<'c> FnOnce(&'c mut Transaction<'static, Sqlite>) -> TransactionFuture<'c, R, E>,
+        E: From<Error>,
+    {
+        let mut tx = self.begin().await?;
+        match callback(&mut tx).await {
+            Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(err) => {
+                tx.rollback().await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Get the connection pool backing the store
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Closes every connection of the store and its clones
+    pub async fn close(&self) {
+        self.pool.close().await
+    }
+
+    // Private Functions
+
+    /// Applies the migrations and wraps the pool
+    async fn migrate(pool: SqlitePool) -> Result<Self, Error> {
+        sqlx::migrate!("./migrations/").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Store;
+    use crate::config::Database;
+    use crate::db::filter::ListSpec;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::{create_test_workspace, Workspace};
+    use crate::db::{DbError, Repository};
+    use chrono::Utc;
+
+    fn assert_shared<T: Clone + Send + Sync + 'static>() {}
+
+    #[tokio::test]
+    async fn concurrent_db() {
+        assert_shared::<Store>();
+        let path = std::env::temp_dir().join(format!("taskify-store-{}.db", uuid::Uuid::new_v4()));
+        let store = Store::open(&Database {
+            path: path.to_string_lossy().into_owned(),
+        })
+        .await
+        .unwrap();
+
+        let jobs: Vec<_> = (0..16)
+            .map(|i| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let mut workspace = Workspace::new(format!("workspace-{}", i), "DESC".into());
+                    workspace.assign_id().assign_created();
+                    let mut conn = store.acquire().await.unwrap();
+                    workspace.insert(&mut *conn).await.unwrap();
+                })
+            })
+            .collect();
+        for job in jobs {
+            job.await.unwrap();
+        }
+
+        let workspaces = Workspace::from_list_db(&ListSpec::new(), 0, 100, store.pool())
+            .await
+            .unwrap();
+        assert_eq!(workspaces.len(), 17);
+        store.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_db() {
+        let store = Store::open_memory().await.unwrap();
+        let mut workspace = Workspace::new("team".into(), "DESC".into());
+        workspace.assign_id().assign_created();
+
+        let mut tx = store.begin().await.unwrap();
+        workspace.insert(&mut *tx).await.unwrap();
+        tx.rollback().await.unwrap();
+
+        let clone = store.clone();
+        workspace
+            .insert(&mut *clone.acquire().await.unwrap())
+            .await
+            .unwrap();
+        let mut conn = store.acquire().await.unwrap();
+        assert_eq!(
+            Workspace::from_list_db(&ListSpec::new(), 0, 10, &mut *conn)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn pool_db() {
+        let store = Store::open_memory().await.unwrap();
+        let mut conn = store.acquire().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let author = *create_test_user(&mut conn).await.id();
+        drop(conn);
+
+        let mut project = Project::new(workspace, "NAME".into(), "DESC".into(), author);
+        project.assign_id().assign_created();
+        project.insert_db(store.pool()).await.unwrap();
+        let stored = Project::from_db(*project.id(), store.pool()).await.unwrap();
+        assert_eq!(stored, project);
+        let rewound = Project::from_history_db(*project.id(), Utc::now(), store.pool())
+            .await
+            .unwrap();
+        assert_eq!(rewound, Some(project));
+    }
+
+    #[tokio::test]
+    async fn transaction_db() {
+        let store = Store::open_memory().await.unwrap();
+        let mut conn = store.acquire().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let author = *create_test_user(&mut conn).await.id();
+        let mut from = Project::new(workspace, "FROM".into(), "DESC".into(), author);
+        from.assign_id().assign_created();
+        from.insert_db(&mut *conn).await.unwrap();
+        let mut into = Project::new(workspace, "INTO".into(), "DESC".into(), author);
+        into.assign_id().assign_created();
+        let mut task = Task::new(*from.id(), "TITLE".into(), "BODY".into(), author);
+        task.assign_id().assign_created();
+        task.insert(&mut *conn).await.unwrap();
+        drop(conn);
+
+        // Fails after the project is inserted and merged, nothing is kept
+        let (mut merged, created) = (from.clone(), into.clone());
+        let result = store
+            .transaction(|tx| {
+                Box::pin(async move {
+                    created.insert_db(&mut *tx).await?;
+                    merged.merge_into_db(&created, author, tx).await?;
+                    Err::<(), _>(DbError::HierarchyCycle)
+                })
+            })
+            .await;
+        assert!(matches!(result, Err(DbError::HierarchyCycle)));
+
+        let mut conn = store.acquire().await.unwrap();
+        let stored = Task::from_db(*task.id(), &mut *conn).await.unwrap();
+        assert_eq!(stored.project_id(), from.id());
+        assert!(!Project::from_db(*from.id(), &mut *conn)
+            .await
+            .unwrap()
+            .is_archived());
+        assert!(Project::from_db(*into.id(), &mut *conn).await.is_err());
+        drop(conn);
+
+        let moved = store
+            .transaction(|tx| {
+                Box::pin(async move {
+                    into.insert_db(&mut *tx).await?;
+                    from.merge_into_db(&into, author, tx).await
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(moved, 1);
+    }
+}
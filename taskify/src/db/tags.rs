@@ -0,0 +1,963 @@
+//! Tag Database Entity
+//! This file contains the tag database entity, colored labels like `bug` or `q3` that can be attached to both
+//! [`Project`](super::projects::Project)s and [`Task`](super::tasks::Task)s. Associations reference tags by id, so
+//! renaming a tag updates every association at once. Every tag belongs to a
+//! [`Workspace`](super::workspaces::Workspace), names are unique per workspace and the DB only attaches tags to the
+//! projects & tasks of their own workspace.
+
+use chrono::{DateTime, Utc};
+use sea_query::{
+    enum_def, Alias, Expr, Func, OnConflict, Order, Query, SelectStatement, SimpleExpr,
+    SqliteQueryBuilder, SubQueryStatement,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::filter::ListSpec;
+use crate::db::{DbError, Entity, Repository};
+
+/// The tag database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Tag {
+    id: Uuid,
+    workspace_id: Uuid,
+    name: String,
+    color: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// The association between a project and a tag
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct ProjectTag {
+    pub project_id: Uuid,
+    pub tag_id: Uuid,
+}
+
+/// The association between a task and a tag
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct TaskTag {
+    pub task_id: Uuid,
+    pub tag_id: Uuid,
+}
+
+/// A tag with the number of projects & tasks it is attached to, as returned by [`Tag::from_usage_list_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct TagUsage {
+    #[sqlx(flatten)]
+    pub tag: Tag,
+    pub projects: i64,
+    pub tasks: i64,
+}
+
+impl Tag {
+    /// New Tag
+    ///
+    /// Create a new tag entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `workspace_id` - The id of the workspace the tag belongs to
+    /// * `name` - The tag name, unique in the workspace
+    /// * `color` - The tag color, e.g. `#d73a4a`
+    pub fn new(workspace_id: Uuid, name: String, color: String) -> Self {
+        Self {
+            workspace_id,
+            name,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// Load a tag from the DB by its name
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace of the tag
+    /// * `name` - The tag name to search for
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_name_db<'c, E>(
+        workspace_id: Uuid,
+        name: &str,
+        executor: E,
+    ) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Tag::select_name_query(workspace_id, name);
+
+        sqlx::query_as_with::<_, Tag, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// List all Tags in the DB
+    ///
+    /// Lists the tags matching the spec, ordered by name unless it sorts them.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tags of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_workspace_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<TagIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Tag>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Tag::list_workspace_query(workspace_id, spec, page, limit);
+        sqlx::query_as_with::<_, Tag, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tags of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_project_list_db<'c, E>(
+        project_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<Tag>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Tag::list_project_query(project_id);
+        sqlx::query_as_with::<_, Tag, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tags of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(task_id: Uuid, executor: E) -> Result<Vec<Tag>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Tag::list_task_query(task_id);
+        sqlx::query_as_with::<_, Tag, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List all Tags with their usage counts in the DB
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace to list the tags of
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_usage_list_db<'c, E>(
+        workspace_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<TagUsage>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Tag::list_usage_query(workspace_id);
+        sqlx::query_as_with::<_, TagUsage, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Tag ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Tag Workspace ID
+    pub fn workspace_id(&self) -> &Uuid {
+        &self.workspace_id
+    }
+
+    /// Get Tag Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get Tag Color
+    pub fn color(&self) -> &String {
+        &self.color
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the tag.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the tag `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Renames the tag, every project & task keeps the tag
+    /// # Arguments
+    /// * `name` - The string name to use, unique in the workspace
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Color
+    ///
+    /// Sets the tag color
+    /// # Arguments
+    /// * `color` - The string color to use
+    pub fn set_color(&mut self, color: String) {
+        self.color = color;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Merges the Tag into another Tag on DB
+    ///
+    /// Every project & task tagged with this tag is tagged with `into` instead and this tag is deleted, atomically.
+    /// A tag can not be merged into itself, that would only delete it, [`DbError::SelfMerge`] is returned instead.
+    /// Tags of other workspaces are rejected with [`DbError::WorkspaceMismatch`].
+    /// # Arguments
+    /// * `into` - The tag to keep
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn merge_db<'c, A>(&self, into: &Tag, conn: A) -> Result<(), DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        if into.id == self.id {
+            return Err(DbError::SelfMerge);
+        }
+        if into.workspace_id != self.workspace_id {
+            return Err(DbError::WorkspaceMismatch);
+        }
+
+        let projects = self.merge_projects_query(into.id);
+        let tasks = self.merge_tasks_query(into.id);
+
+        let mut tx = conn.begin().await?;
+        sqlx::query_with(&projects.0, projects.1)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query_with(&tasks.0, tasks.1)
+            .execute(&mut *tx)
+            .await?;
+        self.delete(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Tags a Project on DB, tagging it twice is a no-op
+    ///
+    /// The DB rejects projects of other workspaces.
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn attach_project<'c, E>(
+        &self,
+        project_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.attach_project_query(project_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Untags a Project on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn detach_project<'c, E>(
+        &self,
+        project_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.detach_project_query(project_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Tags a Task on DB, tagging it twice is a no-op
+    ///
+    /// The DB rejects tasks of other workspaces.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn attach_task<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.attach_task_query(task_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Untags a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn detach_task<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.detach_task_query(task_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to Find a Tag by name on DB
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace of the tag
+    /// * `name` - The tag name to search for
+    pub fn select_name_query(workspace_id: Uuid, name: &str) -> (String, SqlxValues) {
+        Tag::select()
+            .and_where(Expr::col(TagIden::WorkspaceId).eq(workspace_id))
+            .and_where(Expr::col(TagIden::Name).eq(name))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Tags in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tags of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_workspace_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<TagIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Tag::list_select(spec)
+            .and_where(Expr::col(TagIden::WorkspaceId).eq(workspace_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tags of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn list_project_query(project_id: Uuid) -> (String, SqlxValues) {
+        Tag::select()
+            .and_where(
+                Expr::col(TagIden::Id).in_subquery(
+                    Query::select()
+                        .column(ProjectTagIden::TagId)
+                        .from(ProjectTagIden::Table)
+                        .and_where(Expr::col(ProjectTagIden::ProjectId).eq(project_id))
+                        .to_owned(),
+                ),
+            )
+            .order_by(TagIden::Name, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tags of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn list_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        Tag::select()
+            .and_where(
+                Expr::col(TagIden::Id).in_subquery(
+                    Query::select()
+                        .column(TaskTagIden::TagId)
+                        .from(TaskTagIden::Table)
+                        .and_where(Expr::col(TaskTagIden::TaskId).eq(task_id))
+                        .to_owned(),
+                ),
+            )
+            .order_by(TagIden::Name, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Tags with their usage counts in the DB
+    ///
+    /// # Arguments
+    /// * `workspace_id` - The id of the workspace to list the tags of
+    pub fn list_usage_query(workspace_id: Uuid) -> (String, SqlxValues) {
+        let count = |table: Alias, tag: Alias| {
+            SimpleExpr::SubQuery(
+                None,
+                Box::new(SubQueryStatement::SelectStatement(
+                    Query::select()
+                        .expr(Func::count(Expr::col(tag.clone())))
+                        .from(table.clone())
+                        .and_where(Expr::col((table, tag)).equals((TagIden::Table, TagIden::Id)))
+                        .to_owned(),
+                )),
+            )
+        };
+
+        Tag::joined_select()
+            .expr_as(
+                count(Alias::new("project_tag"), Alias::new("tag_id")),
+                Alias::new("projects"),
+            )
+            .expr_as(
+                count(Alias::new("task_tag"), Alias::new("tag_id")),
+                Alias::new("tasks"),
+            )
+            .and_where(Expr::col((TagIden::Table, TagIden::WorkspaceId)).eq(workspace_id))
+            .order_by(TagIden::Name, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Tag a Project on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn attach_project_query(&self, project_id: Uuid) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(ProjectTagIden::Table)
+            .columns([ProjectTagIden::ProjectId, ProjectTagIden::TagId])
+            .values([project_id.into(), self.id.into()])
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([ProjectTagIden::ProjectId, ProjectTagIden::TagId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Untag a Project on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    pub fn detach_project_query(&self, project_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(ProjectTagIden::Table)
+            .and_where(Expr::col(ProjectTagIden::ProjectId).eq(project_id))
+            .and_where(Expr::col(ProjectTagIden::TagId).eq(self.id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Tag a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn attach_task_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(TaskTagIden::Table)
+            .columns([TaskTagIden::TaskId, TaskTagIden::TagId])
+            .values([task_id.into(), self.id.into()])
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([TaskTagIden::TaskId, TaskTagIden::TagId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Untag a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn detach_task_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(TaskTagIden::Table)
+            .and_where(Expr::col(TaskTagIden::TaskId).eq(task_id))
+            .and_where(Expr::col(TaskTagIden::TagId).eq(self.id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Tag every Project tagged with this tag with `into` on DB
+    ///
+    /// # Arguments
+    /// * `into` - The id of the tag to merge into
+    pub fn merge_projects_query(&self, into: Uuid) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(ProjectTagIden::Table)
+            .columns([ProjectTagIden::ProjectId, ProjectTagIden::TagId])
+            .select_from(
+                Query::select()
+                    .column(ProjectTagIden::ProjectId)
+                    .expr(Expr::val(into))
+                    .from(ProjectTagIden::Table)
+                    .and_where(Expr::col(ProjectTagIden::TagId).eq(self.id))
+                    .to_owned(),
+            )
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([ProjectTagIden::ProjectId, ProjectTagIden::TagId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Tag every Task tagged with this tag with `into` on DB
+    ///
+    /// # Arguments
+    /// * `into` - The id of the tag to merge into
+    pub fn merge_tasks_query(&self, into: Uuid) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(TaskTagIden::Table)
+            .columns([TaskTagIden::TaskId, TaskTagIden::TagId])
+            .select_from(
+                Query::select()
+                    .column(TaskTagIden::TaskId)
+                    .expr(Expr::val(into))
+                    .from(TaskTagIden::Table)
+                    .and_where(Expr::col(TaskTagIden::TagId).eq(self.id))
+                    .to_owned(),
+            )
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([TaskTagIden::TaskId, TaskTagIden::TagId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find the items tagged with every one of the tags
+    ///
+    /// Selects the ids (`item`) of the rows of a join table that are associated with all `tag_ids`. Every item has all
+    /// of no tags, so an empty `tag_ids` returns `None` and the caller leaves the condition out.
+    /// # Arguments
+    /// * `table` - The join table, e.g. [`ProjectTagIden::Table`]
+    /// * `item` - The item id column of the join table, e.g. [`ProjectTagIden::ProjectId`]
+    /// * `tag_ids` - The ids of the tags
+    pub(crate) fn tagged_subquery<T, I>(
+        table: T,
+        item: I,
+        tag_ids: &[Uuid],
+    ) -> Option<SelectStatement>
+    where
+        T: sea_query::IntoIden + 'static,
+        I: sea_query::IntoIden + Clone + 'static,
+    {
+        if tag_ids.is_empty() {
+            return None;
+        }
+
+        let mut tag_ids = tag_ids.to_vec();
+        tag_ids.sort();
+        tag_ids.dedup();
+
+        Query::select()
+            .column(item.clone())
+            .from(table)
+            .and_where(Expr::col(Alias::new("tag_id")).is_in(tag_ids.iter().copied()))
+            .group_by_col(item)
+            .and_having(
+                Expr::expr(Func::count_distinct(Expr::col(Alias::new("tag_id"))))
+                    .eq(tag_ids.len() as i64),
+            )
+            .to_owned()
+            .into()
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the tag edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for Tag {
+    type Iden = TagIden;
+    type Key = Uuid;
+
+    const TABLE: TagIden = TagIden::Table;
+    const KEY: &'static [TagIden] = &[TagIden::Id];
+    const CREATED: Option<TagIden> = Some(TagIden::Created);
+    const COLUMNS: &'static [TagIden] = &[
+        TagIden::Id,
+        TagIden::WorkspaceId,
+        TagIden::Name,
+        TagIden::Color,
+        TagIden::Created,
+        TagIden::Modified,
+    ];
+    const ORDER_BY: Option<TagIden> = Some(TagIden::Name);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.workspace_id.into(),
+            self.name.clone().into(),
+            self.color.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a tag removes it from every project & task
+impl Repository for Tag {}
+
+#[cfg(test)]
+mod tests {
+    use super::Tag;
+    use crate::config::init_memory_db;
+    use crate::db::filter::{ListSpec, Sort};
+    use crate::db::projects::Project;
+    use crate::db::tasks::{Task, TaskIden};
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_tag(workspace_id: Uuid, name: &str, conn: &mut SqliteConnection) -> Tag {
+        let mut tag = Tag::new(workspace_id, name.into(), "#d73a4a".into());
+        tag.assign_id().assign_created();
+        tag.insert(conn).await.unwrap();
+        tag
+    }
+
+    async fn create_project(workspace_id: Uuid, conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            workspace_id,
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    async fn create_task(project: &Project, conn: &mut SqliteConnection) -> Task {
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    #[test]
+    fn insert() {
+        let query = Tag::new(Uuid::default(), "bug".into(), "#d73a4a".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"tag\" (\"id\", \"workspace_id\", \"name\", \"color\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn query() {
+        let query = Tag::select_query(Uuid::default());
+
+        assert_eq!(query.0, "SELECT \"id\", \"workspace_id\", \"name\", \"color\", \"created\", \"modified\" FROM \"tag\" WHERE \"id\" = ? LIMIT ?");
+    }
+
+    #[test]
+    fn attach_project() {
+        let query = Tag::default().attach_project_query(Uuid::default());
+
+        assert_eq!(query.0, "INSERT INTO \"project_tag\" (\"project_id\", \"tag_id\") VALUES (?, ?) ON CONFLICT (\"project_id\", \"tag_id\") DO NOTHING");
+    }
+
+    #[tokio::test]
+    async fn unique_name_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        create_tag(workspace, "bug", &mut conn).await;
+
+        let mut tag = Tag::new(workspace, "bug".into(), "#000000".into());
+        tag.assign_id().assign_created();
+        assert!(tag.insert(&mut conn).await.is_err());
+
+        // names are only unique within a workspace
+        let other = *create_test_workspace(&mut conn).await.id();
+        let mut tag = Tag::new(other, "bug".into(), "#000000".into());
+        tag.assign_id().assign_created();
+        tag.insert(&mut conn).await.unwrap();
+        assert_eq!(
+            Tag::from_name_db(other, "bug", &mut conn).await.unwrap(),
+            tag
+        );
+    }
+
+    #[tokio::test]
+    async fn attach_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let infra = create_tag(workspace, "infra", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        infra
+            .attach_project(*project.id(), &mut conn)
+            .await
+            .unwrap();
+        infra.attach_task(*task.id(), &mut conn).await.unwrap();
+
+        assert_eq!(
+            Tag::from_project_list_db(*project.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![bug.clone(), infra.clone()]
+        );
+        assert_eq!(
+            Tag::from_task_list_db(*task.id(), &mut conn).await.unwrap(),
+            vec![infra.clone()]
+        );
+
+        bug.detach_project(*project.id(), &mut conn).await.unwrap();
+        infra.detach_task(*task.id(), &mut conn).await.unwrap();
+        assert_eq!(
+            Tag::from_project_list_db(*project.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![infra]
+        );
+        assert!(Tag::from_task_list_db(*task.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn usage_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let q3 = create_tag(workspace, "q3", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        let other = create_project(workspace, &mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        bug.attach_project(*other.id(), &mut conn).await.unwrap();
+        bug.attach_task(*task.id(), &mut conn).await.unwrap();
+
+        let usage = Tag::from_usage_list_db(workspace, &mut conn).await.unwrap();
+        let counts: Vec<_> = usage
+            .iter()
+            .map(|usage| (usage.tag.name().as_str(), usage.projects, usage.tasks))
+            .collect();
+        assert_eq!(counts, [("bug", 2, 1), ("q3", 0, 0)]);
+        assert_eq!(usage[1].tag, q3);
+    }
+
+    #[tokio::test]
+    async fn rename_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let mut bug = create_tag(workspace, "bug", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+
+        bug.set_name("defect".into());
+        bug.update(&mut conn).await.unwrap();
+
+        assert_eq!(
+            Tag::from_name_db(workspace, "defect", &mut conn)
+                .await
+                .unwrap(),
+            bug
+        );
+        assert!(Tag::from_name_db(workspace, "bug", &mut conn)
+            .await
+            .is_err());
+        assert_eq!(
+            Tag::from_project_list_db(*project.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![bug]
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let defect = create_tag(workspace, "defect", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        let other = create_project(workspace, &mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        defect
+            .attach_project(*project.id(), &mut conn)
+            .await
+            .unwrap();
+        defect.attach_project(*other.id(), &mut conn).await.unwrap();
+        defect.attach_task(*task.id(), &mut conn).await.unwrap();
+
+        defect.merge_db(&bug, &mut conn).await.unwrap();
+
+        assert!(Tag::from_db(*defect.id(), &mut conn).await.is_err());
+        let usage = Tag::from_usage_list_db(workspace, &mut conn).await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].projects, usage[0].tasks), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn self_merge_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        bug.attach_task(*task.id(), &mut conn).await.unwrap();
+
+        assert!(matches!(
+            bug.merge_db(&bug, &mut conn).await,
+            Err(DbError::SelfMerge)
+        ));
+        assert_eq!(Tag::from_db(*bug.id(), &mut conn).await.unwrap(), bug);
+        let usage = Tag::from_usage_list_db(workspace, &mut conn).await.unwrap();
+        assert_eq!((usage[0].projects, usage[0].tasks), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn tagged_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let q3 = create_tag(workspace, "q3", &mut conn).await;
+        let project = create_project(workspace, &mut conn).await;
+        let other = create_project(workspace, &mut conn).await;
+        let both = create_task(&project, &mut conn).await;
+        let only_bug = create_task(&project, &mut conn).await;
+
+        bug.attach_project(*project.id(), &mut conn).await.unwrap();
+        q3.attach_project(*project.id(), &mut conn).await.unwrap();
+        bug.attach_project(*other.id(), &mut conn).await.unwrap();
+        bug.attach_task(*both.id(), &mut conn).await.unwrap();
+        q3.attach_task(*both.id(), &mut conn).await.unwrap();
+        bug.attach_task(*only_bug.id(), &mut conn).await.unwrap();
+
+        let projects = Project::from_tagged_list_db(
+            workspace,
+            &[*bug.id(), *q3.id()],
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(projects, vec![project.clone()]);
+        let projects = Project::from_tagged_list_db(
+            workspace,
+            &[*bug.id()],
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(projects.len(), 2);
+
+        let tasks = Task::from_tagged_list_db(
+            workspace,
+            &[*q3.id(), *bug.id(), *q3.id()],
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(tasks, vec![both.clone()]);
+        let tasks =
+            Task::from_tagged_list_db(workspace, &[*bug.id()], &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(tasks.len(), 2);
+        let spec = ListSpec::new().sort(Sort::Desc(TaskIden::Created));
+        let tasks = Task::from_tagged_list_db(workspace, &[*bug.id()], &spec, 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks, vec![only_bug, both]);
+
+        // no tags filter nothing out
+        let projects =
+            Project::from_tagged_list_db(workspace, &[], &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(projects.len(), 2);
+        let tasks = Task::from_tagged_list_db(workspace, &[], &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn workspace_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let other = *create_test_workspace(&mut conn).await.id();
+        let bug = create_tag(workspace, "bug", &mut conn).await;
+        let foreign = create_tag(other, "bug", &mut conn).await;
+        let project = create_project(other, &mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+
+        assert!(bug.attach_project(*project.id(), &mut conn).await.is_err());
+        assert!(bug.attach_task(*task.id(), &mut conn).await.is_err());
+        assert!(matches!(
+            bug.merge_db(&foreign, &mut conn).await,
+            Err(DbError::WorkspaceMismatch)
+        ));
+
+        assert_eq!(
+            Tag::from_workspace_list_db(workspace, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [bug]
+        );
+        let usage = Tag::from_usage_list_db(other, &mut conn).await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].tag, foreign);
+    }
+}
@@ -0,0 +1,3238 @@
+//! Task Database Entity
+//! This file contains the task database entity for taskify. Every task is owned by a [`Project`](super::projects::Project),
+//! purging a project deletes all of its tasks (`ON DELETE CASCADE`).
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use sea_query::{
+    enum_def, Alias, CommonTableExpression, Condition, Expr, Func, JoinType, NullOrdering, Order,
+    Query, SimpleExpr, SqliteQueryBuilder, SubQueryStatement, UnionType, Value, WithClause,
+};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config::{Urgency, Workflow};
+use crate::db::checklists::{ChecklistItem, ChecklistItemIden};
+use crate::db::custom_fields::{CustomField, CustomValueIden, FieldValue};
+use crate::db::dependencies::{TaskDependency, TaskDependencyIden};
+use crate::db::filter::{ListSpec, Sort};
+use crate::db::history::{Audited, History, HistoryAction};
+use crate::db::projects::Project;
+use crate::db::reminders::Reminder;
+use crate::db::tags::{Tag, TaskTagIden};
+use crate::db::transitions::TaskTransition;
+use crate::db::users::{TaskAssigneeIden, User};
+use crate::db::workflows::ProjectWorkflow;
+use crate::db::{DbError, Entity, Repository};
+use crate::recurrence::{RecurrenceError, RecurrenceRule};
+
+/// The status of new tasks, [`Task::create_db`] replaces it with the initial status of the project's workflow
+pub const DEFAULT_STATUS: &str = "todo";
+
+/// The message the DB aborts with when a parent change would make a task its own ancestor
+const HIERARCHY_CYCLE: &str = "task hierarchy cycle";
+
+/// The message the DB aborts with when a task is planned into a sprint of another project
+const SPRINT_PROJECT_MISMATCH: &str = "task sprint project mismatch";
+
+/// The priority of a task, `P0` is the most urgent
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    sqlx::Type,
+    Deserialize,
+    Serialize,
+)]
+#[repr(i64)]
+pub enum Priority {
+    P0 = 0,
+    P1 = 1,
+    #[default]
+    P2 = 2,
+    P3 = 3,
+    P4 = 4,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P{}", *self as i64)
+    }
+}
+
+/// The task database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Task {
+    id: Uuid,
+    project_id: Uuid,
+    parent_id: Option<Uuid>,
+    title: String,
+    body: String,
+    status: String,
+    start: Option<DateTime<Utc>>,
+    due: Option<DateTime<Utc>>,
+    recurrence: Option<String>,
+    priority: Priority,
+    effort: Option<f64>,
+    sprint_id: Option<Uuid>,
+    author_id: Uuid,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// A task together with its urgency score, as returned by [`Task::from_urgency_list_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct UrgentTask {
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub urgency: f64,
+}
+
+/// A task together with its checklist progress, as returned by [`Task::from_checklist_list_db`]
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct ChecklistTask {
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub checked: i64,
+    pub items: i64,
+}
+
+impl ChecklistTask {
+    /// The checklist progress, e.g. `3/5`
+    pub fn progress(&self) -> String {
+        format!("{}/{}", self.checked, self.items)
+    }
+}
+
+impl Task {
+    /// New Task
+    ///
+    /// Create a new task entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `project_id` - The id of the project that owns the task
+    /// * `title` - The task title
+    /// * `body` - The task body
+    /// * `author_id` - The id of the [`User`](super::users::User) that authors the task
+    pub fn new(project_id: Uuid, title: String, body: String, author_id: Uuid) -> Self {
+        Self {
+            project_id,
+            title,
+            body,
+            author_id,
+            status: DEFAULT_STATUS.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Load a task as it was at a datetime from the DB
+    ///
+    /// Rewinds the task using its [`History`], this also works for deleted tasks. Returns `None` if the task did not
+    /// exist at that time.
+    /// # Arguments
+    /// * `id` - The uuid v4 id to search for
+    /// * `at` - The datetime to load the task at
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn from_history_db<'c, A>(
+        id: Uuid,
+        at: DateTime<Utc>,
+        conn: A,
+    ) -> Result<Option<Self>, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Task::select_query(id);
+        let current = sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let state = History::rewind_db(current, id, at, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(state)
+    }
+
+    /// List all Tasks in the DB
+    ///
+    /// Lists the tasks matching the spec by providing a page and limit count, oldest first unless it sorts them.
+    /// Like the other workspace lists, it leaves out the tasks of archived & deleted projects.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_workspace_list_db<'c, E>(
+        workspace_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_workspace_query(workspace_id, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List all Tasks of a Project in the DB
+    ///
+    /// Lists the tasks owned by a project matching the spec by providing a page and limit count, oldest first unless
+    /// it sorts them.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_project_list_db<'c, E>(
+        project_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_project_query(project_id, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List all Tasks of a Project with their checklist progress in the DB
+    ///
+    /// Counts the checked & total checklist items of every listed task in the same query, oldest first unless the spec
+    /// sorts them.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_checklist_list_db<'c, E>(
+        project_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<ChecklistTask>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_checklist_query(project_id, spec, page, limit);
+        sqlx::query_as_with::<_, ChecklistTask, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks of a Sprint in the DB
+    ///
+    /// Lists the scope of a sprint matching the spec, most urgent priority first unless the spec sorts them.
+    /// # Arguements
+    /// * `sprint_id` - The id of the sprint
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_sprint_list_db<'c, E>(
+        sprint_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_sprint_query(sprint_id, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks of a Custom Field's Project ordered by the field value in the DB
+    ///
+    /// Tasks without a value for the field are listed last, unless `filter` is set, then only the tasks whose value
+    /// equals it are listed. Filters that don't [validate](CustomField::validate) are rejected. The sorts of the spec
+    /// order the tasks with the same value.
+    /// # Arguements
+    /// * `field` - The custom field to filter & sort by
+    /// * `filter` - The value tasks must have
+    /// * `order` - The order of the field values
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_field_list_db<'c, E>(
+        field: &CustomField,
+        filter: Option<FieldValue>,
+        order: Order,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        if let Some(value) = &filter {
+            field.validate(value)?;
+        }
+
+        let query = Task::list_field_query(field, filter, order, spec, page, limit);
+        Ok(sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await?)
+    }
+
+    /// List the Tasks assigned to a User in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `user_id` - The id of the assignee
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_assignee_list_db<'c, E>(
+        workspace_id: Uuid,
+        user_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_assignee_query(workspace_id, user_id, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks tagged with every one of the Tags in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `tag_ids` - The ids of the tags, a task must have all of them, no tags list every task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_tagged_list_db<'c, E>(
+        workspace_id: Uuid,
+        tag_ids: &[Uuid],
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_tagged_query(workspace_id, tag_ids, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks of a Project that are ready to start in the DB
+    ///
+    /// A task is ready when it is not completed and every task blocking it is completed. Lists the ready tasks matching
+    /// the spec, oldest first unless it sorts them.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `workflow` - The workflow of the project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_ready_list_db<'c, E>(
+        project_id: Uuid,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_ready_query(project_id, &workflow.completed, spec, page, limit);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks of a Project in topological order from the DB
+    ///
+    /// Every task comes after all of the tasks of the same project blocking it, tasks that don't depend on each
+    /// other keep their creation order.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn from_topological_db<'c, A>(project_id: Uuid, conn: A) -> Result<Vec<Task>, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Task::project_query(project_id);
+        let tasks = sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(&mut *tx)
+            .await?;
+        let dependencies = TaskDependency::from_project_list_db(project_id, &mut *tx).await?;
+
+        let index: HashMap<Uuid, usize> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| (task.id, i))
+            .collect();
+        let mut blockers = vec![0usize; tasks.len()];
+        let mut blocking = vec![vec![]; tasks.len()];
+        for dependency in &dependencies {
+            if let (Some(&blocker), Some(&blocked)) = (
+                index.get(dependency.blocker_id()),
+                index.get(dependency.blocked_id()),
+            ) {
+                blocking[blocker].push(blocked);
+                blockers[blocked] += 1;
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..tasks.len())
+            .filter(|&i| blockers[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(tasks.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(i);
+            for &blocked in &blocking[i] {
+                blockers[blocked] -= 1;
+                if blockers[blocked] == 0 {
+                    ready.push(Reverse(blocked));
+                }
+            }
+        }
+
+        let mut tasks: Vec<Option<Task>> = tasks.into_iter().map(Some).collect();
+        tx.commit().await?;
+        Ok(order.into_iter().filter_map(|i| tasks[i].take()).collect())
+    }
+
+    /// List the overdue Tasks in the DB
+    ///
+    /// Lists the tasks that are not completed and were due before `now`, the most overdue first.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The current datetime
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_overdue_list_db<'c, E>(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_due_query(
+            workspace_id,
+            None,
+            now,
+            &workflow.completed,
+            spec,
+            page,
+            limit,
+        );
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks due today in the DB
+    ///
+    /// Lists the tasks that are not completed and are due on the (UTC) day of `now`.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The current datetime
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_due_today_list_db<'c, E>(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let today = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let query = Task::list_due_query(
+            workspace_id,
+            Some(today),
+            today + Duration::days(1),
+            &workflow.completed,
+            spec,
+            page,
+            limit,
+        );
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks due this week in the DB
+    ///
+    /// Lists the tasks that are not completed and are due in the (UTC, Monday to Sunday) week of `now`.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The current datetime
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_due_this_week_list_db<'c, E>(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let monday = (now.date_naive()
+            - Duration::days(now.weekday().num_days_from_monday() as i64))
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+        let query = Task::list_due_query(
+            workspace_id,
+            Some(monday),
+            monday + Duration::weeks(1),
+            &workflow.completed,
+            spec,
+            page,
+            limit,
+        );
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the upcoming Tasks in the DB
+    ///
+    /// Lists the tasks that are not completed and are due within the next `days` days of `now`.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The current datetime
+    /// * `days` - How many days ahead to look
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    #[allow(clippy::too_many_arguments)]
+    pub async fn from_upcoming_list_db<'c, E>(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        days: i64,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_due_query(
+            workspace_id,
+            Some(now),
+            now + Duration::days(days),
+            &workflow.completed,
+            spec,
+            page,
+            limit,
+        );
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// List the Tasks by urgency in the DB
+    ///
+    /// Lists the not completed tasks, most urgent first. The urgency is a weighted sum of the task's priority, how
+    /// close it is to being due and its age, see [`Urgency`].
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The datetime to score due dates & ages against
+    /// * `urgency` - The urgency coefficients
+    /// * `workflow` - The workflow used to tell if a task is completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    #[allow(clippy::too_many_arguments)]
+    pub async fn from_urgency_list_db<'c, E>(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        urgency: &Urgency,
+        workflow: &Workflow,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<UrgentTask>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::list_urgency_query(
+            workspace_id,
+            now,
+            urgency,
+            &workflow.completed,
+            spec,
+            page,
+            limit,
+        );
+        sqlx::query_as_with::<_, UrgentTask, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Task ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Task Project ID
+    pub fn project_id(&self) -> &Uuid {
+        &self.project_id
+    }
+
+    /// Get Task Parent ID
+    pub fn parent_id(&self) -> &Option<Uuid> {
+        &self.parent_id
+    }
+
+    /// Get Task Title
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    /// Get Task Body
+    pub fn body(&self) -> &String {
+        &self.body
+    }
+
+    /// Get Task Status
+    pub fn status(&self) -> &String {
+        &self.status
+    }
+
+    /// Get Task Start Datetime
+    pub fn start(&self) -> &Option<DateTime<Utc>> {
+        &self.start
+    }
+
+    /// Get Task Due Datetime
+    pub fn due(&self) -> &Option<DateTime<Utc>> {
+        &self.due
+    }
+
+    /// Get Task Priority
+    pub fn priority(&self) -> &Priority {
+        &self.priority
+    }
+
+    /// Get Task Effort Estimate
+    pub fn effort(&self) -> &Option<f64> {
+        &self.effort
+    }
+
+    /// Get Task Sprint ID
+    pub fn sprint_id(&self) -> &Option<Uuid> {
+        &self.sprint_id
+    }
+
+    /// Get Task Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Get Task Recurrence Rule, an error if the stored rule can not be parsed
+    pub fn recurrence(&self) -> Option<Result<RecurrenceRule, RecurrenceError>> {
+        self.recurrence.as_ref().map(|rule| rule.parse())
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the task.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the task `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Id
+    ///
+    /// Sets the task id
+    /// # Arguments:
+    /// * `id` - The uuid v4 id to use
+    pub fn set_id(&mut self, id: Uuid) -> &mut Self {
+        self.id = id;
+        self
+    }
+
+    /// Set Title
+    ///
+    /// Sets the task title
+    /// # Arguments
+    /// * `title` - The string title to use
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+        self.edited();
+    }
+
+    /// Set Body
+    ///
+    /// Sets the task body
+    /// # Arguments
+    /// * `body` - The string body to use
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+        self.edited();
+    }
+
+    /// Set Status
+    ///
+    /// Sets the task status
+    /// # Arguments
+    /// * `status` - The string status to use
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+        self.edited();
+    }
+
+    /// Set Parent
+    ///
+    /// Sets the parent task, making this task a subtask of it
+    /// # Arguments
+    /// * `parent_id` - The id of the parent task, `None` for a top-level task
+    pub fn set_parent(&mut self, parent_id: Option<Uuid>) {
+        self.parent_id = parent_id;
+        self.edited();
+    }
+
+    /// Set Start
+    ///
+    /// Sets the datetime work on the task should start
+    /// # Arguments
+    /// * `start` - The start datetime, `None` to clear it
+    pub fn set_start(&mut self, start: Option<DateTime<Utc>>) {
+        self.start = start;
+        self.edited();
+    }
+
+    /// Set Due
+    ///
+    /// Sets the datetime the task is due
+    /// # Arguments
+    /// * `due` - The due datetime, `None` to clear it
+    pub fn set_due(&mut self, due: Option<DateTime<Utc>>) {
+        self.due = due;
+        self.edited();
+    }
+
+    /// Set Recurrence
+    ///
+    /// Sets the recurrence rule, completing a recurring task spawns its next occurrence. The series starts at the
+    /// task's due datetime, or its start or created datetime when it has no due datetime. Fails without changing
+    /// the task if the rule is invalid.
+    /// # Arguments
+    /// * `recurrence` - The recurrence rule, e.g. `FREQ=WEEKLY;BYDAY=MO`, `None` for a one-off task
+    pub fn set_recurrence(&mut self, recurrence: Option<&str>) -> Result<(), RecurrenceError> {
+        self.recurrence = recurrence
+            .map(|rule| rule.parse::<RecurrenceRule>())
+            .transpose()?
+            .map(|rule| rule.to_string());
+        self.edited();
+        Ok(())
+    }
+
+    /// Set Priority
+    ///
+    /// Sets the task priority
+    /// # Arguments
+    /// * `priority` - The priority, `P0` is the most urgent
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.edited();
+    }
+
+    /// Set Effort
+    ///
+    /// Sets the estimated effort of the task, in story points or hours, whichever the team estimates in
+    /// # Arguments
+    /// * `effort` - The estimate, `None` to clear it
+    pub fn set_effort(&mut self, effort: Option<f64>) {
+        self.effort = effort;
+        self.edited();
+    }
+
+    /// Next Occurrence
+    ///
+    /// Creates the next occurrence of a recurring task, or `None` if the task doesn't recur or its series is over.
+    /// The occurrence is a copy of the task in the workflow's initial status, with its dates moved to the next
+    /// occurrence of the rule. Fails if the stored rule can not be parsed.
+    /// # Arguments
+    /// * `workflow` - The workflow of the task's project
+    pub fn next_occurrence(&self, workflow: &Workflow) -> Result<Option<Task>, RecurrenceError> {
+        let Some(rule) = self.recurrence().transpose()? else {
+            return Ok(None);
+        };
+        let anchor = self.due.or(self.start).unwrap_or(self.created);
+        let Some(next) = rule.next_after(anchor, anchor) else {
+            return Ok(None);
+        };
+        // the series continues from the occurrence, which uses up one of its count
+        let rule = RecurrenceRule::try_new(
+            rule.frequency(),
+            rule.interval(),
+            rule.by_day().to_vec(),
+            rule.until(),
+            rule.count().map(|count| count - 1),
+        )?;
+
+        let mut task = Task {
+            status: workflow.initial.clone(),
+            start: self.start.map(|start| start + (next - anchor)),
+            due: self.due.map(|_| next),
+            recurrence: Some(rule.to_string()),
+            sprint_id: None,
+            ..self.clone()
+        };
+        task.assign_id().assign_created();
+        Ok(Some(task))
+    }
+
+    /// Transition
+    ///
+    /// Moves the task to another status if the workflow allows it, returning the transition record.
+    /// # Arguments
+    /// * `status` - The status to move to
+    /// * `workflow` - The workflow of the task's project
+    pub fn transition(
+        &mut self,
+        status: String,
+        workflow: &Workflow,
+    ) -> Result<TaskTransition, DbError> {
+        if !workflow.is_state(&status) {
+            return Err(DbError::UnknownStatus(status));
+        }
+
+        if !workflow.can_transition(&self.status, &status) {
+            return Err(DbError::IllegalTransition {
+                from: self.status.clone(),
+                to: status,
+            });
+        }
+
+        let transition = TaskTransition::new(self.id, self.status.clone(), status.clone());
+        self.set_status(status);
+        Ok(transition)
+    }
+
+    // Database Interactions
+
+    /// Transitions a Task on DB
+    ///
+    /// Validates the transition against the workflow, then updates the task & records the transition atomically.
+    /// Completing a recurring task also inserts its [next occurrence](Task::next_occurrence), which is returned
+    /// along with the transition. The occurrence gets the reminders, tags, assignees and an unchecked copy of the
+    /// checklist of the task, the reminders keep their offset to the due datetime. Completing a task whose stored
+    /// recurrence rule can not be parsed fails with [`DbError::Recurrence`].
+    /// # Arguments
+    /// * `status` - The status to move to
+    /// * `workflow` - The workflow of the task's project, see [`ProjectWorkflow::resolve`](crate::db::workflows::ProjectWorkflow::resolve)
+    /// * `author_id` - The id of the user that transitions the task
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn transition_db<'c, A>(
+        &mut self,
+        status: String,
+        workflow: &Workflow,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<(TaskTransition, Option<Task>), DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut task = self.clone();
+        let transition = task.transition(status, workflow)?;
+        let next = match !workflow.is_completed(transition.from_status())
+            && workflow.is_completed(transition.to_status())
+        {
+            true => task
+                .next_occurrence(workflow)
+                .map_err(DbError::Recurrence)?,
+            false => None,
+        };
+
+        let mut tx = conn.begin().await?;
+        task.update_db(author_id, &mut *tx).await?;
+        transition.insert(&mut *tx).await?;
+        if let Some(next) = &next {
+            next.insert(&mut *tx).await?;
+            task.copy_details_db(next.id, &mut *tx).await?;
+        }
+        tx.commit().await?;
+
+        *self = task;
+        Ok((transition, next))
+    }
+
+    /// Plans a Task into a Sprint on DB
+    ///
+    /// The DB rejects sprints of other projects with [`DbError::SprintProjectMismatch`].
+    /// # Arguments
+    /// * `sprint_id` - The id of the sprint, `None` to take the task out of its sprint
+    /// * `author_id` - The id of the user that plans the task
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn set_sprint_db<'c, A>(
+        &mut self,
+        sprint_id: Option<Uuid>,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut task = self.clone();
+        task.sprint_id = sprint_id;
+        task.edited();
+
+        let result = task
+            .update_db(author_id, conn)
+            .await
+            .map_err(|err| match &err {
+                Error::Database(db) if db.message() == SPRINT_PROJECT_MISMATCH => {
+                    DbError::SprintProjectMismatch
+                }
+                _ => DbError::Sqlx(err),
+            })?;
+
+        *self = task;
+        Ok(result)
+    }
+
+    /// Sets the parent of a Task on DB
+    ///
+    /// The DB rejects parents that would make the task its own ancestor with [`DbError::HierarchyCycle`].
+    /// # Arguments
+    /// * `parent_id` - The id of the parent task, `None` for a top-level task
+    /// * `author_id` - The id of the user that moves the task
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn set_parent_db<'c, A>(
+        &mut self,
+        parent_id: Option<Uuid>,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut task = self.clone();
+        task.set_parent(parent_id);
+
+        let result = task
+            .update_db(author_id, conn)
+            .await
+            .map_err(|err| match &err {
+                Error::Database(db) if db.message() == HIERARCHY_CYCLE => DbError::HierarchyCycle,
+                _ => DbError::Sqlx(err),
+            })?;
+
+        *self = task;
+        Ok(result)
+    }
+
+    /// Load a Task subtree from the DB
+    ///
+    /// Fetches a task followed by all of its descendants, ordered by depth.
+    /// # Arguments
+    /// * `id` - The id of the subtree root
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_subtree_db<'c, E>(id: Uuid, executor: E) -> Result<Vec<Task>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::subtree_query(id);
+        sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Rolled-up completion of a Task on DB
+    ///
+    /// Returns the percentage (0 - 100) of the leaf tasks in the subtree that are in a completed state of the
+    /// workflow. A task without subtasks is either 0% or 100% complete.
+    /// # Arguments
+    /// * `id` - The id of the subtree root
+    /// * `workflow` - The workflow of the task's project
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn completion_db<'c, E>(
+        id: Uuid,
+        workflow: &Workflow,
+        executor: E,
+    ) -> Result<f64, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Task::completion_query(id, &workflow.completed);
+        let (total, completed): (i64, i64) = sqlx::query_as_with(&query.0, query.1)
+            .fetch_one(executor)
+            .await?;
+
+        Ok(match total {
+            0 => 0.0,
+            total => completed as f64 * 100.0 / total as f64,
+        })
+    }
+
+    /// Moves a Task subtree to another Project on DB
+    ///
+    /// The task and all of its descendants are moved, the task is detached from its parent and the moved tasks are
+    /// taken out of their sprints. Their custom field values move like the ones of a
+    /// [merge](Project::merge_into_db). The move of every task is recorded in the [`History`]. Returns how many tasks
+    /// were moved.
+    ///
+    /// Moving from or to a deleted project fails with [`DbError::ProjectDeleted`] and to a project of another
+    /// workspace with [`DbError::WorkspaceMismatch`].
+    /// # Arguments
+    /// * `project_id` - The id of the project to move to
+    /// * `author_id` - The id of the user that moves the task
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn move_subtree_db<'c, A>(
+        &mut self,
+        project_id: Uuid,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let from = Project::from_db(self.project_id, &mut *tx).await?;
+        let into = Project::from_db(project_id, &mut *tx).await?;
+        from.check_move(&into)?;
+
+        let mut subtree = Task::from_subtree_db(self.id, &mut *tx).await?;
+        if let Some(root) = subtree.first_mut() {
+            *root = self.clone();
+            root.parent_id = None;
+        }
+        let moved = Task::move_all_db(&mut subtree, project_id, author_id, &mut *tx).await?;
+        CustomField::remap_values_db(self.project_id, project_id, &mut *tx).await?;
+        tx.commit().await?;
+
+        if let Some(root) = subtree.into_iter().next() {
+            *self = root;
+        }
+        Ok(moved)
+    }
+
+    /// Moves every Task of a Project to another Project on DB
+    ///
+    /// The moved tasks are taken out of their sprints, the sprints stay with their project. The move of every task is
+    /// recorded in the [`History`]. Returns how many tasks were moved.
+    /// # Arguments
+    /// * `project_id` - The id of the project to move from
+    /// * `to_project_id` - The id of the project to move to
+    /// * `author_id` - The id of the user that moves the tasks
+    /// * `conn` - The SQLite database connection or transaction
+    pub(crate) async fn move_project_db<'c, A>(
+        project_id: Uuid,
+        to_project_id: Uuid,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Task::select_project_query(project_id);
+        let mut tasks = sqlx::query_as_with::<_, Task, _>(&query.0, query.1)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let moved = Task::move_all_db(&mut tasks, to_project_id, author_id, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(moved)
+    }
+
+    /// Creates the Task on DB
+    ///
+    /// Inserts the task in the initial status of its project's workflow, the project's
+    /// [`ProjectWorkflow`](crate::db::workflows::ProjectWorkflow) if it has one, else `workflow`.
+    /// # Arguments
+    /// * `workflow` - The workflow to fall back to, usually the config.toml one
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn create_db<'c, A>(
+        &mut self,
+        workflow: &Workflow,
+        conn: A,
+    ) -> Result<SqliteQueryResult, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let workflow = ProjectWorkflow::resolve(self.project_id, workflow, &mut *tx).await?;
+        let mut task = self.clone();
+        task.status = workflow.initial;
+
+        let result = task.insert(&mut *tx).await?;
+        *self = task;
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Updates a Task on DB
+    ///
+    /// Records the changed fields in the [`History`].
+    /// # Arguments
+    /// * `author_id` - The id of the user that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn update_db<'c, A>(
+        &self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        self.audit_db(HistoryAction::Update, author_id, conn).await
+    }
+
+    /// Deletes a Task on DB
+    ///
+    /// Deleting a task also deletes its subtasks, the last state of the task and of every subtask is kept in the
+    /// [`History`].
+    /// # Arguments
+    /// * `author_id` - The id of the user that deletes the task
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn delete_db<'c, A>(
+        &self,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        // The deepest subtasks go first, so none of them is left to the `ON DELETE CASCADE`
+        let subtree = Task::from_subtree_db(self.id, &mut *tx).await?;
+        for task in subtree.iter().skip(1).rev() {
+            History::audit_db(HistoryAction::Purge, task, author_id, &mut *tx).await?;
+        }
+        let (result, _) =
+            History::audit_db(HistoryAction::Purge, self, author_id, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Generates a sqlx query to List all Tasks in the DB
+    ///
+    /// Lists all tasks in the DB by providing a page and limit count.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_workspace_query(
+        workspace_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Task::list_select(spec)
+            .and_where(
+                Expr::col(TaskIden::ProjectId).in_subquery(Project::active_subquery(workspace_id)),
+            )
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Tasks of a Project in the DB
+    ///
+    /// Lists all tasks owned by a project by providing a page and limit count.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_project_query(
+        project_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Task::list_select(spec)
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Tasks of a Project with their checklist progress in the DB
+    ///
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_checklist_query(
+        project_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let count = |checked_only: bool| {
+            let mut items = Query::select();
+            items
+                .expr(Func::count(Expr::col((
+                    ChecklistItemIden::Table,
+                    ChecklistItemIden::Id,
+                ))))
+                .from(ChecklistItemIden::Table)
+                .and_where(
+                    Expr::col((ChecklistItemIden::Table, ChecklistItemIden::TaskId))
+                        .equals((TaskIden::Table, TaskIden::Id)),
+                );
+            if checked_only {
+                items.and_where(
+                    Expr::col((ChecklistItemIden::Table, ChecklistItemIden::Checked)).eq(true),
+                );
+            }
+            SimpleExpr::SubQuery(
+                None,
+                Box::new(SubQueryStatement::SelectStatement(items.to_owned())),
+            )
+        };
+
+        Task::list_select(spec)
+            .expr_as(count(true), Alias::new("checked"))
+            .expr_as(count(false), Alias::new("items"))
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks of a Sprint in the DB
+    ///
+    /// Tasks the sorts of the spec don't tell apart are listed oldest first.
+    /// # Arguements
+    /// * `sprint_id` - The id of the sprint
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_sprint_query(
+        sprint_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let mut select = Task::select();
+
+        spec.apply(&mut select, Some(Sort::Asc(TaskIden::Priority)))
+            .and_where(Expr::col(TaskIden::SprintId).eq(sprint_id))
+            .order_by(TaskIden::Created, Order::Asc)
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks of a Custom Field's Project ordered by the field value in the DB
+    ///
+    /// The sorts of the spec order the tasks with the same value, oldest first if it has none. The `custom_value`
+    /// columns don't clash with the task columns, so the spec can leave its columns unqualified.
+    /// # Arguements
+    /// * `field` - The custom field to filter & sort by
+    /// * `filter` - The value tasks must have
+    /// * `order` - The order of the field values
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_field_query(
+        field: &CustomField,
+        filter: Option<FieldValue>,
+        order: Order,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let column = (CustomValueIden::Table, field.kind().column());
+
+        let mut select = Task::joined_select();
+        select.order_by_with_nulls(column, order, NullOrdering::Last);
+
+        spec.apply(&mut select, Some(Sort::Asc(TaskIden::Created)))
+            .left_join(
+                CustomValueIden::Table,
+                Condition::all()
+                    .add(
+                        Expr::col((CustomValueIden::Table, CustomValueIden::FieldId))
+                            .eq(*field.id()),
+                    )
+                    .add(
+                        Expr::col((CustomValueIden::Table, CustomValueIden::TaskId))
+                            .equals((TaskIden::Table, TaskIden::Id)),
+                    ),
+            )
+            .and_where(Expr::col((TaskIden::Table, TaskIden::ProjectId)).eq(*field.project_id()))
+            .and_where_option(filter.map(|value| Expr::col(column).eq(value)))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks assigned to a User in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `user_id` - The id of the assignee
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_assignee_query(
+        workspace_id: Uuid,
+        user_id: Uuid,
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Task::list_select(spec)
+            .and_where(
+                Expr::col(TaskIden::ProjectId).in_subquery(Project::active_subquery(workspace_id)),
+            )
+            .and_where(
+                Expr::col(TaskIden::Id).in_subquery(
+                    Query::select()
+                        .column(TaskAssigneeIden::TaskId)
+                        .from(TaskAssigneeIden::Table)
+                        .and_where(Expr::col(TaskAssigneeIden::UserId).eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks tagged with every one of the Tags in the DB
+    ///
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `tag_ids` - The ids of the tags, a task must have all of them, no tags list every task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_tagged_query(
+        workspace_id: Uuid,
+        tag_ids: &[Uuid],
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        Task::list_select(spec)
+            .and_where(
+                Expr::col(TaskIden::ProjectId).in_subquery(Project::active_subquery(workspace_id)),
+            )
+            .and_where_option(
+                Tag::tagged_subquery(TaskTagIden::Table, TaskTagIden::TaskId, tag_ids)
+                    .map(|tagged| Expr::col(TaskIden::Id).in_subquery(tagged)),
+            )
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks due in a time range in the DB
+    ///
+    /// Lists the tasks whose status is not one of `completed` and are due in `[after, before)`, ordered by due. The
+    /// sorts of the spec order the tasks that are due at the same time.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `after` - The inclusive lower bound, `None` for no lower bound
+    /// * `before` - The exclusive upper bound
+    /// * `completed` - The statuses that count as completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_due_query(
+        workspace_id: Uuid,
+        after: Option<DateTime<Utc>>,
+        before: DateTime<Utc>,
+        completed: &[String],
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let mut select = Task::select();
+        select.order_by(TaskIden::Due, Order::Asc);
+
+        spec.apply(&mut select, None)
+            .and_where(
+                Expr::col(TaskIden::ProjectId).in_subquery(Project::active_subquery(workspace_id)),
+            )
+            .and_where_option(after.map(|after| Expr::col(TaskIden::Due).gte(after)))
+            .and_where(Expr::col(TaskIden::Due).lt(before))
+            .and_where(Expr::col(TaskIden::Status).is_not_in(completed.iter().cloned()))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks by urgency in the DB
+    ///
+    /// Lists the tasks whose status is not one of `completed`, most urgent first. Ties are ordered by the sorts of the
+    /// spec, oldest first if it has none.
+    /// # Arguements
+    /// * `workspace_id` - The id of the workspace to list the tasks of
+    /// * `now` - The datetime to score due dates & ages against
+    /// * `urgency` - The urgency coefficients
+    /// * `completed` - The statuses that count as completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_urgency_query(
+        workspace_id: Uuid,
+        now: DateTime<Utc>,
+        urgency: &Urgency,
+        completed: &[String],
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let mut select = Task::select();
+        select
+            .expr_as(Task::urgency_expr(now, urgency), Alias::new("urgency"))
+            .order_by(Alias::new("urgency"), Order::Desc);
+
+        spec.apply(&mut select, Some(Sort::Asc(TaskIden::Created)))
+            .and_where(
+                Expr::col(TaskIden::ProjectId).in_subquery(Project::active_subquery(workspace_id)),
+            )
+            .and_where(Expr::col(TaskIden::Status).is_not_in(completed.iter().cloned()))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Tasks of a Project in creation order in the DB
+    ///
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    pub fn project_query(project_id: Uuid) -> (String, SqlxValues) {
+        Task::select()
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .order_by(TaskIden::Created, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Tasks of a Project that are ready to start in the DB
+    ///
+    /// A task is ready when its status is not one of `completed` and every task blocking it is completed.
+    /// # Arguements
+    /// * `project_id` - The id of the owning project
+    /// * `completed` - The statuses that count as completed
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_ready_query(
+        project_id: Uuid,
+        completed: &[String],
+        spec: &ListSpec<TaskIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let open_blockers = Query::select()
+            .expr(Expr::val(1))
+            .from(TaskDependencyIden::Table)
+            .join_as(
+                JoinType::InnerJoin,
+                TaskIden::Table,
+                Alias::new("blocker"),
+                Expr::col((Alias::new("blocker"), TaskIden::Id))
+                    .equals((TaskDependencyIden::Table, TaskDependencyIden::BlockerId)),
+            )
+            .and_where(
+                Expr::col((TaskDependencyIden::Table, TaskDependencyIden::BlockedId))
+                    .equals((TaskIden::Table, TaskIden::Id)),
+            )
+            .and_where(
+                Expr::col((Alias::new("blocker"), TaskIden::Status))
+                    .is_not_in(completed.iter().cloned()),
+            )
+            .to_owned();
+
+        Task::list_select(spec)
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .and_where(Expr::col(TaskIden::Status).is_not_in(completed.iter().cloned()))
+            .and_where(Expr::exists(open_blockers).not())
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find a Task subtree on DB
+    ///
+    /// Selects the task followed by all of its descendants, ordered by depth.
+    /// # Arguments
+    /// * `id` - The id of the subtree root
+    pub fn subtree_query(id: Uuid) -> (String, SqlxValues) {
+        Task::joined_select()
+            .inner_join(
+                Alias::new("subtree"),
+                Expr::col((TaskIden::Table, TaskIden::Id))
+                    .equals((Alias::new("subtree"), TaskIden::Id)),
+            )
+            .order_by((Alias::new("subtree"), Alias::new("depth")), Order::Asc)
+            .to_owned()
+            .with(Task::subtree_cte(id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Count the leaf Tasks of a subtree on DB
+    ///
+    /// Selects the count of leaf tasks in the subtree and how many of them have one of the `completed` statuses.
+    /// # Arguments
+    /// * `id` - The id of the subtree root
+    /// * `completed` - The statuses that count as completed
+    pub fn completion_query(id: Uuid, completed: &[String]) -> (String, SqlxValues) {
+        Query::select()
+            .expr(Func::count(Expr::col((TaskIden::Table, TaskIden::Id))))
+            .expr(Func::coalesce([
+                Func::sum(
+                    Expr::col((TaskIden::Table, TaskIden::Status)).is_in(completed.iter().cloned()),
+                )
+                .into(),
+                Expr::val(0).into(),
+            ]))
+            .from(TaskIden::Table)
+            .inner_join(
+                Alias::new("subtree"),
+                Expr::col((TaskIden::Table, TaskIden::Id))
+                    .equals((Alias::new("subtree"), TaskIden::Id)),
+            )
+            .and_where(
+                Expr::exists(
+                    Query::select()
+                        .expr(Expr::val(1))
+                        .from_as(TaskIden::Table, Alias::new("child"))
+                        .and_where(
+                            Expr::col((Alias::new("child"), TaskIden::ParentId))
+                                .equals((TaskIden::Table, TaskIden::Id)),
+                        )
+                        .to_owned(),
+                )
+                .not(),
+            )
+            .to_owned()
+            .with(Task::subtree_cte(id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Find every Task of a Project on DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the owning project
+    pub fn select_project_query(project_id: Uuid) -> (String, SqlxValues) {
+        Task::select()
+            .and_where(Expr::col(TaskIden::ProjectId).eq(project_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Subtree CTE
+    ///
+    /// A recursive `subtree (id, depth)` table holding the task and all of its descendants.
+    fn subtree_cte(id: Uuid) -> WithClause {
+        let descendants = Query::select()
+            .column((TaskIden::Table, TaskIden::Id))
+            .expr(Expr::col((Alias::new("subtree"), Alias::new("depth"))).add(1))
+            .from(TaskIden::Table)
+            .inner_join(
+                Alias::new("subtree"),
+                Expr::col((TaskIden::Table, TaskIden::ParentId))
+                    .equals((Alias::new("subtree"), TaskIden::Id)),
+            )
+            .to_owned();
+
+        let subtree = Query::select()
+            .column(TaskIden::Id)
+            .expr(Expr::val(0))
+            .from(TaskIden::Table)
+            .and_where(Expr::col(TaskIden::Id).eq(id))
+            .union(UnionType::All, descendants)
+            .to_owned();
+
+        WithClause::new()
+            .recursive(true)
+            .cte(
+                CommonTableExpression::new()
+                    .query(subtree)
+                    .columns([Alias::new("id"), Alias::new("depth")])
+                    .table_name(Alias::new("subtree"))
+                    .to_owned(),
+            )
+            .to_owned()
+    }
+
+    /// The urgency score of a task as an SQL expression
+    ///
+    /// Mirrors Taskwarrior's urgency: the priority term goes from 1 for `P0` to 0 for `P4`, the due term from 0.2
+    /// for tasks due in two weeks or later to 1 for tasks a week overdue and the age term grows linearly to 1 at
+    /// [`Urgency::age_max_days`]. Each term is multiplied by its coefficient.
+    fn urgency_expr(now: DateTime<Utc>, urgency: &Urgency) -> SimpleExpr {
+        let now = now.timestamp();
+        let overdue = "(? - CAST(strftime('%s', \"task\".\"due\") AS INTEGER)) / 86400.0";
+        let age = "(? - CAST(strftime('%s', \"task\".\"created\") AS INTEGER)) / 86400.0";
+
+        Expr::cust_with_values(
+            format!(
+                "? * (4 - \"task\".\"priority\") / 4.0 \
+                 + ? * CASE WHEN \"task\".\"due\" IS NULL THEN 0.0 \
+                 WHEN {overdue} >= 7 THEN 1.0 \
+                 WHEN {overdue} >= -14 THEN ({overdue} + 14) * 0.8 / 21 + 0.2 \
+                 ELSE 0.2 END \
+                 + ? * MIN({age} / ?, 1.0)"
+            ),
+            [
+                Value::from(urgency.priority),
+                Value::from(urgency.due),
+                Value::from(now),
+                Value::from(now),
+                Value::from(now),
+                Value::from(urgency.age),
+                Value::from(now),
+                Value::from(urgency.age_max_days),
+            ],
+        )
+    }
+
+    /// Set Edited
+    ///
+    /// Sets the task edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+
+    /// Copies the reminders, tags, assignees & checklist of the Task to another Task on DB
+    ///
+    /// The checklist items are copied unchecked, in checklist order.
+    /// # Arguments
+    /// * `task_id` - The id of the task to copy to
+    /// * `conn` - The SQLite database connection or transaction
+    async fn copy_details_db<'c, A>(&self, task_id: Uuid, conn: A) -> Result<(), Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        for reminder in Reminder::from_task_list_db(self.id, &mut *tx).await? {
+            Reminder::new(task_id, reminder.before())
+                .insert(&mut *tx)
+                .await?;
+        }
+        for tag in Tag::from_task_list_db(self.id, &mut *tx).await? {
+            tag.attach_task(task_id, &mut *tx).await?;
+        }
+        for user in User::from_task_list_db(self.id, &mut *tx).await? {
+            user.assign_task(task_id, &mut *tx).await?;
+        }
+        for item in ChecklistItem::from_task_list_db(self.id, &mut *tx).await? {
+            let mut copy = ChecklistItem::new(task_id, item.text().clone());
+            copy.assign_id().assign_created();
+            copy.append_db(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Moves Tasks to another Project on DB
+    ///
+    /// Takes the tasks out of their sprints and records the move of every task in the [`History`], the tasks are
+    /// updated with the moved state. Returns how many tasks were moved.
+    /// # Arguments
+    /// * `tasks` - The tasks to move
+    /// * `project_id` - The id of the project to move to
+    /// * `author_id` - The id of the user that moves the tasks
+    /// * `conn` - The SQLite database connection or transaction
+    async fn move_all_db<'c, A>(
+        tasks: &mut [Task],
+        project_id: Uuid,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<u64, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let modified = Utc::now();
+        for task in tasks.iter_mut() {
+            task.project_id = project_id;
+            task.sprint_id = None;
+            task.modified = modified;
+            task.update_db(author_id, &mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(tasks.len() as u64)
+    }
+
+    /// Writes the Task to DB and records the change in the [`History`]
+    ///
+    /// # Arguments
+    /// * `action` - The kind of change
+    /// * `author_id` - The id of the user that made the change
+    /// * `conn` - The SQLite database connection or transaction
+    async fn audit_db<'c, A>(
+        &self,
+        action: HistoryAction,
+        author_id: Uuid,
+        conn: A,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let (result, _) = History::audit_db(action, self, author_id, &mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+}
+
+impl Entity for Task {
+    type Iden = TaskIden;
+    type Key = Uuid;
+
+    const TABLE: TaskIden = TaskIden::Table;
+    const KEY: &'static [TaskIden] = &[TaskIden::Id];
+    const CREATED: Option<TaskIden> = Some(TaskIden::Created);
+    const COLUMNS: &'static [TaskIden] = &[
+        TaskIden::Id,
+        TaskIden::ProjectId,
+        TaskIden::ParentId,
+        TaskIden::Title,
+        TaskIden::Body,
+        TaskIden::Status,
+        TaskIden::Start,
+        TaskIden::Due,
+        TaskIden::Recurrence,
+        TaskIden::Priority,
+        TaskIden::Effort,
+        TaskIden::SprintId,
+        TaskIden::AuthorId,
+        TaskIden::Created,
+        TaskIden::Modified,
+    ];
+    const ORDER_BY: Option<TaskIden> = Some(TaskIden::Created);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.project_id.into(),
+            self.parent_id.into(),
+            self.title.clone().into(),
+            self.body.clone().into(),
+            self.status.clone().into(),
+            self.start.into(),
+            self.due.into(),
+            self.recurrence.clone().into(),
+            (self.priority as i64).into(),
+            self.effort.into(),
+            self.sprint_id.into(),
+            self.author_id.into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// The plain update & delete skip the [`History`], edit tasks with [`Task::update_db`] & [`Task::delete_db`]
+impl Repository for Task {}
+
+impl Audited for Task {
+    const ENTITY: &'static str = "task";
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn set_audit_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    fn audit_created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    fn audit_fields(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("project_id", Some(self.project_id.to_string())),
+            ("parent_id", self.parent_id.map(|id| id.to_string())),
+            ("title", Some(self.title.clone())),
+            ("body", Some(self.body.clone())),
+            ("status", Some(self.status.clone())),
+            ("start", self.start.map(|date| date.to_rfc3339())),
+            ("due", self.due.map(|date| date.to_rfc3339())),
+            ("recurrence", self.recurrence.clone()),
+            ("priority", Some(self.priority.to_string())),
+            ("effort", self.effort.map(|effort| effort.to_string())),
+            ("sprint_id", self.sprint_id.map(|id| id.to_string())),
+            ("author_id", Some(self.author_id.to_string())),
+            ("created", Some(self.created.to_rfc3339())),
+            ("modified", Some(self.modified.to_rfc3339())),
+        ]
+    }
+
+    fn restore_field(&mut self, field: &str, value: Option<&str>) -> bool {
+        let date = match value.map(DateTime::parse_from_rfc3339) {
+            Some(Ok(date)) => Some(date.with_timezone(&Utc)),
+            _ => None,
+        };
+        let id = value.and_then(|value| Uuid::parse_str(value).ok());
+        let priority = [
+            Priority::P0,
+            Priority::P1,
+            Priority::P2,
+            Priority::P3,
+            Priority::P4,
+        ]
+        .into_iter()
+        .find(|priority| value == Some(priority.to_string().as_str()));
+        let effort = value.and_then(|value| value.parse().ok());
+
+        match (field, value, date) {
+            ("title", Some(value), _) => self.title = value.into(),
+            ("body", Some(value), _) => self.body = value.into(),
+            ("status", Some(value), _) => self.status = value.into(),
+            ("recurrence", value, _) => self.recurrence = value.map(Into::into),
+            ("project_id", _, _) if id.is_some() => self.project_id = id.unwrap(),
+            ("author_id", _, _) if id.is_some() => self.author_id = id.unwrap(),
+            ("parent_id", None, _) => self.parent_id = None,
+            ("parent_id", _, _) if id.is_some() => self.parent_id = id,
+            ("sprint_id", None, _) => self.sprint_id = None,
+            ("sprint_id", _, _) if id.is_some() => self.sprint_id = id,
+            ("priority", _, _) if priority.is_some() => self.priority = priority.unwrap(),
+            ("effort", None, _) => self.effort = None,
+            ("effort", _, _) if effort.is_some() => self.effort = effort,
+            ("start", None, _) => self.start = None,
+            ("start", _, Some(date)) => self.start = Some(date),
+            ("due", None, _) => self.due = None,
+            ("due", _, Some(date)) => self.due = Some(date),
+            ("created", _, Some(date)) => self.created = date,
+            ("modified", _, Some(date)) => self.modified = date,
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Priority, Task, TaskIden};
+    use crate::config::{init_memory_db, Urgency, Workflow};
+    use crate::db::checklists::ChecklistItem;
+    use crate::db::custom_fields::{CustomField, FieldKind, FieldValue};
+    use crate::db::dependencies::TaskDependency;
+    use crate::db::filter::{Filter, ListSpec, Sort};
+    use crate::db::history::{Audited, History, HistoryAction};
+    use crate::db::projects::Project;
+    use crate::db::reminders::Reminder;
+    use crate::db::sprints::Sprint;
+    use crate::db::tags::Tag;
+    use crate::db::transitions::TaskTransition;
+    use crate::db::users::{create_test_user, User};
+    use crate::db::workflows::ProjectWorkflow;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use crate::recurrence::{RecurrenceError, RecurrenceRule};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use sqlx::{Error, SqliteConnection};
+    use uuid::Uuid;
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "PROJECT_NAME".into(),
+            "PROJECT_DESCRIPTION".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    fn create_task(project: &Project) -> Task {
+        let mut task = Task::new(
+            *project.id(),
+            "TASK_TITLE".into(),
+            "TASK_BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task
+    }
+
+    #[test]
+    fn insert() {
+        let query = create_task(&Project::default()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"task\" (\"id\", \"project_id\", \"parent_id\", \"title\", \"body\", \"status\", \"start\", \"due\", \"recurrence\", \"priority\", \"effort\", \"sprint_id\", \"author_id\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn update() {
+        let query = create_task(&Project::default()).update_query();
+
+        assert_eq!(query.0, "UPDATE \"task\" SET \"project_id\" = ?, \"parent_id\" = ?, \"title\" = ?, \"body\" = ?, \"status\" = ?, \"start\" = ?, \"due\" = ?, \"recurrence\" = ?, \"priority\" = ?, \"effort\" = ?, \"sprint_id\" = ?, \"author_id\" = ?, \"modified\" = ? WHERE \"id\" = ?")
+    }
+
+    #[test]
+    fn delete() {
+        let query = create_task(&Project::default()).delete_query();
+
+        assert_eq!(query.0, "DELETE FROM \"task\" WHERE \"id\" = ?");
+    }
+
+    #[test]
+    fn query() {
+        let query = Task::select_query(Uuid::default());
+
+        assert_eq!(query.0, "SELECT \"id\", \"project_id\", \"parent_id\", \"title\", \"body\", \"status\", \"start\", \"due\", \"recurrence\", \"priority\", \"effort\", \"sprint_id\", \"author_id\", \"created\", \"modified\" FROM \"task\" WHERE \"id\" = ? LIMIT ?")
+    }
+
+    #[test]
+    fn list_project() {
+        let spec = ListSpec::new()
+            .filter(Filter::equals(TaskIden::Status, "todo"))
+            .sort(Sort::Desc(TaskIden::Priority));
+        let query = Task::list_project_query(Uuid::default(), &spec, 0, 10);
+
+        assert_eq!(query.0, "SELECT \"id\", \"project_id\", \"parent_id\", \"title\", \"body\", \"status\", \"start\", \"due\", \"recurrence\", \"priority\", \"effort\", \"sprint_id\", \"author_id\", \"created\", \"modified\" FROM \"task\" WHERE \"status\" = ? AND \"project_id\" = ? ORDER BY \"priority\" DESC LIMIT ? OFFSET ?")
+    }
+
+    #[tokio::test]
+    async fn insert_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+
+        let query = create_task(&project).insert(&mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+    }
+
+    #[tokio::test]
+    async fn insert_db_without_project() {
+        let mut conn = init_memory_db().await.unwrap();
+
+        let query = create_task(&Project::default()).insert(&mut conn).await;
+        assert!(query.is_err());
+    }
+
+    #[tokio::test]
+    async fn update_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+
+        let query = task.update_db(task.author_id, &mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 0);
+
+        task.insert(&mut conn).await.unwrap();
+        task.set_status("done".into());
+
+        let query = task.update_db(task.author_id, &mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+        assert_eq!(Task::from_db(task.id, &mut conn).await.unwrap(), task);
+    }
+
+    #[tokio::test]
+    async fn delete_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project);
+
+        let query = task.delete_db(task.author_id, &mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 0);
+
+        task.insert(&mut conn).await.unwrap();
+
+        let query = task.delete_db(task.author_id, &mut conn).await.unwrap();
+        assert_eq!(query.rows_affected(), 1);
+    }
+
+    #[tokio::test]
+    async fn history_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let admin = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+        let created = task.clone();
+
+        task.set_title("RENAMED".into());
+        task.set_priority(Priority::P0);
+        task.set_effort(Some(2.5));
+        task.update_db(editor, &mut conn).await.unwrap();
+        let renamed = task.clone();
+        task.set_due(Some(Utc::now()));
+        task.update_db(admin, &mut conn).await.unwrap();
+        let planned = task.clone();
+        task.delete_db(admin, &mut conn).await.unwrap();
+
+        let timeline = History::from_entity_list_db(Task::ENTITY, task.id, &mut conn)
+            .await
+            .unwrap();
+        let changes: Vec<_> = timeline
+            .iter()
+            .map(|entry| (entry.action(), entry.field().as_str(), *entry.author_id()))
+            .collect();
+        assert_eq!(
+            changes[..6],
+            [
+                (HistoryAction::Update, "title", editor),
+                (HistoryAction::Update, "priority", editor),
+                (HistoryAction::Update, "effort", editor),
+                (HistoryAction::Update, "modified", editor),
+                (HistoryAction::Update, "due", admin),
+                (HistoryAction::Update, "modified", admin),
+            ]
+        );
+        assert_eq!(timeline[0].old_value().as_deref(), Some("TASK_TITLE"));
+        assert_eq!(timeline[1].new_value().as_deref(), Some("P0"));
+        assert!(timeline[6..]
+            .iter()
+            .all(|entry| entry.action() == HistoryAction::Purge && entry.new_value().is_none()));
+
+        let deleted = *timeline[6].changed();
+        for (at, state) in [
+            (created.created - Duration::seconds(1), None),
+            (created.created, Some(created)),
+            (*timeline[0].changed(), Some(renamed)),
+            (*timeline[4].changed(), Some(planned)),
+            (deleted, None),
+        ] {
+            // the delete may be recorded within the same instant as the last update
+            let state = state.filter(|_| at < deleted);
+            let rewound = Task::from_history_db(task.id, at, &mut conn).await.unwrap();
+            assert_eq!(rewound, state);
+        }
+    }
+
+    #[tokio::test]
+    async fn query_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project);
+
+        let query = Task::from_db(task.id, &mut conn).await;
+        assert_eq!(
+            query.unwrap_err().to_string(),
+            Error::RowNotFound.to_string()
+        );
+
+        task.insert(&mut conn).await.unwrap();
+
+        let query = Task::from_db(task.id, &mut conn).await.unwrap();
+        assert_eq!(task, query);
+    }
+
+    #[tokio::test]
+    async fn list_project_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+
+        create_task(&project).insert(&mut conn).await.unwrap();
+        create_task(&project).insert(&mut conn).await.unwrap();
+        create_task(&other).insert(&mut conn).await.unwrap();
+
+        let tasks = Task::from_project_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().all(|task| task.project_id == *project.id()));
+
+        let tasks = Task::from_workspace_list_db(
+            *project.workspace_id(),
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(tasks.len(), 2);
+        let tasks =
+            Task::from_workspace_list_db(*other.workspace_id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(tasks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn project_delete_cascades() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+
+        project.purge(editor, &mut conn).await.unwrap();
+
+        let query = Task::from_db(task.id, &mut conn).await;
+        assert_eq!(
+            query.unwrap_err().to_string(),
+            Error::RowNotFound.to_string()
+        );
+    }
+
+    #[test]
+    fn transition() {
+        let workflow = Workflow::default();
+        let mut task = create_task(&Project::default());
+
+        let transition = task.transition("in-progress".into(), &workflow).unwrap();
+        assert_eq!(transition.from_status(), "todo");
+        assert_eq!(transition.to_status(), "in-progress");
+        assert_eq!(task.status(), "in-progress");
+
+        assert!(matches!(
+            task.transition("done".into(), &workflow),
+            Err(DbError::IllegalTransition { .. })
+        ));
+        assert!(matches!(
+            task.transition("shipped".into(), &workflow),
+            Err(DbError::UnknownStatus(_))
+        ));
+        assert_eq!(task.status(), "in-progress");
+    }
+
+    #[tokio::test]
+    async fn create_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow {
+            initial: "blocked".into(),
+            ..Default::default()
+        };
+        let project = create_project(&mut conn).await;
+
+        let mut task = create_task(&project);
+        task.create_db(&workflow, &mut conn).await.unwrap();
+        assert_eq!(task.status(), "blocked");
+        assert_eq!(Task::from_db(task.id, &mut conn).await.unwrap(), task);
+
+        // the project's own workflow wins over the default one
+        let mut custom = Workflow::default();
+        custom.states.push("triage".into());
+        custom.initial = "triage".into();
+        ProjectWorkflow::new(*project.id(), &custom)
+            .insert(&mut conn)
+            .await
+            .unwrap();
+        let mut task = create_task(&project);
+        task.create_db(&workflow, &mut conn).await.unwrap();
+        assert_eq!(task.status(), "triage");
+    }
+
+    #[tokio::test]
+    async fn transition_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+
+        for status in ["in-progress", "review", "done"] {
+            task.transition_db(status.into(), &workflow, task.author_id, &mut conn)
+                .await
+                .unwrap();
+        }
+        assert!(task
+            .transition_db("review".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .is_err());
+
+        assert_eq!(
+            Task::from_db(task.id, &mut conn).await.unwrap().status(),
+            "done"
+        );
+
+        let transitions = TaskTransition::from_task_list_db(task.id, &mut conn)
+            .await
+            .unwrap();
+        let statuses: Vec<_> = transitions
+            .iter()
+            .map(|t| (t.from_status().as_str(), t.to_status().as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("todo", "in-progress"),
+                ("in-progress", "review"),
+                ("review", "done")
+            ]
+        );
+    }
+
+    #[test]
+    fn subtree() {
+        let query = Task::subtree_query(Uuid::default());
+
+        assert_eq!(query.0, "WITH RECURSIVE \"subtree\" (\"id\", \"depth\") AS (SELECT \"id\", ? FROM \"task\" WHERE \"id\" = ? UNION ALL SELECT \"task\".\"id\", \"subtree\".\"depth\" + ? FROM \"task\" INNER JOIN \"subtree\" ON \"task\".\"parent_id\" = \"subtree\".\"id\") SELECT \"task\".\"id\", \"task\".\"project_id\", \"task\".\"parent_id\", \"task\".\"title\", \"task\".\"body\", \"task\".\"status\", \"task\".\"start\", \"task\".\"due\", \"task\".\"recurrence\", \"task\".\"priority\", \"task\".\"effort\", \"task\".\"sprint_id\", \"task\".\"author_id\", \"task\".\"created\", \"task\".\"modified\" FROM \"task\" INNER JOIN \"subtree\" ON \"task\".\"id\" = \"subtree\".\"id\" ORDER BY \"subtree\".\"depth\" ASC")
+    }
+
+    async fn create_subtask(parent: &Task, conn: &mut SqliteConnection) -> Task {
+        let mut task = Task::new(
+            parent.project_id,
+            "TASK_TITLE".into(),
+            "TASK_BODY".into(),
+            parent.author_id,
+        );
+        task.assign_id().assign_created();
+        task.set_parent(Some(parent.id));
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    #[tokio::test]
+    async fn subtree_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let epic = create_task(&project);
+        epic.insert(&mut conn).await.unwrap();
+        let story = create_subtask(&epic, &mut conn).await;
+        let subtask = create_subtask(&story, &mut conn).await;
+        create_task(&project).insert(&mut conn).await.unwrap();
+
+        let subtree = Task::from_subtree_db(epic.id, &mut conn).await.unwrap();
+        assert_eq!(subtree, [epic.clone(), story.clone(), subtask.clone()]);
+
+        let subtree = Task::from_subtree_db(story.id, &mut conn).await.unwrap();
+        assert_eq!(subtree, [story, subtask]);
+    }
+
+    #[tokio::test]
+    async fn hierarchy_cycle_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut epic = create_task(&project);
+        epic.insert(&mut conn).await.unwrap();
+        let story = create_subtask(&epic, &mut conn).await;
+        let subtask = create_subtask(&story, &mut conn).await;
+
+        assert!(matches!(
+            epic.set_parent_db(Some(subtask.id), epic.author_id, &mut conn)
+                .await,
+            Err(DbError::HierarchyCycle)
+        ));
+        assert!(matches!(
+            epic.set_parent_db(Some(epic.id), epic.author_id, &mut conn)
+                .await,
+            Err(DbError::HierarchyCycle)
+        ));
+        assert_eq!(epic.parent_id(), &None);
+
+        let mut task = create_task(&project);
+        task.set_parent(Some(task.id));
+        assert!(task.insert(&mut conn).await.is_err());
+
+        let mut subtask = subtask;
+        subtask
+            .set_parent_db(Some(epic.id), subtask.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            Task::from_db(subtask.id, &mut conn)
+                .await
+                .unwrap()
+                .parent_id(),
+            &Some(epic.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn completion_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let epic = create_task(&project);
+        epic.insert(&mut conn).await.unwrap();
+
+        assert_eq!(
+            Task::completion_db(epic.id, &workflow, &mut conn)
+                .await
+                .unwrap(),
+            0.0
+        );
+
+        let story = create_subtask(&epic, &mut conn).await;
+        let mut done = create_subtask(&story, &mut conn).await;
+        create_subtask(&story, &mut conn).await;
+        create_subtask(&epic, &mut conn).await;
+        done.set_status("done".into());
+        done.update_db(done.author_id, &mut conn).await.unwrap();
+
+        assert_eq!(
+            Task::completion_db(story.id, &workflow, &mut conn)
+                .await
+                .unwrap(),
+            50.0
+        );
+        assert!(
+            (Task::completion_db(epic.id, &workflow, &mut conn)
+                .await
+                .unwrap()
+                - 100.0 / 3.0)
+                .abs()
+                < f64::EPSILON
+        );
+        assert_eq!(
+            Task::completion_db(done.id, &workflow, &mut conn)
+                .await
+                .unwrap(),
+            100.0
+        );
+    }
+
+    async fn create_sibling_project(project: &Project, conn: &mut SqliteConnection) -> Project {
+        let mut other = Project::new(
+            *project.workspace_id(),
+            "PROJECT_NAME".into(),
+            "PROJECT_DESCRIPTION".into(),
+            *project.author_id(),
+        );
+        other.assign_id().assign_created();
+        other.insert_db(conn).await.unwrap();
+        other
+    }
+
+    #[tokio::test]
+    async fn move_subtree_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let other = create_sibling_project(&project, &mut conn).await;
+        let epic = create_task(&project);
+        epic.insert(&mut conn).await.unwrap();
+        let mut story = create_subtask(&epic, &mut conn).await;
+        let subtask = create_subtask(&story, &mut conn).await;
+
+        story
+            .move_subtree_db(*other.id(), story.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(story.parent_id(), &None);
+
+        let moved = Task::from_subtree_db(story.id, &mut conn).await.unwrap();
+        assert_eq!(moved.len(), 2);
+        assert!(moved.iter().all(|task| task.project_id == *other.id()));
+        assert_eq!(moved[1].id, subtask.id);
+        assert!(moved[1].modified > subtask.modified);
+
+        let tasks = Task::from_project_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks, [epic]);
+    }
+
+    #[tokio::test]
+    async fn move_subtree_rejected_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let foreign = create_project(&mut conn).await;
+        let mut trashed = create_sibling_project(&project, &mut conn).await;
+        trashed
+            .delete_db(*project.author_id(), &mut conn)
+            .await
+            .unwrap();
+        let mut task = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+        let subtask = create_subtask(&task, &mut conn).await;
+
+        let stored = task.clone();
+        assert!(matches!(
+            task.move_subtree_db(*foreign.id(), task.author_id, &mut conn)
+                .await,
+            Err(DbError::WorkspaceMismatch)
+        ));
+        assert!(matches!(
+            task.move_subtree_db(*trashed.id(), task.author_id, &mut conn)
+                .await,
+            Err(DbError::ProjectDeleted)
+        ));
+        assert_eq!(task, stored);
+        assert_eq!(
+            Task::from_subtree_db(task.id, &mut conn).await.unwrap(),
+            [stored, subtask]
+        );
+    }
+
+    #[tokio::test]
+    async fn move_subtree_custom_values_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let other = create_sibling_project(&project, &mut conn).await;
+        let mut story = create_task(&project);
+        story.insert(&mut conn).await.unwrap();
+        let subtask = create_subtask(&story, &mut conn).await;
+        let sibling = create_task(&project);
+        sibling.insert(&mut conn).await.unwrap();
+
+        // `customer` has a match in the other project, `estimate` only a field of another kind
+        let mut fields = Vec::new();
+        for (project_id, name, kind) in [
+            (*project.id(), "customer", FieldKind::Text),
+            (*project.id(), "estimate", FieldKind::Number),
+            (*other.id(), "customer", FieldKind::Text),
+            (*other.id(), "estimate", FieldKind::Text),
+        ] {
+            let mut field = CustomField::new(project_id, name.into(), kind);
+            field.assign_id().assign_created();
+            field.insert(&mut conn).await.unwrap();
+            fields.push(field);
+        }
+        let customer = FieldValue::Text("ACME".into());
+        for task in [&subtask, &sibling] {
+            fields[0]
+                .set_value_db(task, customer.clone(), &mut conn)
+                .await
+                .unwrap();
+            fields[1]
+                .set_value_db(task, FieldValue::Number(3.0), &mut conn)
+                .await
+                .unwrap();
+        }
+
+        story
+            .move_subtree_db(*other.id(), story.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            fields[2].value_db(subtask.id, &mut conn).await.unwrap(),
+            Some(customer.clone())
+        );
+        assert_eq!(
+            fields[3].value_db(subtask.id, &mut conn).await.unwrap(),
+            None
+        );
+        for field in &fields[..2] {
+            assert_eq!(field.value_db(subtask.id, &mut conn).await.unwrap(), None);
+        }
+
+        // the tasks left behind keep their values
+        assert_eq!(
+            fields[0].value_db(sibling.id, &mut conn).await.unwrap(),
+            Some(customer)
+        );
+        assert_eq!(
+            fields[1].value_db(sibling.id, &mut conn).await.unwrap(),
+            Some(FieldValue::Number(3.0))
+        );
+    }
+
+    #[tokio::test]
+    async fn moves_history_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let author = *project.author_id();
+        let mut other = create_sibling_project(&project, &mut conn).await;
+        let mut sprints = Vec::new();
+        for weeks in [0, 2] {
+            let start = Utc::now() + Duration::weeks(weeks);
+            let mut sprint = Sprint::new(
+                *project.id(),
+                "SPRINT".into(),
+                "GOAL".into(),
+                start,
+                start + Duration::weeks(2),
+            );
+            sprint.assign_id().assign_created();
+            sprint.insert(&mut conn).await.unwrap();
+            sprints.push(sprint);
+        }
+        let kept = create_task(&project);
+        kept.insert(&mut conn).await.unwrap();
+        let mut task = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+        let subtask = create_subtask(&task, &mut conn).await;
+        task.set_sprint_db(Some(*sprints[0].id()), author, &mut conn)
+            .await
+            .unwrap();
+        let planned = (Utc::now(), task.clone());
+
+        sprints[0]
+            .carry_over_db(&sprints[1], &workflow, author, &mut conn)
+            .await
+            .unwrap();
+        let carried = (Utc::now(), Task::from_db(task.id, &mut conn).await.unwrap());
+
+        task.move_subtree_db(*other.id(), author, &mut conn)
+            .await
+            .unwrap();
+        let moved = (Utc::now(), task.clone());
+
+        other
+            .merge_into_db(&project, author, &mut conn)
+            .await
+            .unwrap();
+        let merged = (Utc::now(), Task::from_db(task.id, &mut conn).await.unwrap());
+
+        // the subtask is deleted with its parent, the tasks left in the project with the project
+        merged.1.delete_db(author, &mut conn).await.unwrap();
+        project.purge(author, &mut conn).await.unwrap();
+
+        for (at, state) in [&planned, &carried, &moved, &merged] {
+            let rewound = Task::from_history_db(task.id, *at, &mut conn)
+                .await
+                .unwrap();
+            assert_eq!(rewound.as_ref(), Some(state));
+        }
+        assert_eq!(carried.1.sprint_id, Some(*sprints[1].id()));
+        assert_eq!(moved.1.project_id, *other.id());
+        assert_eq!(merged.1.project_id, *project.id());
+
+        let rewound = Task::from_history_db(subtask.id, planned.0, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rewound, Some(subtask.clone()));
+        for (at, project_id) in [(moved.0, other.id()), (merged.0, project.id())] {
+            let rewound = Task::from_history_db(subtask.id, at, &mut conn)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(rewound.project_id, *project_id);
+        }
+        let rewound = Task::from_history_db(kept.id, merged.0, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rewound, Some(kept.clone()));
+
+        for id in [task.id, subtask.id, kept.id] {
+            let rewound = Task::from_history_db(id, Utc::now(), &mut conn)
+                .await
+                .unwrap();
+            assert_eq!(rewound, None);
+        }
+    }
+
+    async fn block(blocker: &Task, blocked: &Task, conn: &mut SqliteConnection) {
+        TaskDependency::new(blocker.id, blocked.id)
+            .insert(conn)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn ready_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+
+        let mut design = create_task(&project);
+        design.insert(&mut conn).await.unwrap();
+        let build = create_task(&project);
+        build.insert(&mut conn).await.unwrap();
+        let mut approval = create_task(&other);
+        approval.insert(&mut conn).await.unwrap();
+        block(&design, &build, &mut conn).await;
+        block(&approval, &build, &mut conn).await;
+
+        let ready =
+            Task::from_ready_list_db(*project.id(), &workflow, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(ready, [design.clone()]);
+
+        design.set_status("done".into());
+        design.update_db(design.author_id, &mut conn).await.unwrap();
+        let ready =
+            Task::from_ready_list_db(*project.id(), &workflow, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert!(ready.is_empty());
+
+        approval.set_status("done".into());
+        approval
+            .update_db(approval.author_id, &mut conn)
+            .await
+            .unwrap();
+        let ready =
+            Task::from_ready_list_db(*project.id(), &workflow, &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap();
+        assert_eq!(ready, [build]);
+        let spec = ListSpec::new().filter(Filter::equals(TaskIden::Status, "in-progress"));
+        let ready = Task::from_ready_list_db(*project.id(), &workflow, &spec, 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert!(ready.is_empty());
+    }
+
+    #[tokio::test]
+    async fn topological_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+
+        let mut tasks = vec![];
+        for _ in 0..4 {
+            let task = create_task(&project);
+            task.insert(&mut conn).await.unwrap();
+            tasks.push(task);
+        }
+        // 3 -> 1 -> 0, 2 is independent
+        block(&tasks[3], &tasks[1], &mut conn).await;
+        block(&tasks[1], &tasks[0], &mut conn).await;
+
+        let order: Vec<_> = Task::from_topological_db(*project.id(), &mut conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(order, [tasks[2].id, tasks[3].id, tasks[1].id, tasks[0].id]);
+    }
+
+    #[tokio::test]
+    async fn due_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        // Wednesday
+        let now = Utc.with_ymd_and_hms(2024, 6, 19, 12, 0, 0).unwrap();
+
+        let due = |offset: Duration| {
+            let mut task = create_task(&project);
+            task.set_due(Some(now + offset));
+            task
+        };
+        let overdue = due(-Duration::days(2));
+        let earlier_today = due(-Duration::hours(2));
+        let later_today = due(Duration::hours(2));
+        let sunday = due(Duration::days(4));
+        let next_week = due(Duration::days(6));
+        let mut done = due(-Duration::days(1));
+        done.set_status("done".into());
+        let undated = create_task(&project);
+        for task in [
+            &overdue,
+            &earlier_today,
+            &later_today,
+            &sunday,
+            &next_week,
+            &done,
+            &undated,
+        ] {
+            task.insert(&mut conn).await.unwrap();
+        }
+
+        let ids = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.id).collect::<Vec<_>>();
+
+        let tasks = Task::from_overdue_list_db(
+            *project.workspace_id(),
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(tasks), [overdue.id, earlier_today.id]);
+
+        let tasks = Task::from_due_today_list_db(
+            *project.workspace_id(),
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(tasks), [earlier_today.id, later_today.id]);
+
+        let tasks = Task::from_due_this_week_list_db(
+            *project.workspace_id(),
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            ids(tasks),
+            [overdue.id, earlier_today.id, later_today.id, sunday.id]
+        );
+
+        let tasks = Task::from_upcoming_list_db(
+            *project.workspace_id(),
+            now,
+            7,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(tasks), [later_today.id, sunday.id, next_week.id]);
+
+        let tasks = Task::from_upcoming_list_db(
+            *project.workspace_id(),
+            now,
+            7,
+            &workflow,
+            &ListSpec::new(),
+            1,
+            2,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids(tasks), [next_week.id]);
+    }
+
+    #[tokio::test]
+    async fn inactive_project_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workflow = Workflow::default();
+        let now = Utc::now();
+        let mut project = create_project(&mut conn).await;
+        let workspace_id = *project.workspace_id();
+        let mut task = create_task(&project);
+        task.set_due(Some(now - Duration::days(1)));
+        task.insert(&mut conn).await.unwrap();
+
+        let tasks = Task::from_overdue_list_db(
+            workspace_id,
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(tasks, [task.clone()]);
+
+        // the tasks of trashed projects are left out of the workspace lists
+        project.delete_db(editor, &mut conn).await.unwrap();
+        let tasks = Task::from_overdue_list_db(
+            workspace_id,
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert!(tasks.is_empty());
+        let tasks = Task::from_workspace_list_db(workspace_id, &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert!(tasks.is_empty());
+        let tasks = Task::from_urgency_list_db(
+            workspace_id,
+            now,
+            &Urgency::default(),
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert!(tasks.is_empty());
+
+        // and so are the tasks of archived projects
+        project.restore_db(editor, &mut conn).await.unwrap();
+        project.archive_db(editor, &mut conn).await.unwrap();
+        let tasks = Task::from_overdue_list_db(
+            workspace_id,
+            now,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert!(tasks.is_empty());
+
+        // the project's own list still has them
+        let tasks = Task::from_project_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks, [task]);
+    }
+
+    #[tokio::test]
+    async fn dates_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+        let start: DateTime<Utc> = Utc.with_ymd_and_hms(2024, 6, 19, 9, 30, 0).unwrap();
+        task.set_start(Some(start));
+        task.set_due(Some(start + Duration::days(2)));
+        task.insert(&mut conn).await.unwrap();
+
+        let query = Task::from_db(task.id, &mut conn).await.unwrap();
+        assert_eq!(query.start(), &Some(start));
+        assert_eq!(query, task);
+    }
+
+    #[test]
+    fn next_occurrence() {
+        let workflow = Workflow::default();
+        let due = Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap();
+        let mut task = create_task(&Project::default());
+        task.set_status("done".into());
+        task.set_start(Some(due - Duration::hours(4)));
+        task.set_due(Some(due));
+
+        assert_eq!(task.next_occurrence(&workflow), Ok(None));
+
+        task.set_recurrence(Some("FREQ=WEEKLY;BYDAY=FR;COUNT=2"))
+            .unwrap();
+        let next = task.next_occurrence(&workflow).unwrap().unwrap();
+        assert_ne!(next.id, task.id);
+        assert_eq!(next.title(), task.title());
+        assert_eq!(next.status(), "todo");
+        assert_eq!(next.due(), &Some(due + Duration::weeks(1)));
+        assert_eq!(
+            next.start(),
+            &Some(due + Duration::weeks(1) - Duration::hours(4))
+        );
+        assert_eq!(
+            next.recurrence(),
+            Some("FREQ=WEEKLY;BYDAY=FR;COUNT=1".parse::<RecurrenceRule>())
+        );
+
+        assert_eq!(next.next_occurrence(&workflow), Ok(None));
+    }
+
+    #[test]
+    fn set_recurrence_invalid() {
+        let mut task = create_task(&Project::default());
+        task.set_recurrence(Some("FREQ=DAILY")).unwrap();
+
+        assert_eq!(
+            task.set_recurrence(Some("FREQ=DAILY;INTERVAL=0")),
+            Err(RecurrenceError::InvalidValue {
+                part: "INTERVAL".into(),
+                value: "0".into()
+            })
+        );
+        assert_eq!(task.recurrence(), Some("FREQ=DAILY".parse()));
+
+        task.set_recurrence(None).unwrap();
+        assert_eq!(task.recurrence(), None);
+    }
+
+    #[tokio::test]
+    async fn recurrence_invalid_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow::default();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+        task.set_status("review".into());
+        // stored by an older version or edited on DB, `set_recurrence` rejects it
+        task.recurrence = Some("FREQ=SOMETIMES".into());
+        task.insert(&mut conn).await.unwrap();
+
+        assert!(matches!(task.recurrence(), Some(Err(_))));
+        assert!(matches!(
+            task.transition_db("done".into(), &workflow, task.author_id, &mut conn)
+                .await,
+            Err(DbError::Recurrence(RecurrenceError::InvalidValue { .. }))
+        ));
+        assert_eq!(task.status(), "review");
+        assert_eq!(Task::from_db(task.id, &mut conn).await.unwrap(), task);
+    }
+
+    #[tokio::test]
+    async fn recurrence_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workflow = Workflow {
+            completed: vec!["done".into(), "cancelled".into()],
+            ..Default::default()
+        };
+        let project = create_project(&mut conn).await;
+        let due = Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap();
+        let mut task = create_task(&project);
+        task.set_due(Some(due));
+        task.set_recurrence(Some("FREQ=WEEKLY")).unwrap();
+        task.insert(&mut conn).await.unwrap();
+
+        let (_, next) = task
+            .transition_db("in-progress".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(next, None);
+
+        task.transition_db("review".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .unwrap();
+        let (_, next) = task
+            .transition_db("done".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .unwrap();
+        let next = next.unwrap();
+        assert_eq!(next.due(), &Some(due + Duration::weeks(1)));
+        assert_eq!(Task::from_db(next.id, &mut conn).await.unwrap(), next);
+
+        // Reopening spawns nothing, completing again (cancelled is completed here) spawns another occurrence
+        let (_, again) = task
+            .transition_db("todo".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(again, None);
+        let (_, again) = task
+            .transition_db("cancelled".into(), &workflow, task.author_id, &mut conn)
+            .await
+            .unwrap();
+        assert!(again.is_some());
+
+        let tasks = Task::from_project_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 3);
+    }
+
+    async fn create_recurring(project: &Project, conn: &mut SqliteConnection) -> Task {
+        let mut task = create_task(project);
+        task.set_due(Some(Utc.with_ymd_and_hms(2024, 6, 21, 17, 0, 0).unwrap()));
+        task.set_recurrence(Some("FREQ=WEEKLY")).unwrap();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn complete(task: &mut Task, conn: &mut SqliteConnection) -> Task {
+        let workflow = Workflow::default();
+        let mut next = None;
+        for status in ["in-progress", "review", "done"] {
+            (_, next) = task
+                .transition_db(status.into(), &workflow, task.author_id, &mut *conn)
+                .await
+                .unwrap();
+        }
+        next.unwrap()
+    }
+
+    #[tokio::test]
+    async fn recurrence_reminders_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_recurring(&project, &mut conn).await;
+        for before in [Duration::hours(1), Duration::days(1)] {
+            Reminder::new(task.id, before)
+                .insert(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        let next = complete(&mut task, &mut conn).await;
+        let reminders = Reminder::from_task_list_db(next.id, &mut conn)
+            .await
+            .unwrap();
+        let mut offsets: Vec<_> = reminders.iter().map(|reminder| reminder.before()).collect();
+        offsets.sort();
+        assert_eq!(offsets, [Duration::hours(1), Duration::days(1)]);
+        assert_eq!(
+            Reminder::from_task_list_db(task.id, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn recurrence_tags_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_recurring(&project, &mut conn).await;
+        let mut tag = Tag::new(*project.workspace_id(), "bug".into(), "#d73a4a".into());
+        tag.assign_id().assign_created();
+        tag.insert(&mut conn).await.unwrap();
+        tag.attach_task(task.id, &mut conn).await.unwrap();
+
+        let next = complete(&mut task, &mut conn).await;
+        assert_eq!(
+            Tag::from_task_list_db(next.id, &mut conn).await.unwrap(),
+            [tag]
+        );
+    }
+
+    #[tokio::test]
+    async fn recurrence_assignees_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_recurring(&project, &mut conn).await;
+        let mut assignees = Vec::new();
+        for _ in 0..2 {
+            let user = create_test_user(&mut conn).await;
+            user.assign_task(task.id, &mut conn).await.unwrap();
+            assignees.push(*user.id());
+        }
+
+        let next = complete(&mut task, &mut conn).await;
+        let mut assigned: Vec<_> = User::from_task_list_db(next.id, &mut conn)
+            .await
+            .unwrap()
+            .iter()
+            .map(|user| *user.id())
+            .collect();
+        assigned.sort();
+        assignees.sort();
+        assert_eq!(assigned, assignees);
+    }
+
+    #[tokio::test]
+    async fn recurrence_checklist_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_recurring(&project, &mut conn).await;
+        for text in ["pack", "ship"] {
+            let mut item = ChecklistItem::new(task.id, text.into());
+            item.assign_id().assign_created();
+            item.append_db(&mut conn).await.unwrap();
+        }
+        ChecklistItem::check_all_db(task.id, true, &mut conn)
+            .await
+            .unwrap();
+
+        let next = complete(&mut task, &mut conn).await;
+        let checklist = ChecklistItem::from_task_list_db(next.id, &mut conn)
+            .await
+            .unwrap();
+        let items: Vec<_> = checklist
+            .iter()
+            .map(|item| (item.text().as_str(), item.checked()))
+            .collect();
+        assert_eq!(items, [("pack", false), ("ship", false)]);
+        assert!(ChecklistItem::from_task_list_db(task.id, &mut conn)
+            .await
+            .unwrap()
+            .iter()
+            .all(ChecklistItem::checked));
+    }
+
+    #[tokio::test]
+    async fn priority_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let mut task = create_task(&project);
+        assert_eq!(task.priority(), &Priority::P2);
+
+        task.set_priority(Priority::P0);
+        task.set_effort(Some(2.5));
+        task.insert(&mut conn).await.unwrap();
+
+        let loaded = Task::from_db(*task.id(), &mut conn).await.unwrap();
+        assert_eq!(loaded, task);
+        assert_eq!(loaded.priority().to_string(), "P0");
+    }
+
+    #[test]
+    fn urgency_validate() {
+        assert!(Urgency::default().validate().is_ok());
+
+        for age_max_days in [0.0, -1.0, f64::NAN] {
+            let urgency = Urgency {
+                age_max_days,
+                ..Default::default()
+            };
+            assert!(matches!(
+                urgency.validate(),
+                Err(DbError::InvalidUrgency(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn urgency_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let workflow = Workflow::default();
+        let urgency = Urgency::default();
+        let now = Utc::now();
+
+        let mut important = create_task(&project);
+        important.set_priority(Priority::P0);
+        let mut overdue = create_task(&project);
+        overdue.set_due(Some(now - Duration::days(10)));
+        let mut later = create_task(&project);
+        later.set_priority(Priority::P4);
+        later.set_due(Some(now + Duration::days(30)));
+        let mut done = create_task(&project);
+        done.set_priority(Priority::P0);
+        done.set_status("done".into());
+        for task in [&important, &overdue, &later, &done] {
+            task.insert(&mut conn).await.unwrap();
+        }
+
+        let ranked = Task::from_urgency_list_db(
+            *project.workspace_id(),
+            now,
+            &urgency,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        let tasks: Vec<_> = ranked.iter().map(|ranked| &ranked.task).collect();
+        assert_eq!(tasks, [&overdue, &important, &later]);
+        assert!((ranked[0].urgency - 15.0).abs() < 0.01);
+        assert!((ranked[1].urgency - 6.0).abs() < 0.01);
+        assert!((ranked[2].urgency - 2.4).abs() < 0.01);
+
+        let page = Task::from_urgency_list_db(
+            *project.workspace_id(),
+            now,
+            &urgency,
+            &workflow,
+            &ListSpec::new(),
+            1,
+            1,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page[0].task, important);
+
+        let due_only = Urgency {
+            priority: 0.0,
+            ..Urgency::default()
+        };
+        let ranked = Task::from_urgency_list_db(
+            *project.workspace_id(),
+            now,
+            &due_only,
+            &workflow,
+            &ListSpec::new(),
+            0,
+            10,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ranked[1].task, later);
+    }
+
+    #[tokio::test]
+    async fn checklist_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project);
+        let empty = create_task(&project);
+        task.insert(&mut conn).await.unwrap();
+        empty.insert(&mut conn).await.unwrap();
+
+        for text in ["a", "b", "c"] {
+            let mut item = ChecklistItem::new(*task.id(), text.into());
+            item.assign_id().assign_created();
+            item.append_db(&mut conn).await.unwrap();
+            if text != "c" {
+                item.toggle_db(&mut conn).await.unwrap();
+            }
+        }
+
+        let tasks = Task::from_checklist_list_db(*project.id(), &ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].task, task);
+        assert_eq!(tasks[0].progress(), "2/3");
+        assert_eq!(tasks[1].task, empty);
+        assert_eq!(tasks[1].progress(), "0/0");
+
+        let spec = ListSpec::new().sort(Sort::Desc(TaskIden::Created));
+        let tasks = Task::from_checklist_list_db(*project.id(), &spec, 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks[0].task, empty);
+        assert_eq!(tasks[1].progress(), "2/3");
+    }
+}
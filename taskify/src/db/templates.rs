@@ -0,0 +1,721 @@
+//! Template Database Entity
+//! This file contains the project templates, a project together with its tasks, their checklists and tags that can
+//! be instantiated again and again, e.g. the monthly release. Templates are written in TOML and are either stored in
+//! the DB as a [`Template`] or kept as files in the [`Templates`] directory. Texts can use `{{variable}}`s that are
+//! given their values on instantiation, task dates are days relative to the instantiation date.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use chrono::{DateTime, Duration, Utc};
+use sea_query::{enum_def, Expr, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config::{Templates, Workflow};
+use crate::db::checklists::ChecklistItem;
+use crate::db::projects::Project;
+use crate::db::tags::Tag;
+use crate::db::tasks::{Priority, Task};
+use crate::db::{DbError, Entity, Repository};
+
+/// The color of the tags an instantiation creates because no tag of that name exists yet
+const NEW_TAG_COLOR: &str = "#ededed";
+
+/// A task of a [`ProjectTemplate`]
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct TaskTemplate {
+    /// The task title.
+    pub title: String,
+
+    /// The task body.
+    pub body: String,
+
+    /// The days after the instantiation date the task starts, negative days are before it.
+    pub start_days: Option<i64>,
+
+    /// The days after the instantiation date the task is due, negative days are before it.
+    pub due_days: Option<i64>,
+
+    /// The task priority.
+    pub priority: Priority,
+
+    /// The names of the task's tags.
+    pub tags: Vec<String>,
+
+    /// The texts of the task's checklist items, in checklist order.
+    pub checklist: Vec<String>,
+}
+
+/// A project and its tasks that can be instantiated, the schema of the template TOML
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct ProjectTemplate {
+    /// The project name.
+    pub name: String,
+
+    /// The project description.
+    pub description: String,
+
+    /// The names of the project's tags.
+    pub tags: Vec<String>,
+
+    /// The project's tasks, in creation order.
+    pub tasks: Vec<TaskTemplate>,
+}
+
+impl ProjectTemplate {
+    /// Parse a template definition
+    ///
+    /// # Arguments
+    /// * `definition` - The template TOML
+    pub fn from_toml(definition: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(definition)
+    }
+
+    /// Load the template files
+    ///
+    /// Parses every `*.toml` file of the template directory, the templates are keyed by their file name without the
+    /// extension. A file that fails to parse is listed with its [`DbError::Template`], so one malformed file doesn't
+    /// hide the other templates. A missing directory has no templates.
+    /// # Arguments
+    /// * `config` - The template configuration
+    pub fn from_dir_list(
+        config: &Templates,
+    ) -> Result<BTreeMap<String, Result<ProjectTemplate, DbError>>, std::io::Error> {
+        let mut templates = BTreeMap::new();
+        let entries = match fs::read_dir(&config.path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(templates),
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            let Some(stem) = path.file_stem() else {
+                continue;
+            };
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                let name = stem.to_string_lossy().into_owned();
+                let template = ProjectTemplate::from_toml(&fs::read_to_string(&path)?)
+                    .map_err(DbError::Template);
+                templates.insert(name, template);
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Capture a project as a template from the DB
+    ///
+    /// Captures the project with its tags and its tasks, in topological order, with their tags and checklists. Task
+    /// dates become whole days relative to `anchor`. Sub-task hierarchies and dependencies are not captured.
+    /// # Arguments
+    /// * `project` - The project to capture
+    /// * `anchor` - The datetime the task dates are made relative to, e.g. the release date
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn from_project_db<'c, A>(
+        project: &Project,
+        anchor: DateTime<Utc>,
+        conn: A,
+    ) -> Result<Self, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let tag_names = |tags: Vec<Tag>| tags.iter().map(|tag| tag.name().clone()).collect();
+        let days = |date: &Option<DateTime<Utc>>| date.map(|date| (date - anchor).num_days());
+
+        let mut tasks = vec![];
+        for task in Task::from_topological_db(*project.id(), &mut *tx).await? {
+            let checklist = ChecklistItem::from_task_list_db(*task.id(), &mut *tx).await?;
+            tasks.push(TaskTemplate {
+                title: task.title().clone(),
+                body: task.body().clone(),
+                start_days: days(task.start()),
+                due_days: days(task.due()),
+                priority: *task.priority(),
+                tags: tag_names(Tag::from_task_list_db(*task.id(), &mut *tx).await?),
+                checklist: checklist.iter().map(|item| item.text().clone()).collect(),
+            });
+        }
+
+        let tags = tag_names(Tag::from_project_list_db(*project.id(), &mut *tx).await?);
+        tx.commit().await?;
+
+        Ok(Self {
+            name: project.name().clone(),
+            description: project.description().clone(),
+            tags,
+            tasks,
+        })
+    }
+
+    /// Serialize the template definition to TOML
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Error while serializing template")
+    }
+
+    /// The names of the variables the template uses
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        self.map_texts(|name| {
+            variables.insert(name.to_string());
+            Ok(String::new())
+        })
+        .expect("collecting variables never fails");
+        variables
+    }
+
+    /// Substitute the variables of the template
+    ///
+    /// Replaces every `{{variable}}` in the names, descriptions, titles, bodies, checklists and tags.
+    /// # Arguments
+    /// * `variables` - The value of every variable the template uses
+    pub fn render(&self, variables: &BTreeMap<String, String>) -> Result<Self, DbError> {
+        self.map_texts(|name| {
+            variables
+                .get(name)
+                .cloned()
+                .ok_or_else(|| DbError::UnknownVariable(name.into()))
+        })
+    }
+
+    // Database Interactions
+
+    /// Instantiates the Template on DB
+    ///
+    /// Creates the project, its tasks and their checklists at once, tags that don't exist yet are created.
+    /// # Arguments
+    /// * `variables` - The value of every variable the template uses
+    /// * `workspace_id` - The id of the workspace the project is created in
+    /// * `author_id` - The id of the user that authors the project
+    /// * `anchor` - The datetime the task dates are relative to, e.g. the release date
+    /// * `workflow` - The workflow the tasks take their initial status from, usually the config.toml one
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn instantiate_db<'c, A>(
+        &self,
+        variables: &BTreeMap<String, String>,
+        workspace_id: Uuid,
+        author_id: Uuid,
+        anchor: DateTime<Utc>,
+        workflow: &Workflow,
+        conn: A,
+    ) -> Result<Project, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let template = self.render(variables)?;
+        let date = |days: Option<i64>| days.map(|days| anchor + Duration::days(days));
+
+        let mut tx = conn.begin().await?;
+        let mut project =
+            Project::new(workspace_id, template.name, template.description, author_id);
+        project.assign_id().assign_created();
+        project.insert_db(&mut *tx).await?;
+        for name in &template.tags {
+            ProjectTemplate::tag_db(workspace_id, name, &mut *tx)
+                .await?
+                .attach_project(*project.id(), &mut *tx)
+                .await?;
+        }
+
+        for task_template in template.tasks {
+            let mut task = Task::new(
+                *project.id(),
+                task_template.title,
+                task_template.body,
+                *project.author_id(),
+            );
+            task.assign_id().assign_created();
+            task.set_start(date(task_template.start_days));
+            task.set_due(date(task_template.due_days));
+            task.set_priority(task_template.priority);
+            task.create_db(workflow, &mut *tx).await?;
+            for name in &task_template.tags {
+                ProjectTemplate::tag_db(workspace_id, name, &mut *tx)
+                    .await?
+                    .attach_task(*task.id(), &mut *tx)
+                    .await?;
+            }
+            for text in task_template.checklist {
+                let mut item = ChecklistItem::new(*task.id(), text);
+                item.assign_id().assign_created();
+                item.append_db(&mut *tx).await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(project)
+    }
+
+    // Private Functions
+
+    /// Rebuilds the template with every variable in its texts replaced by `value(variable)`
+    fn map_texts<F>(&self, mut value: F) -> Result<Self, DbError>
+    where
+        F: FnMut(&str) -> Result<String, DbError>,
+    {
+        let mut map = |text: &String| substitute(text, &mut value);
+
+        let mut tasks = vec![];
+        for task in &self.tasks {
+            tasks.push(TaskTemplate {
+                title: map(&task.title)?,
+                body: map(&task.body)?,
+                tags: task.tags.iter().map(&mut map).collect::<Result<_, _>>()?,
+                checklist: task
+                    .checklist
+                    .iter()
+                    .map(&mut map)
+                    .collect::<Result<_, _>>()?,
+                ..task.clone()
+            });
+        }
+
+        Ok(Self {
+            name: map(&self.name)?,
+            description: map(&self.description)?,
+            tags: self.tags.iter().map(&mut map).collect::<Result<_, _>>()?,
+            tasks,
+        })
+    }
+
+    /// Loads the tag with the name in the workspace from the DB, creating it if there is none
+    async fn tag_db<'c, A>(workspace_id: Uuid, name: &str, conn: A) -> Result<Tag, Error>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        let mut tx = conn.begin().await?;
+        let query = Tag::select_name_query(workspace_id, name);
+        let tag = sqlx::query_as_with::<_, Tag, _>(&query.0, query.1)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let tag = match tag {
+            Some(tag) => tag,
+            None => {
+                let mut tag = Tag::new(workspace_id, name.into(), NEW_TAG_COLOR.into());
+                tag.assign_id().assign_created();
+                tag.insert(&mut *tx).await?;
+                tag
+            }
+        };
+        tx.commit().await?;
+
+        Ok(tag)
+    }
+}
+
+/// Replaces every `{{variable}}` of the text by `value(variable)`, an unclosed `{{` is kept as is
+fn substitute<F>(text: &str, value: &mut F) -> Result<String, DbError>
+where
+    F: FnMut(&str) -> Result<String, DbError>,
+{
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str(&value(rest[start + 2..end].trim())?);
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// The template database entity for taskify, the template is stored as TOML
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Template {
+    id: Uuid,
+    name: String,
+    definition: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+impl Template {
+    /// New Template
+    ///
+    /// Create a new template entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `name` - The unique template name
+    /// * `template` - The project template to store
+    pub fn new(name: String, template: &ProjectTemplate) -> Self {
+        Self {
+            name,
+            definition: template.to_toml(),
+            ..Default::default()
+        }
+    }
+
+    /// Load a template from the DB by its name
+    ///
+    /// # Arguments
+    /// * `name` - The template name to search for
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_name_db<'c, E>(name: &str, executor: E) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Template::select_name_query(name);
+
+        sqlx::query_as_with::<_, Template, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Get Template ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Template Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Parse the stored template definition
+    pub fn template(&self) -> Result<ProjectTemplate, toml::de::Error> {
+        ProjectTemplate::from_toml(&self.definition)
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the template.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the template `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Renames the template
+    /// # Arguments
+    /// * `name` - The unique string name to use
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Template
+    ///
+    /// Replaces the stored template definition
+    /// # Arguments
+    /// * `template` - The project template to store
+    pub fn set_template(&mut self, template: &ProjectTemplate) {
+        self.definition = template.to_toml();
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Instantiates the stored Template on DB
+    ///
+    /// See [`ProjectTemplate::instantiate_db`].
+    /// # Arguments
+    /// * `variables` - The value of every variable the template uses
+    /// * `workspace_id` - The id of the workspace the project is created in
+    /// * `author_id` - The id of the user that authors the project
+    /// * `anchor` - The datetime the task dates are relative to, e.g. the release date
+    /// * `workflow` - The workflow the tasks take their initial status from, usually the config.toml one
+    /// * `conn` - The SQLite database connection or transaction
+    pub async fn instantiate_db<'c, A>(
+        &self,
+        variables: &BTreeMap<String, String>,
+        workspace_id: Uuid,
+        author_id: Uuid,
+        anchor: DateTime<Utc>,
+        workflow: &Workflow,
+        conn: A,
+    ) -> Result<Project, DbError>
+    where
+        A: Acquire<'c, Database = Sqlite>,
+    {
+        self.template()
+            .map_err(DbError::Template)?
+            .instantiate_db(variables, workspace_id, author_id, anchor, workflow, conn)
+            .await
+    }
+
+    /// Generates a sqlx query to Find a Template on DB by its name
+    ///
+    /// # Arguments
+    /// * `name` - The template name to search for
+    pub fn select_name_query(name: &str) -> (String, SqlxValues) {
+        Template::select()
+            .and_where(Expr::col(TemplateIden::Name).eq(name))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the template edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for Template {
+    type Iden = TemplateIden;
+    type Key = Uuid;
+
+    const TABLE: TemplateIden = TemplateIden::Table;
+    const KEY: &'static [TemplateIden] = &[TemplateIden::Id];
+    const CREATED: Option<TemplateIden> = Some(TemplateIden::Created);
+    const COLUMNS: &'static [TemplateIden] = &[
+        TemplateIden::Id,
+        TemplateIden::Name,
+        TemplateIden::Definition,
+        TemplateIden::Created,
+        TemplateIden::Modified,
+    ];
+    const ORDER_BY: Option<TemplateIden> = Some(TemplateIden::Name);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.name.clone().into(),
+            self.definition.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a template keeps the projects instantiated from it
+impl Repository for Template {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProjectTemplate, Template};
+    use crate::config::{init_memory_db, Templates, Workflow};
+    use crate::db::checklists::ChecklistItem;
+    use crate::db::filter::ListSpec;
+    use crate::db::tags::Tag;
+    use crate::db::tasks::{Priority, Task};
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Repository};
+    use chrono::{Duration, TimeZone, Utc};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use uuid::Uuid;
+
+    const RELEASE: &str = r#"
+name = "Release {{version}}"
+description = "Ship {{ version }}"
+tags = ["release"]
+
+[[tasks]]
+title = "Freeze {{version}}"
+due_days = -7
+priority = "P1"
+checklist = ["Branch release/{{version}}", "Announce the freeze"]
+
+[[tasks]]
+title = "Tag {{version}}"
+body = "Released on {{date}}"
+start_days = -1
+due_days = 0
+priority = "P0"
+tags = ["release", "ops"]
+"#;
+
+    fn variables() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("version".into(), "1.2".into()),
+            ("date".into(), "2024-07-01".into()),
+        ])
+    }
+
+    #[test]
+    fn render() {
+        let template = ProjectTemplate::from_toml(RELEASE).unwrap();
+        assert_eq!(
+            template.variables().into_iter().collect::<Vec<_>>(),
+            ["date", "version"]
+        );
+
+        let rendered = template.render(&variables()).unwrap();
+        assert_eq!(rendered.name, "Release 1.2");
+        assert_eq!(rendered.description, "Ship 1.2");
+        assert_eq!(rendered.tasks[0].checklist[0], "Branch release/1.2");
+        assert_eq!(rendered.tasks[1].body, "Released on 2024-07-01");
+        assert_eq!(rendered.tasks[1].due_days, Some(0));
+
+        let mut missing = variables();
+        missing.remove("date");
+        assert!(matches!(
+            template.render(&missing),
+            Err(DbError::UnknownVariable(name)) if name == "date"
+        ));
+    }
+
+    #[test]
+    fn dir_list() {
+        let templates = ProjectTemplate::from_dir_list(&Templates {
+            path: "../templates".into(),
+        })
+        .unwrap();
+        let release = templates["release"].as_ref().unwrap();
+        assert!(release.variables().contains("version"));
+
+        let missing = ProjectTemplate::from_dir_list(&Templates {
+            path: "./missing".into(),
+        });
+        assert!(missing.unwrap().is_empty());
+    }
+
+    #[test]
+    fn dir_list_malformed() {
+        let dir = std::env::temp_dir().join(format!("taskify-templates-{}", Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("release.toml"), RELEASE).unwrap();
+        fs::write(dir.join("broken.toml"), "name = ").unwrap();
+
+        let templates = ProjectTemplate::from_dir_list(&Templates {
+            path: dir.to_string_lossy().into(),
+        })
+        .unwrap();
+        assert!(templates["release"].is_ok());
+        assert!(matches!(templates["broken"], Err(DbError::Template(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn instantiate_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let author = *create_test_user(&mut conn).await.id();
+        let template = ProjectTemplate::from_toml(RELEASE).unwrap();
+        let release = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        let workflow = Workflow {
+            initial: "in-progress".into(),
+            ..Default::default()
+        };
+
+        let project = template
+            .instantiate_db(
+                &variables(),
+                workspace,
+                author,
+                release,
+                &workflow,
+                &mut conn,
+            )
+            .await
+            .unwrap();
+        assert_eq!(project.name(), "Release 1.2");
+        let tasks = Task::from_topological_db(*project.id(), &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(*tasks[0].due(), Some(release - Duration::days(7)));
+        assert_eq!(*tasks[1].start(), Some(release - Duration::days(1)));
+        assert_eq!(*tasks[1].priority(), Priority::P0);
+        assert_eq!(tasks[1].status(), "in-progress");
+        let checklist = ChecklistItem::from_task_list_db(*tasks[0].id(), &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(checklist[1].text(), "Announce the freeze");
+
+        // the second instantiation reuses the tags the first one created
+        let next = BTreeMap::from([
+            ("version".into(), "1.3".into()),
+            ("date".into(), "2024-08-01".into()),
+        ]);
+        let next = template
+            .instantiate_db(&next, workspace, author, release, &workflow, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            Tag::from_usage_list_db(workspace, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let captured = ProjectTemplate::from_project_db(&next, release, &mut conn)
+            .await
+            .unwrap();
+        let mut expected = template.render(&variables()).unwrap();
+        expected.name = "Release 1.3".into();
+        expected.description = "Ship 1.3".into();
+        expected.tasks[0].title = "Freeze 1.3".into();
+        expected.tasks[0].checklist[0] = "Branch release/1.3".into();
+        expected.tasks[1].title = "Tag 1.3".into();
+        expected.tasks[1].body = "Released on 2024-08-01".into();
+        expected.tasks[1].tags.sort();
+        assert_eq!(captured, expected);
+    }
+
+    #[tokio::test]
+    async fn template_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let workspace = *create_test_workspace(&mut conn).await.id();
+        let author = *create_test_user(&mut conn).await.id();
+        let release = ProjectTemplate::from_toml(RELEASE).unwrap();
+        let mut template = Template::new("release".into(), &release);
+        template.assign_id().assign_created();
+        template.insert(&mut conn).await.unwrap();
+
+        let stored = Template::from_name_db("release", &mut conn).await.unwrap();
+        assert_eq!(stored.template().unwrap(), release);
+
+        let project = stored
+            .instantiate_db(
+                &variables(),
+                workspace,
+                author,
+                Utc::now(),
+                &Workflow::default(),
+                &mut conn,
+            )
+            .await
+            .unwrap();
+        assert_eq!(project.description(), "Ship 1.2");
+
+        template.set_template(&ProjectTemplate::default());
+        template.update(&mut conn).await.unwrap();
+        assert!(Template::from_db(*template.id(), &mut conn)
+            .await
+            .unwrap()
+            .template()
+            .unwrap()
+            .tasks
+            .is_empty());
+        assert_eq!(
+            Template::from_list_db(&ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            [template]
+        );
+    }
+}
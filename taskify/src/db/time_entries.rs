@@ -0,0 +1,690 @@
+//! Time Entry Database Entity
+//! This file contains the time entry database entity, the time an author spent on a task. An entry without an end is
+//! a running timer, the DB allows at most one running timer per author. Timers live in the DB, so a timer started
+//! before a restart is still running after it, see [`TimeEntry::from_running_db`].
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sea_query::{enum_def, Expr, Func, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::filter::{ListSpec, Sort};
+use crate::db::tasks::TaskIden;
+use crate::db::{DbError, Entity, Repository};
+
+/// The message the DB aborts with when an author starts a second timer
+const TIMER_RUNNING: &str = "UNIQUE constraint failed: time_entry.author_id";
+
+/// The time entry database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct TimeEntry {
+    id: Uuid,
+    task_id: Uuid,
+    author_id: Uuid,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    note: String,
+    created: DateTime<Utc>,
+}
+
+/// The time tracked on one day, as returned by [`TimeEntry::from_daily_list_db`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyTotal {
+    pub day: NaiveDate,
+    pub total: Duration,
+}
+
+impl TimeEntry {
+    /// New Time Entry
+    ///
+    /// Create a new time entry with a random id.
+    /// # Arguments:
+    /// * `task_id` - The id of the task the time was spent on
+    /// * `author_id` - The id of the [`User`](super::users::User) that spent the time
+    /// * `start` - When the work started
+    /// * `end` - When the work ended, `None` for a running timer
+    /// * `note` - What was done
+    pub fn new(
+        task_id: Uuid,
+        author_id: Uuid,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        note: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            author_id,
+            start,
+            end,
+            note,
+            created: Utc::now(),
+        }
+    }
+
+    /// Start Timer
+    ///
+    /// Create a new running timer starting at the current datetime.
+    /// # Arguments:
+    /// * `task_id` - The id of the task being worked on
+    /// * `author_id` - The id of the user working on it
+    /// * `note` - What is being done
+    pub fn start(task_id: Uuid, author_id: Uuid, note: String) -> Self {
+        TimeEntry::new(task_id, author_id, Utc::now(), None, note)
+    }
+
+    /// Load the running timer of an author from the DB
+    ///
+    /// Returns `None` if the author has no running timer. Timers are not stopped when the app exits, so this also
+    /// finds timers started in a previous session.
+    /// # Arguments
+    /// * `author_id` - The id of the author of the timer
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_running_db<'c, E>(author_id: Uuid, executor: E) -> Result<Option<Self>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TimeEntry::running_query(author_id);
+
+        sqlx::query_as_with::<_, TimeEntry, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// List the Time Entries of a Task in the DB
+    ///
+    /// Lists the entries of a task matching the spec, most recent first unless it sorts them.
+    /// # Arguements
+    /// * `task_id` - The id of the task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(
+        task_id: Uuid,
+        spec: &ListSpec<TimeEntryIden>,
+        page: u64,
+        limit: u64,
+        executor: E,
+    ) -> Result<Vec<TimeEntry>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TimeEntry::list_task_query(task_id, spec, page, limit);
+        sqlx::query_as_with::<_, TimeEntry, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Total time spent on a Task in the DB
+    ///
+    /// Running timers count up to `now`.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `now` - The current datetime
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn total_task_db<'c, E>(
+        task_id: Uuid,
+        now: DateTime<Utc>,
+        executor: E,
+    ) -> Result<Duration, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TimeEntry::total_task_query(task_id, now);
+        let seconds: Option<i64> = sqlx::query_scalar_with(&query.0, query.1)
+            .fetch_one(executor)
+            .await?;
+
+        Ok(Duration::seconds(seconds.unwrap_or_default()))
+    }
+
+    /// Total time spent on the Tasks of a Project in the DB
+    ///
+    /// Running timers count up to `now`.
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `now` - The current datetime
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn total_project_db<'c, E>(
+        project_id: Uuid,
+        now: DateTime<Utc>,
+        executor: E,
+    ) -> Result<Duration, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TimeEntry::total_project_query(project_id, now);
+        let seconds: Option<i64> = sqlx::query_scalar_with(&query.0, query.1)
+            .fetch_one(executor)
+            .await?;
+
+        Ok(Duration::seconds(seconds.unwrap_or_default()))
+    }
+
+    /// Time spent per day in the DB
+    ///
+    /// Sums the time tracked in `[after, before)` per UTC day, optionally only on the tasks of one project. Entries
+    /// spanning midnight are split between the days and running timers count up to `now`. Days without tracked
+    /// time are left out.
+    /// # Arguments
+    /// * `project_id` - The id of the project, `None` for every project
+    /// * `after` - The inclusive lower bound
+    /// * `before` - The exclusive upper bound
+    /// * `now` - The current datetime
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_daily_list_db<'c, E>(
+        project_id: Option<Uuid>,
+        after: DateTime<Utc>,
+        before: DateTime<Utc>,
+        now: DateTime<Utc>,
+        executor: E,
+    ) -> Result<Vec<DailyTotal>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TimeEntry::list_range_query(project_id, after, before, now);
+        let entries = sqlx::query_as_with::<_, TimeEntry, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await?;
+
+        let mut days = BTreeMap::<NaiveDate, Duration>::new();
+        for entry in entries {
+            let mut start = entry.start.max(after);
+            let end = entry.end.unwrap_or(now).min(before);
+            while start < end {
+                let midnight = (start.date_naive() + Duration::days(1))
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                let until = midnight.min(end);
+                *days
+                    .entry(start.date_naive())
+                    .or_insert_with(Duration::zero) += until - start;
+                start = until;
+            }
+        }
+
+        Ok(days
+            .into_iter()
+            .map(|(day, total)| DailyTotal { day, total })
+            .collect())
+    }
+
+    /// Get Time Entry ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Time Entry Task ID
+    pub fn task_id(&self) -> &Uuid {
+        &self.task_id
+    }
+
+    /// Get Time Entry Author ID
+    pub fn author_id(&self) -> &Uuid {
+        &self.author_id
+    }
+
+    /// Get Time Entry Start Datetime
+    pub fn started(&self) -> &DateTime<Utc> {
+        &self.start
+    }
+
+    /// Get Time Entry End Datetime, `None` while the timer is running
+    pub fn ended(&self) -> &Option<DateTime<Utc>> {
+        &self.end
+    }
+
+    /// Get Time Entry Note
+    pub fn note(&self) -> &String {
+        &self.note
+    }
+
+    /// Is the timer still running?
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// The time spent, running timers count up to `now`
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+
+    /// Set Note
+    ///
+    /// Sets what was done
+    /// # Arguments
+    /// * `note` - The string note to use
+    pub fn set_note(&mut self, note: String) {
+        self.note = note;
+    }
+
+    /// Stop
+    ///
+    /// Stops the timer at `end`, never before it started.
+    /// # Arguments
+    /// * `end` - When the work ended
+    pub fn stop(&mut self, end: DateTime<Utc>) {
+        self.end = Some(end.max(self.start));
+    }
+
+    // Database Interactions
+
+    /// Starts the Timer on DB
+    ///
+    /// Inserts the entry, the DB rejects a second running timer of the same author with [`DbError::TimerRunning`].
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn insert_db<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1)
+            .execute(executor)
+            .await
+            .map_err(|err| match &err {
+                Error::Database(db) if db.message() == TIMER_RUNNING => DbError::TimerRunning,
+                _ => DbError::Sqlx(err),
+            })
+    }
+
+    /// Stops the Timer on DB
+    ///
+    /// # Arguments
+    /// * `end` - When the work ended
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn stop_db<'c, E>(
+        &mut self,
+        end: DateTime<Utc>,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let mut entry = self.clone();
+        entry.stop(end);
+
+        let result = entry.update(executor).await?;
+        *self = entry;
+        Ok(result)
+    }
+
+    /// Generates a sqlx query to Find the running timer of an author on DB
+    ///
+    /// # Arguments
+    /// * `author_id` - The id of the author of the timer
+    pub fn running_query(author_id: Uuid) -> (String, SqlxValues) {
+        TimeEntry::select()
+            .and_where(Expr::col(TimeEntryIden::AuthorId).eq(author_id))
+            .and_where(Expr::col(TimeEntryIden::End).is_null())
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Time Entries of a Task in the DB
+    ///
+    /// # Arguements
+    /// * `task_id` - The id of the task
+    /// * `spec` - The filters & sort order of the list
+    /// * `page` - The query page
+    /// * `limit` - The row per page
+    pub fn list_task_query(
+        task_id: Uuid,
+        spec: &ListSpec<TimeEntryIden>,
+        page: u64,
+        limit: u64,
+    ) -> (String, SqlxValues) {
+        let mut select = TimeEntry::select();
+
+        spec.apply(&mut select, Some(Sort::Desc(TimeEntryIden::Start)))
+            .and_where(Expr::col(TimeEntryIden::TaskId).eq(task_id))
+            .limit(limit)
+            .offset(page * limit)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Sum the time spent on a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `now` - The datetime running timers count up to
+    pub fn total_task_query(task_id: Uuid, now: DateTime<Utc>) -> (String, SqlxValues) {
+        Query::select()
+            .expr(Func::sum(TimeEntry::seconds_expr(now)))
+            .from(TimeEntryIden::Table)
+            .and_where(Expr::col(TimeEntryIden::TaskId).eq(task_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Sum the time spent on the Tasks of a Project in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `now` - The datetime running timers count up to
+    pub fn total_project_query(project_id: Uuid, now: DateTime<Utc>) -> (String, SqlxValues) {
+        Query::select()
+            .expr(Func::sum(TimeEntry::seconds_expr(now)))
+            .from(TimeEntryIden::Table)
+            .inner_join(
+                TaskIden::Table,
+                Expr::col((TaskIden::Table, TaskIden::Id))
+                    .equals((TimeEntryIden::Table, TimeEntryIden::TaskId)),
+            )
+            .and_where(Expr::col((TaskIden::Table, TaskIden::ProjectId)).eq(project_id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the Time Entries overlapping a time range in the DB
+    ///
+    /// # Arguments
+    /// * `project_id` - The id of the project, `None` for every project
+    /// * `after` - The inclusive lower bound
+    /// * `before` - The exclusive upper bound
+    /// * `now` - The datetime running timers count up to
+    pub fn list_range_query(
+        project_id: Option<Uuid>,
+        after: DateTime<Utc>,
+        before: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> (String, SqlxValues) {
+        TimeEntry::joined_select()
+            .inner_join(
+                TaskIden::Table,
+                Expr::col((TaskIden::Table, TaskIden::Id))
+                    .equals((TimeEntryIden::Table, TimeEntryIden::TaskId)),
+            )
+            .and_where_option(
+                project_id.map(|project_id| {
+                    Expr::col((TaskIden::Table, TaskIden::ProjectId)).eq(project_id)
+                }),
+            )
+            .and_where(Expr::col((TimeEntryIden::Table, TimeEntryIden::Start)).lt(before))
+            .and_where(
+                Expr::expr(Func::coalesce([
+                    Expr::col((TimeEntryIden::Table, TimeEntryIden::End)).into(),
+                    Expr::val(now).into(),
+                ]))
+                .gt(after),
+            )
+            .order_by((TimeEntryIden::Table, TimeEntryIden::Start), Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// The seconds spent on a time entry as an SQL expression, running timers count up to `now`
+    fn seconds_expr(now: DateTime<Utc>) -> SimpleExpr {
+        Expr::cust_with_values(
+            "CAST(strftime('%s', COALESCE(\"time_entry\".\"end\", ?)) AS INTEGER) \
+             - CAST(strftime('%s', \"time_entry\".\"start\") AS INTEGER)",
+            [now],
+        )
+    }
+}
+
+impl Entity for TimeEntry {
+    type Iden = TimeEntryIden;
+    type Key = Uuid;
+
+    const TABLE: TimeEntryIden = TimeEntryIden::Table;
+    const KEY: &'static [TimeEntryIden] = &[TimeEntryIden::Id];
+    const CREATED: Option<TimeEntryIden> = Some(TimeEntryIden::Created);
+    const COLUMNS: &'static [TimeEntryIden] = &[
+        TimeEntryIden::Id,
+        TimeEntryIden::TaskId,
+        TimeEntryIden::AuthorId,
+        TimeEntryIden::Start,
+        TimeEntryIden::End,
+        TimeEntryIden::Note,
+        TimeEntryIden::Created,
+    ];
+    const ORDER_BY: Option<TimeEntryIden> = Some(TimeEntryIden::Start);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.task_id.into(),
+            self.author_id.into(),
+            self.start.into(),
+            self.end.into(),
+            self.note.clone().into(),
+            self.created.into(),
+        ]
+    }
+}
+
+/// The plain insert does not map the running timer conflict, start timers with [`TimeEntry::insert_db`]
+impl Repository for TimeEntry {}
+
+#[cfg(test)]
+mod tests {
+    use super::{DailyTotal, TimeEntry};
+    use crate::config::init_memory_db;
+    use crate::db::filter::ListSpec;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+    use sqlx::SqliteConnection;
+    use uuid::Uuid;
+
+    async fn create_task(project: &Project, conn: &mut SqliteConnection) -> Task {
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(conn).await.unwrap();
+        task
+    }
+
+    async fn create_project(conn: &mut SqliteConnection) -> Project {
+        let mut project = Project::new(
+            *create_test_workspace(conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(conn).await.unwrap();
+        project
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn insert() {
+        let query =
+            TimeEntry::start(Uuid::default(), Uuid::default(), "NOTE".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"time_entry\" (\"id\", \"task_id\", \"author_id\", \"start\", \"end\", \"note\", \"created\") VALUES (?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn duration() {
+        let mut entry =
+            TimeEntry::new(Uuid::default(), Uuid::default(), at(20, 9), None, "".into());
+        assert!(entry.is_running());
+        assert_eq!(entry.duration(at(20, 11)), Duration::hours(2));
+
+        entry.stop(at(20, 8));
+        assert!(!entry.is_running());
+        assert_eq!(entry.duration(at(20, 11)), Duration::zero());
+    }
+
+    #[tokio::test]
+    async fn running_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let alice = *create_test_user(&mut conn).await.id();
+        let bob = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+
+        let mut timer = TimeEntry::start(*task.id(), alice, "".into());
+        timer.insert_db(&mut conn).await.unwrap();
+        assert!(matches!(
+            TimeEntry::start(*task.id(), alice, "".into())
+                .insert_db(&mut conn)
+                .await,
+            Err(DbError::TimerRunning)
+        ));
+        TimeEntry::start(*task.id(), bob, "".into())
+            .insert_db(&mut conn)
+            .await
+            .unwrap();
+
+        // a timer left running is found again, e.g. after a restart
+        let mut running = TimeEntry::from_running_db(alice, &mut conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(running, timer);
+
+        running.stop_db(Utc::now(), &mut conn).await.unwrap();
+        assert!(TimeEntry::from_running_db(alice, &mut conn)
+            .await
+            .unwrap()
+            .is_none());
+        timer = TimeEntry::start(*task.id(), alice, "".into());
+        timer.insert_db(&mut conn).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn total_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let alice = *create_test_user(&mut conn).await.id();
+        let bob = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+        let second = create_task(&project, &mut conn).await;
+        let elsewhere = create_task(&other, &mut conn).await;
+
+        let entries = [
+            TimeEntry::new(*task.id(), alice, at(20, 9), Some(at(20, 11)), "".into()),
+            TimeEntry::new(*task.id(), bob, at(20, 10), None, "".into()),
+            TimeEntry::new(*second.id(), alice, at(20, 12), Some(at(20, 13)), "".into()),
+            TimeEntry::new(
+                *elsewhere.id(),
+                alice,
+                at(20, 13),
+                Some(at(20, 14)),
+                "".into(),
+            ),
+        ];
+        for entry in &entries {
+            entry.insert(&mut conn).await.unwrap();
+        }
+
+        let now = at(20, 14);
+        assert_eq!(
+            TimeEntry::total_task_db(*task.id(), now, &mut conn)
+                .await
+                .unwrap(),
+            Duration::hours(6)
+        );
+        assert_eq!(
+            TimeEntry::total_project_db(*project.id(), now, &mut conn)
+                .await
+                .unwrap(),
+            Duration::hours(7)
+        );
+        assert_eq!(
+            TimeEntry::total_task_db(Uuid::new_v4(), now, &mut conn)
+                .await
+                .unwrap(),
+            Duration::zero()
+        );
+        assert_eq!(
+            TimeEntry::from_task_list_db(*task.id(), &ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap(),
+            vec![entries[1].clone(), entries[0].clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn daily_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let alice = *create_test_user(&mut conn).await.id();
+        let bob = *create_test_user(&mut conn).await.id();
+        let project = create_project(&mut conn).await;
+        let other = create_project(&mut conn).await;
+        let task = create_task(&project, &mut conn).await;
+        let elsewhere = create_task(&other, &mut conn).await;
+
+        // 22:00 → 02:00 spans midnight, the running timer started the day before `now`
+        let entries = [
+            TimeEntry::new(*task.id(), alice, at(19, 22), Some(at(20, 2)), "".into()),
+            TimeEntry::new(*task.id(), bob, at(20, 23), None, "".into()),
+            TimeEntry::new(
+                *elsewhere.id(),
+                alice,
+                at(20, 9),
+                Some(at(20, 10)),
+                "".into(),
+            ),
+        ];
+        for entry in &entries {
+            entry.insert(&mut conn).await.unwrap();
+        }
+
+        let day = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let now = at(21, 1);
+        let daily = TimeEntry::from_daily_list_db(
+            Some(*project.id()),
+            at(19, 0),
+            at(22, 0),
+            now,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            daily,
+            vec![
+                DailyTotal {
+                    day: day(19),
+                    total: Duration::hours(2)
+                },
+                DailyTotal {
+                    day: day(20),
+                    total: Duration::hours(3)
+                },
+                DailyTotal {
+                    day: day(21),
+                    total: Duration::hours(1)
+                },
+            ]
+        );
+
+        let daily = TimeEntry::from_daily_list_db(None, at(20, 0), at(21, 0), now, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            daily,
+            vec![DailyTotal {
+                day: day(20),
+                total: Duration::hours(4)
+            }]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Task Transition Database Entity
+//! This file contains the task transition database entity, an append-only log of every status change of a task.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, Order, Query, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+/// The task transition database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct TaskTransition {
+    id: Uuid,
+    task_id: Uuid,
+    from_status: String,
+    to_status: String,
+    transitioned: DateTime<Utc>,
+}
+
+impl TaskTransition {
+    /// New Task Transition
+    ///
+    /// Create a new task transition entity with a random id, happening at the current datetime.
+    /// # Arguments:
+    /// * `task_id` - The id of the task that changed status
+    /// * `from_status` - The status the task had
+    /// * `to_status` - The status the task has now
+    pub fn new(task_id: Uuid, from_status: String, to_status: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            from_status,
+            to_status,
+            transitioned: Utc::now(),
+        }
+    }
+
+    /// List all Transitions of a Task in the DB
+    ///
+    /// Lists the transitions of a task in chronological order.
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<Vec<TaskTransition>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = TaskTransition::list_task_query(task_id);
+        sqlx::query_as_with::<_, TaskTransition, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get Transition Task ID
+    pub fn task_id(&self) -> &Uuid {
+        &self.task_id
+    }
+
+    /// Get Transition From Status
+    pub fn from_status(&self) -> &String {
+        &self.from_status
+    }
+
+    /// Get Transition To Status
+    pub fn to_status(&self) -> &String {
+        &self.to_status
+    }
+
+    /// Get Transition Datetime
+    pub fn transitioned(&self) -> &DateTime<Utc> {
+        &self.transitioned
+    }
+
+    // Database Interactions
+
+    /// Inserts Task Transition to DB
+    ///
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn insert<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.insert_query();
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to Insert Task Transition to DB
+    pub fn insert_query(&self) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(TaskTransitionIden::Table)
+            .columns([
+                TaskTransitionIden::Id,
+                TaskTransitionIden::TaskId,
+                TaskTransitionIden::FromStatus,
+                TaskTransitionIden::ToStatus,
+                TaskTransitionIden::Transitioned,
+            ])
+            .values([
+                self.id.into(),
+                self.task_id.into(),
+                self.from_status.clone().into(),
+                self.to_status.clone().into(),
+                self.transitioned.into(),
+            ])
+            .unwrap()
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List all Transitions of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn list_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        Query::select()
+            .columns([
+                TaskTransitionIden::Id,
+                TaskTransitionIden::TaskId,
+                TaskTransitionIden::FromStatus,
+                TaskTransitionIden::ToStatus,
+                TaskTransitionIden::Transitioned,
+            ])
+            .from(TaskTransitionIden::Table)
+            .and_where(Expr::col(TaskTransitionIden::TaskId).eq(task_id))
+            .order_by(TaskTransitionIden::Transitioned, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaskTransition;
+    use uuid::Uuid;
+
+    #[test]
+    fn insert() {
+        let query =
+            TaskTransition::new(Uuid::default(), "todo".into(), "done".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"task_transition\" (\"id\", \"task_id\", \"from_status\", \"to_status\", \"transitioned\") VALUES (?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn list_task() {
+        let query = TaskTransition::list_task_query(Uuid::default());
+
+        assert_eq!(query.0, "SELECT \"id\", \"task_id\", \"from_status\", \"to_status\", \"transitioned\" FROM \"task_transition\" WHERE \"task_id\" = ? ORDER BY \"transitioned\" ASC");
+    }
+}
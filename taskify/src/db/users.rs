@@ -0,0 +1,518 @@
+//! User Database Entity
+//! This file contains the user database entity, the people that author [`Project`](super::projects::Project)s,
+//! [`Task`](super::tasks::Task)s, comments, time entries & the history, and are assigned to tasks. A task can have any
+//! number of assignees.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, OnConflict, Order, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::db::{Entity, Repository};
+
+/// The user database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct User {
+    id: Uuid,
+    name: String,
+    email: Option<String>,
+    handle: String,
+    avatar: Option<String>,
+    color: Option<String>,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+/// The assignment of a user to a task
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct TaskAssignee {
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+}
+
+impl User {
+    /// New User
+    ///
+    /// Create a new user entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `name` - The display name
+    /// * `handle` - The unique handle, e.g. `mmitsios`
+    pub fn new(name: String, handle: String) -> Self {
+        Self {
+            name,
+            handle,
+            ..Default::default()
+        }
+    }
+
+    /// Load a user from the DB by their handle
+    ///
+    /// # Arguments
+    /// * `handle` - The handle to search for
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_handle_db<'c, E>(handle: &str, executor: E) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = User::select_handle_query(handle);
+
+        sqlx::query_as_with::<_, User, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// List the assignees of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_task_list_db<'c, E>(task_id: Uuid, executor: E) -> Result<Vec<User>, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = User::list_task_query(task_id);
+        sqlx::query_as_with::<_, User, _>(&query.0, query.1)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Get User ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get User Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get User Email
+    pub fn email(&self) -> &Option<String> {
+        &self.email
+    }
+
+    /// Get User Handle
+    pub fn handle(&self) -> &String {
+        &self.handle
+    }
+
+    /// Get User Avatar
+    pub fn avatar(&self) -> &Option<String> {
+        &self.avatar
+    }
+
+    /// Get User Color
+    pub fn color(&self) -> &Option<String> {
+        &self.color
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the user.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the user `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Sets the user display name
+    /// # Arguments
+    /// * `name` - The string display name to use
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Email
+    ///
+    /// Sets the user email, emails are unique
+    /// # Arguments
+    /// * `email` - The email to use
+    pub fn set_email(&mut self, email: Option<String>) {
+        self.email = email;
+        self.edited();
+    }
+
+    /// Set Handle
+    ///
+    /// Sets the user handle
+    /// # Arguments
+    /// * `handle` - The unique handle to use
+    pub fn set_handle(&mut self, handle: String) {
+        self.handle = handle;
+        self.edited();
+    }
+
+    /// Set Avatar
+    ///
+    /// Sets the user avatar
+    /// # Arguments
+    /// * `avatar` - The avatar path or url to use
+    pub fn set_avatar(&mut self, avatar: Option<String>) {
+        self.avatar = avatar;
+        self.edited();
+    }
+
+    /// Set Color
+    ///
+    /// Sets the user color
+    /// # Arguments
+    /// * `color` - The color to use, e.g. `#d73a4a`
+    pub fn set_color(&mut self, color: Option<String>) {
+        self.color = color;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Assigns the User to a Task on DB, assigning them twice is a no-op
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn assign_task<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.assign_task_query(task_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Unassigns the User from a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn unassign_task<'c, E>(
+        &self,
+        task_id: Uuid,
+        executor: E,
+    ) -> Result<SqliteQueryResult, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = self.unassign_task_query(task_id);
+
+        sqlx::query_with(&query.0, query.1).execute(executor).await
+    }
+
+    /// Generates a sqlx query to Find a User on DB by their handle
+    ///
+    /// # Arguments
+    /// * `handle` - The handle to search for
+    pub fn select_handle_query(handle: &str) -> (String, SqlxValues) {
+        User::select()
+            .and_where(Expr::col(UserIden::Handle).eq(handle))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to List the assignees of a Task in the DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn list_task_query(task_id: Uuid) -> (String, SqlxValues) {
+        User::select()
+            .and_where(
+                Expr::col(UserIden::Id).in_subquery(
+                    Query::select()
+                        .column(TaskAssigneeIden::UserId)
+                        .from(TaskAssigneeIden::Table)
+                        .and_where(Expr::col(TaskAssigneeIden::TaskId).eq(task_id))
+                        .to_owned(),
+                ),
+            )
+            .order_by(UserIden::Handle, Order::Asc)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Assign the User to a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn assign_task_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(TaskAssigneeIden::Table)
+            .columns([TaskAssigneeIden::TaskId, TaskAssigneeIden::UserId])
+            .values([task_id.into(), self.id.into()])
+            .unwrap()
+            .on_conflict(
+                OnConflict::columns([TaskAssigneeIden::TaskId, TaskAssigneeIden::UserId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    /// Generates a sqlx query to Unassign the User from a Task on DB
+    ///
+    /// # Arguments
+    /// * `task_id` - The id of the task
+    pub fn unassign_task_query(&self, task_id: Uuid) -> (String, SqlxValues) {
+        Query::delete()
+            .from_table(TaskAssigneeIden::Table)
+            .and_where(Expr::col(TaskAssigneeIden::TaskId).eq(task_id))
+            .and_where(Expr::col(TaskAssigneeIden::UserId).eq(self.id))
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the user edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for User {
+    type Iden = UserIden;
+    type Key = Uuid;
+
+    const TABLE: UserIden = UserIden::Table;
+    const KEY: &'static [UserIden] = &[UserIden::Id];
+    const CREATED: Option<UserIden> = Some(UserIden::Created);
+    const COLUMNS: &'static [UserIden] = &[
+        UserIden::Id,
+        UserIden::Name,
+        UserIden::Email,
+        UserIden::Handle,
+        UserIden::Avatar,
+        UserIden::Color,
+        UserIden::Created,
+        UserIden::Modified,
+    ];
+    const ORDER_BY: Option<UserIden> = Some(UserIden::Handle);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.name.clone().into(),
+            self.email.clone().into(),
+            self.handle.clone().into(),
+            self.avatar.clone().into(),
+            self.color.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// Deleting a user removes their task assignments, users that authored anything can not be deleted
+impl Repository for User {}
+
+/// Inserts a user with a random handle, for the tests of entities that reference users
+#[cfg(test)]
+pub(crate) async fn create_test_user(conn: &mut sqlx::SqliteConnection) -> User {
+    let id = Uuid::new_v4();
+    let mut user = User::new("NAME".into(), id.to_string());
+    user.assign_id().assign_created();
+    user.insert(conn).await.unwrap();
+    user
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_test_user, User};
+    use crate::config::init_memory_db;
+    use crate::db::filter::ListSpec;
+    use crate::db::projects::Project;
+    use crate::db::tasks::Task;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{Entity, Repository};
+    use sqlx::migrate::Migrator;
+    use sqlx::{Connection, SqliteConnection};
+    use std::borrow::Cow;
+    use uuid::Uuid;
+
+    #[test]
+    fn insert() {
+        let query = User::new("NAME".into(), "handle".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"user\" (\"id\", \"name\", \"email\", \"handle\", \"avatar\", \"color\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?, ?, ?, ?)");
+    }
+
+    #[test]
+    fn assign_task() {
+        let query = User::default().assign_task_query(Uuid::default());
+
+        assert_eq!(query.0, "INSERT INTO \"task_assignee\" (\"task_id\", \"user_id\") VALUES (?, ?) ON CONFLICT (\"task_id\", \"user_id\") DO NOTHING");
+    }
+
+    #[tokio::test]
+    async fn user_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let mut user = User::new("Marios".into(), "mmitsios".into());
+        user.assign_id().assign_created();
+        user.insert(&mut conn).await.unwrap();
+
+        user.set_email(Some("xrteach@gmail.com".into()));
+        user.set_color(Some("#d73a4a".into()));
+        user.update(&mut conn).await.unwrap();
+        assert_eq!(
+            User::from_handle_db("mmitsios", &mut conn).await.unwrap(),
+            user
+        );
+
+        let mut duplicate = User::new("Other".into(), "other".into());
+        duplicate.assign_id().assign_created();
+        duplicate.set_email(Some("xrteach@gmail.com".into()));
+        assert!(duplicate.insert(&mut conn).await.is_err());
+
+        let other = create_test_user(&mut conn).await;
+        let users = User::from_list_db(&ListSpec::new(), 0, 10, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(users.len(), 2);
+        assert!(users.contains(&other));
+    }
+
+    #[tokio::test]
+    async fn assignee_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let author = create_test_user(&mut conn).await;
+        let assignee = create_test_user(&mut conn).await;
+        let workspace = create_test_workspace(&mut conn).await;
+        let mut project = Project::new(*workspace.id(), "NAME".into(), "DESC".into(), *author.id());
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+        let mut task = Task::new(
+            *project.id(),
+            "TITLE".into(),
+            "BODY".into(),
+            *project.author_id(),
+        );
+        task.assign_id().assign_created();
+        task.insert(&mut conn).await.unwrap();
+
+        for user in [&author, &assignee, &assignee] {
+            user.assign_task(*task.id(), &mut conn).await.unwrap();
+        }
+        let mut expected = vec![author.clone(), assignee.clone()];
+        expected.sort_by(|a, b| a.handle().cmp(b.handle()));
+        assert_eq!(
+            User::from_task_list_db(*task.id(), &mut conn)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            Task::from_assignee_list_db(
+                *workspace.id(),
+                *assignee.id(),
+                &ListSpec::new(),
+                0,
+                10,
+                &mut conn
+            )
+            .await
+            .unwrap(),
+            vec![task.clone()]
+        );
+
+        assignee.unassign_task(*task.id(), &mut conn).await.unwrap();
+        assert_eq!(
+            User::from_task_list_db(*task.id(), &mut conn)
+                .await
+                .unwrap(),
+            vec![author.clone()]
+        );
+
+        // authors can not be deleted while they own projects or tasks, assignees can
+        assert!(author.delete(&mut conn).await.is_err());
+        author.unassign_task(*task.id(), &mut conn).await.unwrap();
+        assignee.assign_task(*task.id(), &mut conn).await.unwrap();
+        assignee.delete(&mut conn).await.unwrap();
+        assert!(User::from_task_list_db(*task.id(), &mut conn)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn author_migration_db() {
+        // Migrate the released project table, then seed authors the way it stored them
+        let migrator = sqlx::migrate!("./migrations/");
+        let before = Migrator {
+            migrations: Cow::Owned(
+                migrator
+                    .iter()
+                    .filter(|migration| migration.version < 20240615120000)
+                    .cloned()
+                    .collect(),
+            ),
+            ..sqlx::migrate!("./migrations/")
+        };
+        let mut conn = SqliteConnection::connect("sqlite://:memory:")
+            .await
+            .unwrap();
+        before.run(&mut conn).await.unwrap();
+
+        let project = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO project (id, name, description, author, created, modified)
+            VALUES (?, 'NAME', 'DESC', 'alice', '2024-07-01T09:00:00+00:00', '2024-07-01T09:00:00+00:00'),
+                (randomblob(16), 'NAME', 'DESC', 'alice', '2024-07-02T09:00:00+00:00', '2024-07-02T09:00:00+00:00'),
+                (randomblob(16), 'NAME', 'DESC', 'bob', '2024-07-03T09:00:00+00:00', '2024-07-03T09:00:00+00:00')",
+        )
+        .bind(project)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        migrator.run(&mut conn).await.unwrap();
+
+        let mut authors: Vec<String> = sqlx::query_scalar(
+            "SELECT handle FROM project JOIN user ON user.id = project.author_id",
+        )
+        .fetch_all(&mut conn)
+        .await
+        .unwrap();
+        authors.sort();
+        assert_eq!(authors, ["alice", "alice", "bob"]);
+        assert_eq!(
+            User::from_list_db(&ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // the tables created after the project one require their authors from the start
+        let result = sqlx::query(
+            "INSERT INTO task (id, project_id, status, created, modified)
+            VALUES (randomblob(16), ?, 'todo', '', '')",
+        )
+        .bind(project)
+        .execute(&mut conn)
+        .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("NOT NULL constraint failed: task.author_id"));
+    }
+}
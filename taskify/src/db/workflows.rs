@@ -0,0 +1,225 @@
+//! Project Workflow Database Entity
+//! This file contains the per-project workflow override. Projects without one use the [`Workflow`] of the config.toml.
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, OnConflict, Query, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config::Workflow;
+use crate::db::{DbError, Entity, Repository};
+
+/// The project workflow database entity for taskify, the workflow is stored as TOML
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct ProjectWorkflow {
+    project_id: Uuid,
+    definition: String,
+    modified: DateTime<Utc>,
+}
+
+impl ProjectWorkflow {
+    /// New Project Workflow
+    ///
+    /// Create a new workflow override for a project.
+    /// # Arguments:
+    /// * `project_id` - The id of the project
+    /// * `workflow` - The workflow the project should use
+    pub fn new(project_id: Uuid, workflow: &Workflow) -> Self {
+        Self {
+            project_id,
+            definition: toml::to_string(workflow).expect("Error while serializing workflow"),
+            modified: Utc::now(),
+        }
+    }
+
+    /// Resolve the workflow of a project
+    ///
+    /// Returns the project's workflow override, or `default` when the project has none.
+    /// # Arguments
+    /// * `project_id` - The id of the project
+    /// * `default` - The workflow to fall back to, usually the config.toml one
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn resolve<'c, E>(
+        project_id: Uuid,
+        default: &Workflow,
+        executor: E,
+    ) -> Result<Workflow, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = ProjectWorkflow::select_query(project_id);
+
+        match sqlx::query_as_with::<_, ProjectWorkflow, _>(&query.0, query.1)
+            .fetch_optional(executor)
+            .await?
+        {
+            Some(workflow) => Ok(workflow.workflow()?),
+            None => Ok(default.clone()),
+        }
+    }
+
+    /// Get Project ID
+    pub fn project_id(&self) -> &Uuid {
+        &self.project_id
+    }
+
+    /// Parse the stored workflow definition
+    ///
+    /// Fails if the definition can not be parsed or is not a valid workflow, see [`Workflow::validate`].
+    pub fn workflow(&self) -> Result<Workflow, DbError> {
+        let workflow: Workflow = toml::from_str(&self.definition).map_err(DbError::Workflow)?;
+        workflow.validate()?;
+        Ok(workflow)
+    }
+
+    // Database Interactions
+}
+
+impl Entity for ProjectWorkflow {
+    type Iden = ProjectWorkflowIden;
+    type Key = Uuid;
+
+    const TABLE: ProjectWorkflowIden = ProjectWorkflowIden::Table;
+    const KEY: &'static [ProjectWorkflowIden] = &[ProjectWorkflowIden::ProjectId];
+    const CREATED: Option<ProjectWorkflowIden> = None;
+    const COLUMNS: &'static [ProjectWorkflowIden] = &[
+        ProjectWorkflowIden::ProjectId,
+        ProjectWorkflowIden::Definition,
+        ProjectWorkflowIden::Modified,
+    ];
+
+    fn key(&self) -> Uuid {
+        self.project_id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.project_id.into(),
+            self.definition.clone().into(),
+            self.modified.into(),
+        ]
+    }
+
+    /// Generates a sqlx query to Insert or replace the Project Workflow on DB
+    fn insert_query(&self) -> (String, SqlxValues) {
+        Query::insert()
+            .into_table(ProjectWorkflowIden::Table)
+            .columns(Self::COLUMNS.iter().copied())
+            .values(self.values())
+            .unwrap()
+            .on_conflict(
+                OnConflict::column(ProjectWorkflowIden::ProjectId)
+                    .update_columns([
+                        ProjectWorkflowIden::Definition,
+                        ProjectWorkflowIden::Modified,
+                    ])
+                    .to_owned(),
+            )
+            .build_sqlx(SqliteQueryBuilder)
+    }
+}
+
+/// Deleting the project workflow makes the project fall back to the default workflow
+impl Repository for ProjectWorkflow {}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectWorkflow;
+    use crate::config::{init_memory_db, Workflow};
+    use crate::db::projects::Project;
+    use crate::db::users::create_test_user;
+    use crate::db::workspaces::create_test_workspace;
+    use crate::db::{DbError, Entity, Repository};
+    use uuid::Uuid;
+
+    #[test]
+    fn insert() {
+        let query = ProjectWorkflow::new(Uuid::default(), &Workflow::default()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"project_workflow\" (\"project_id\", \"definition\", \"modified\") VALUES (?, ?, ?) ON CONFLICT (\"project_id\") DO UPDATE SET \"definition\" = \"excluded\".\"definition\", \"modified\" = \"excluded\".\"modified\"");
+    }
+
+    #[test]
+    fn round_trip() {
+        let workflow = Workflow::default();
+
+        assert_eq!(
+            ProjectWorkflow::new(Uuid::default(), &workflow)
+                .workflow()
+                .unwrap(),
+            workflow
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert!(Workflow::default().validate().is_ok());
+
+        let workflow = Workflow {
+            initial: "draft".into(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ProjectWorkflow::new(Uuid::default(), &workflow).workflow(),
+            Err(DbError::UnknownWorkflowState(state)) if state == "draft"
+        ));
+
+        let mut workflow = Workflow::default();
+        workflow.completed.push("shipped".into());
+        assert!(matches!(
+            workflow.validate(),
+            Err(DbError::UnknownWorkflowState(state)) if state == "shipped"
+        ));
+
+        let mut workflow = Workflow::default();
+        workflow
+            .transitions
+            .insert("review".into(), vec!["done".into(), "qa".into()]);
+        assert!(matches!(
+            workflow.validate(),
+            Err(DbError::UnknownWorkflowState(state)) if state == "qa"
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let mut project = Project::new(
+            *create_test_workspace(&mut conn).await.id(),
+            "NAME".into(),
+            "DESC".into(),
+            *create_test_user(&mut conn).await.id(),
+        );
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+
+        let default = Workflow::default();
+        let resolved = ProjectWorkflow::resolve(*project.id(), &default, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(resolved, default);
+
+        let mut custom = Workflow::default();
+        custom.states.push("qa".into());
+        ProjectWorkflow::new(*project.id(), &custom)
+            .insert(&mut conn)
+            .await
+            .unwrap();
+        custom.states.push("released".into());
+        let stored = ProjectWorkflow::new(*project.id(), &custom);
+        stored.insert(&mut conn).await.unwrap();
+
+        let resolved = ProjectWorkflow::resolve(*project.id(), &default, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(resolved, custom);
+
+        stored.delete(&mut conn).await.unwrap();
+        let resolved = ProjectWorkflow::resolve(*project.id(), &default, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(resolved, default);
+    }
+}
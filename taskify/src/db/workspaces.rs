@@ -0,0 +1,311 @@
+//! Workspace Database Entity
+//! This file contains the workspace database entity, that separates e.g. work and personal data kept in the same
+//! database. Every [`Project`](super::projects::Project), tag & standalone note belongs to a workspace, only empty
+//! workspaces can be deleted, see [`Workspace::delete_db`]. List queries are scoped to one workspace, usually the current one from
+//! the config.toml, see [`Workspace::from_current_db`].
+
+use chrono::{DateTime, Utc};
+use sea_query::{enum_def, Expr, SimpleExpr, SqliteQueryBuilder};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{Error, Executor, FromRow, Sqlite};
+use uuid::Uuid;
+
+use crate::config;
+use crate::db::{DbError, Entity, Repository};
+
+/// The name of the workspace the migrations create and move existing projects to
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// The message the DB aborts with when a workspace that still has projects or notes is deleted
+const WORKSPACE_NOT_EMPTY: &str = "workspace not empty";
+
+/// The workspace database entity for taskify
+#[enum_def]
+#[derive(Debug, Default, Clone, PartialEq, FromRow)]
+pub struct Workspace {
+    id: Uuid,
+    name: String,
+    description: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+}
+
+impl Workspace {
+    /// New Workspace
+    ///
+    /// Create a new workspace entity with a default id, created and edited.
+    /// # Arguments:
+    /// * `name` - The unique workspace name
+    /// * `description` - The workspace description
+    pub fn new(name: String, description: String) -> Self {
+        Self {
+            name,
+            description,
+            ..Default::default()
+        }
+    }
+
+    /// Load a workspace from the DB by its name
+    ///
+    /// # Arguments
+    /// * `name` - The name to search for
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_name_db<'c, E>(name: &str, executor: E) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let query = Workspace::select_name_query(name);
+
+        sqlx::query_as_with::<_, Workspace, _>(&query.0, query.1)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Load the current workspace from the DB
+    ///
+    /// # Arguments
+    /// * `config` - The workspace configuration naming the current workspace
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn from_current_db<'c, E>(
+        config: &config::Workspace,
+        executor: E,
+    ) -> Result<Self, Error>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        Workspace::from_name_db(&config.current, executor).await
+    }
+
+    /// Get Workspace ID
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Get Workspace Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get Workspace Description
+    pub fn description(&self) -> &String {
+        &self.description
+    }
+
+    /// ID Assignment
+    ///
+    /// Assigns a random uuid v4 id to the workspace.
+    pub fn assign_id(&mut self) -> &mut Self {
+        self.id = Uuid::new_v4();
+        self
+    }
+
+    /// Created Assignment
+    ///
+    /// Assigns the workspace `created` and `modified` field to the current datetime.
+    pub fn assign_created(&mut self) -> &mut Self {
+        self.created = Utc::now();
+        self.modified = self.created;
+        self
+    }
+
+    /// Set Name
+    ///
+    /// Sets the workspace name, the config.toml names the current workspace so it has to be updated too
+    /// # Arguments
+    /// * `name` - The unique name to use
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.edited();
+    }
+
+    /// Set Description
+    ///
+    /// Sets the workspace description
+    /// # Arguments
+    /// * `description` - The string description to use
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+        self.edited();
+    }
+
+    // Database Interactions
+
+    /// Deletes the Workspace on DB
+    ///
+    /// The DB rejects workspaces that still have projects or standalone notes with [`DbError::WorkspaceNotEmpty`],
+    /// projects in the trash included. Deleting them would bypass the trash, the history & the undo journal, so the
+    /// projects have to be [deleted](super::projects::Project::delete_db) and [purged](super::projects::Project::purge)
+    /// first. The tags of the workspace are deleted with it.
+    /// # Arguments
+    /// * `executor` - The SQLite database connection, transaction or pool
+    pub async fn delete_db<'c, E>(&self, executor: E) -> Result<SqliteQueryResult, DbError>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        self.delete(executor).await.map_err(|err| match &err {
+            Error::Database(db) if db.message() == WORKSPACE_NOT_EMPTY => {
+                DbError::WorkspaceNotEmpty
+            }
+            _ => DbError::Sqlx(err),
+        })
+    }
+
+    /// Generates a sqlx query to Find a Workspace on DB by its name
+    ///
+    /// # Arguments
+    /// * `name` - The name to search for
+    pub fn select_name_query(name: &str) -> (String, SqlxValues) {
+        Workspace::select()
+            .and_where(Expr::col(WorkspaceIden::Name).eq(name))
+            .limit(1)
+            .build_sqlx(SqliteQueryBuilder)
+    }
+
+    // Private Functions
+
+    /// Set Edited
+    ///
+    /// Sets the workspace edited field to the current datetime
+    fn edited(&mut self) {
+        self.modified = Utc::now();
+    }
+}
+
+impl Entity for Workspace {
+    type Iden = WorkspaceIden;
+    type Key = Uuid;
+
+    const TABLE: WorkspaceIden = WorkspaceIden::Table;
+    const KEY: &'static [WorkspaceIden] = &[WorkspaceIden::Id];
+    const CREATED: Option<WorkspaceIden> = Some(WorkspaceIden::Created);
+    const COLUMNS: &'static [WorkspaceIden] = &[
+        WorkspaceIden::Id,
+        WorkspaceIden::Name,
+        WorkspaceIden::Description,
+        WorkspaceIden::Created,
+        WorkspaceIden::Modified,
+    ];
+    const ORDER_BY: Option<WorkspaceIden> = Some(WorkspaceIden::Name);
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+
+    fn values(&self) -> Vec<SimpleExpr> {
+        vec![
+            self.id.into(),
+            self.name.clone().into(),
+            self.description.clone().into(),
+            self.created.into(),
+            self.modified.into(),
+        ]
+    }
+}
+
+/// The DB refuses to delete a workspace that still has projects, see [`Workspace::delete_db`]
+impl Repository for Workspace {}
+
+/// Inserts a workspace with a random name, for the tests of entities that belong to workspaces
+#[cfg(test)]
+pub(crate) async fn create_test_workspace(conn: &mut sqlx::SqliteConnection) -> Workspace {
+    let mut workspace = Workspace::new(Uuid::new_v4().to_string(), "DESC".into());
+    workspace.assign_id().assign_created();
+    workspace.insert(conn).await.unwrap();
+    workspace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_test_workspace, Workspace, DEFAULT_WORKSPACE};
+    use crate::config::{self, init_memory_db};
+    use crate::db::filter::ListSpec;
+    use crate::db::notes::{Note, NoteOwner};
+    use crate::db::projects::Project;
+    use crate::db::users::create_test_user;
+    use crate::db::{DbError, Entity, Repository};
+    use sqlx::Error;
+
+    #[test]
+    fn insert() {
+        let query = Workspace::new("NAME".into(), "DESC".into()).insert_query();
+
+        assert_eq!(query.0, "INSERT INTO \"workspace\" (\"id\", \"name\", \"description\", \"created\", \"modified\") VALUES (?, ?, ?, ?, ?)");
+    }
+
+    #[tokio::test]
+    async fn current_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let default = Workspace::from_current_db(&config::Workspace::default(), &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(default.name(), DEFAULT_WORKSPACE);
+
+        let mut personal = Workspace::new("personal".into(), "DESC".into());
+        personal.assign_id().assign_created();
+        personal.insert(&mut conn).await.unwrap();
+        let current = config::Workspace {
+            current: "personal".into(),
+        };
+        assert_eq!(
+            Workspace::from_current_db(&current, &mut conn)
+                .await
+                .unwrap(),
+            personal
+        );
+
+        personal.set_name("home".into());
+        personal.update(&mut conn).await.unwrap();
+        assert!(matches!(
+            Workspace::from_current_db(&current, &mut conn).await,
+            Err(Error::RowNotFound)
+        ));
+        assert_eq!(
+            Workspace::from_list_db(&ListSpec::new(), 0, 10, &mut conn)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_db() {
+        let mut conn = init_memory_db().await.unwrap();
+        let editor = *create_test_user(&mut conn).await.id();
+        let workspace = create_test_workspace(&mut conn).await;
+        let author = create_test_user(&mut conn).await;
+        let mut project = Project::new(*workspace.id(), "NAME".into(), "DESC".into(), *author.id());
+        project.assign_id().assign_created();
+        project.insert_db(&mut conn).await.unwrap();
+
+        assert!(matches!(
+            workspace.delete_db(&mut conn).await,
+            Err(DbError::WorkspaceNotEmpty)
+        ));
+        // the projects in the trash still count
+        project.delete_db(editor, &mut conn).await.unwrap();
+        assert!(matches!(
+            workspace.delete_db(&mut conn).await,
+            Err(DbError::WorkspaceNotEmpty)
+        ));
+        assert!(Project::from_db(*project.id(), &mut conn).await.is_ok());
+
+        project.purge(editor, &mut conn).await.unwrap();
+        let owner = NoteOwner::Standalone(*workspace.id());
+        let mut note = Note::new(owner, "TITLE".into(), "BODY".into());
+        note.assign_id().assign_created();
+        note.insert(&mut conn).await.unwrap();
+        assert!(matches!(
+            workspace.delete_db(&mut conn).await,
+            Err(DbError::WorkspaceNotEmpty)
+        ));
+
+        note.delete(&mut conn).await.unwrap();
+        workspace.delete_db(&mut conn).await.unwrap();
+        assert!(matches!(
+            Workspace::from_db(*workspace.id(), &mut conn).await,
+            Err(Error::RowNotFound)
+        ));
+    }
+}
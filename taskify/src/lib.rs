@@ -2,29 +2,32 @@
 //!
 //! This `taskify` crate contains the whole backend code for all taskify services. Taskify is an open-source and memory-safe Task Management System (TMS) designed to help you stay organized, productive, and connected with your GitHub or not workflow. Built with the power of Rust and leveraging the capabilities of SeaORM, Ratatui, and Tauri, Taskify offers a seamless experience for managing your tasks, projects, notes, and GitHub issues, all from a single, intuitive interface.
 
-use sqlx::SqliteConnection;
 use config::Config;
+use db::Store;
 
 pub mod config;
 pub mod db;
+pub mod recurrence;
 
 /// Initializes Taskify by loading the config & any other necessary thing that is needed for startup
 ///
+/// Fails if the config.toml can't be loaded, see [`Config::load`], or the database can't be opened.
+///
 /// # Examples
 /// ```
 /// # #[tokio::test]
 /// # async fn test() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = taskify::init().await?;
-/// println!("{}", config.0.database.path); // Should print: "./taskify.db"
+/// println!("{}", config.0.database.path); // Should print: "./taskify.db" from the repository root
 /// # Ok(())
 /// # }
 /// ```
-pub async fn init() -> Result<(Config, SqliteConnection), Box<dyn std::error::Error>> {
-    let config = Config::load().expect("Error while loading config.toml");
+pub async fn init() -> Result<(Config, Store), Box<dyn std::error::Error>> {
+    let config = Config::load()?;
     config::init_log(&config.logger);
-    let db = config::init_db(&config.database).await?;
+    let store = Store::open(&config.database).await?;
 
-    Ok((config, db))
+    Ok((config, store))
 }
 
 #[cfg(test)]
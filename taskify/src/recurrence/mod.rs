@@ -0,0 +1,945 @@
+//! Recurrence Module
+//! This module contains the recurrence rules of recurring tasks, a subset of the RFC 5545 `RRULE` syntax.
+//!
+//! Supported are the `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY` or `YEARLY`), `INTERVAL`, `BYDAY` (plain weekdays, only for
+//! weekly rules and daily rules whose interval isn't whole weeks), `UNTIL` and `COUNT` parts. Everything is computed
+//! in UTC and the start of a series always counts as its first occurrence.
+//!
+//! ```
+//! # use chrono::{TimeZone, Utc};
+//! # use taskify::recurrence::RecurrenceRule;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let rule: RecurrenceRule = "FREQ=WEEKLY;BYDAY=MO,FR;COUNT=3".parse()?;
+//! let start = Utc.with_ymd_and_hms(2024, 6, 17, 9, 0, 0).unwrap(); // A Monday
+//!
+//! assert_eq!(rule.next_after(start, start), Utc.with_ymd_and_hms(2024, 6, 21, 9, 0, 0).single());
+//! assert_eq!(rule.occurrences(start).count(), 3);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+
+/// How often a rule repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The error returned when parsing or building a [`RecurrenceRule`] fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecurrenceError {
+    /// The rule has no `FREQ` part
+    MissingFrequency,
+    /// A part is not `KEY=VALUE`
+    MalformedPart(String),
+    /// A part appears more than once
+    DuplicatePart(String),
+    /// The part is valid RFC 5545 but not supported by taskify
+    UnsupportedPart(String),
+    /// The value of a part is invalid or not supported
+    InvalidValue { part: String, value: String },
+    /// `COUNT` and `UNTIL` can not be used together
+    CountAndUntil,
+    /// `BYDAY` is only supported for daily & weekly rules
+    UnsupportedByDay,
+    /// `BYDAY` can not be used by daily rules whose `INTERVAL` is a multiple of 7, they never change the weekday
+    ByDayInterval,
+}
+
+impl Display for RecurrenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecurrenceError::MissingFrequency => write!(f, "missing FREQ part"),
+            RecurrenceError::MalformedPart(part) => write!(f, "malformed part `{}`", part),
+            RecurrenceError::DuplicatePart(part) => write!(f, "duplicate part `{}`", part),
+            RecurrenceError::UnsupportedPart(part) => write!(f, "unsupported part `{}`", part),
+            RecurrenceError::InvalidValue { part, value } => {
+                write!(f, "invalid value `{}` for part `{}`", value, part)
+            }
+            RecurrenceError::CountAndUntil => write!(f, "COUNT and UNTIL can not be combined"),
+            RecurrenceError::UnsupportedByDay => {
+                write!(f, "BYDAY is only supported for DAILY and WEEKLY rules")
+            }
+            RecurrenceError::ByDayInterval => {
+                write!(
+                    f,
+                    "BYDAY can not be used with a DAILY INTERVAL of whole weeks"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecurrenceError {}
+
+/// A recurrence rule, e.g. `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    /// How often the rule repeats
+    frequency: Frequency,
+
+    /// Repeat every `interval` days/weeks/months/years
+    interval: u32,
+
+    /// The weekdays the rule repeats on, empty for the weekday of the series start
+    by_day: Vec<Weekday>,
+
+    /// The last datetime an occurrence may happen at (inclusive)
+    until: Option<DateTime<Utc>>,
+
+    /// The number of occurrences of the series, including the first one
+    count: Option<u32>,
+}
+
+/// The most consecutive periods without an occurrence before a series ends, valid rules skip at most 7 (a yearly
+/// leap day across a century)
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+impl RecurrenceRule {
+    /// New Recurrence Rule
+    ///
+    /// Create a rule repeating every period of `frequency`, forever.
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            by_day: vec![],
+            until: None,
+            count: None,
+        }
+    }
+
+    /// New Recurrence Rule with all parts
+    ///
+    /// Validates the parts like parsing does, the `INTERVAL` & `COUNT` have to be positive, `COUNT` & `UNTIL` can not
+    /// be combined and `BYDAY` is only allowed for weekly rules and daily rules whose interval isn't whole weeks.
+    /// # Arguments
+    /// * `frequency` - How often the rule repeats
+    /// * `interval` - Repeat every `interval` days/weeks/months/years
+    /// * `by_day` - The weekdays the rule repeats on, empty for the weekday of the series start
+    /// * `until` - The last datetime an occurrence may happen at (inclusive)
+    /// * `count` - The number of occurrences of the series, including the first one
+    pub fn try_new(
+        frequency: Frequency,
+        interval: u32,
+        by_day: Vec<Weekday>,
+        until: Option<DateTime<Utc>>,
+        count: Option<u32>,
+    ) -> Result<Self, RecurrenceError> {
+        if interval == 0 {
+            return Err(RecurrenceError::InvalidValue {
+                part: "INTERVAL".into(),
+                value: interval.to_string(),
+            });
+        }
+        if count == Some(0) {
+            return Err(RecurrenceError::InvalidValue {
+                part: "COUNT".into(),
+                value: "0".into(),
+            });
+        }
+        if count.is_some() && until.is_some() {
+            return Err(RecurrenceError::CountAndUntil);
+        }
+        if !by_day.is_empty() && matches!(frequency, Frequency::Monthly | Frequency::Yearly) {
+            return Err(RecurrenceError::UnsupportedByDay);
+        }
+        // every occurrence of such a rule falls on the weekday of the series start, other days never match
+        if !by_day.is_empty() && frequency == Frequency::Daily && interval.is_multiple_of(7) {
+            return Err(RecurrenceError::ByDayInterval);
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            by_day,
+            until,
+            count,
+        })
+    }
+
+    /// How often the rule repeats
+    pub fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+
+    /// Repeat every `interval` days/weeks/months/years
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// The weekdays the rule repeats on, empty for the weekday of the series start
+    pub fn by_day(&self) -> &[Weekday] {
+        &self.by_day
+    }
+
+    /// The last datetime an occurrence may happen at (inclusive)
+    pub fn until(&self) -> Option<DateTime<Utc>> {
+        self.until
+    }
+
+    /// The number of occurrences of the series, including the first one
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// All the occurrences of a series starting at `start`, in chronological order
+    ///
+    /// # Arguments
+    /// * `start` - The start of the series (`DTSTART`), always the first occurrence
+    pub fn occurrences(&self, start: DateTime<Utc>) -> Occurrences<'_> {
+        Occurrences {
+            rule: self,
+            start,
+            period: 0,
+            batch: vec![start],
+            emitted: 0,
+            empty: 0,
+            done: false,
+        }
+    }
+
+    /// The first occurrence of a series starting at `start` that is strictly after `after`
+    ///
+    /// # Arguments
+    /// * `start` - The start of the series (`DTSTART`), always the first occurrence
+    /// * `after` - The datetime the occurrence has to come after
+    pub fn next_after(&self, start: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.occurrences(start)
+            .find(|occurrence| *occurrence > after)
+    }
+
+    /// The start & the candidate occurrences of the `period`th period of the series, `None` once the dates overflow
+    fn period(
+        &self,
+        start: DateTime<Utc>,
+        period: i64,
+    ) -> Option<(DateTime<Utc>, Vec<DateTime<Utc>>)> {
+        let step = period.checked_mul(self.interval as i64)?;
+        let time = start.time();
+
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start
+                    .date_naive()
+                    .checked_add_signed(Duration::try_days(step)?)?;
+                Some((
+                    at(date, time),
+                    match self.by_day.is_empty() || self.by_day.contains(&date.weekday()) {
+                        true => vec![at(date, time)],
+                        false => vec![],
+                    },
+                ))
+            }
+            Frequency::Weekly => {
+                let monday = start.date_naive()
+                    - Duration::days(start.weekday().num_days_from_monday() as i64);
+                let week = monday.checked_add_signed(Duration::try_weeks(step)?)?;
+                let mut days = match self.by_day.is_empty() {
+                    true => vec![start.weekday()],
+                    false => self.by_day.clone(),
+                };
+                days.sort_by_key(|day| day.num_days_from_monday());
+                days.dedup();
+
+                let candidates = days
+                    .into_iter()
+                    .map(|day| {
+                        week.checked_add_signed(Duration::days(day.num_days_from_monday() as i64))
+                            .map(|date| at(date, time))
+                    })
+                    .collect::<Option<_>>()?;
+                Some((at(week, time), candidates))
+            }
+            Frequency::Monthly => {
+                let month = start.date_naive().with_day(1)?;
+                let month = month.checked_add_months(Months::new(u32::try_from(step).ok()?))?;
+                Some((
+                    at(month, time),
+                    month
+                        .with_day(start.day())
+                        .map(|date| at(date, time))
+                        .into_iter()
+                        .collect(),
+                ))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+                let january = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                Some((
+                    at(january, time),
+                    NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                        .map(|date| at(date, time))
+                        .into_iter()
+                        .collect(),
+                ))
+            }
+        }
+    }
+}
+
+/// Combines a date & time into a UTC datetime
+fn at(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&NaiveDateTime::new(date, time))
+}
+
+/// The iterator over the occurrences of a series, see [`RecurrenceRule::occurrences`]
+#[derive(Debug, Clone)]
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    start: DateTime<Utc>,
+    period: i64,
+    /// The pending occurrences of the current period, in reverse order
+    batch: Vec<DateTime<Utc>>,
+    emitted: u32,
+    /// The number of consecutive periods without an occurrence
+    empty: u32,
+    done: bool,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            if self.rule.count.is_some_and(|count| self.emitted >= count) {
+                self.done = true;
+                break;
+            }
+
+            if let Some(occurrence) = self.batch.pop() {
+                if self.rule.until.is_some_and(|until| occurrence > until) {
+                    self.done = true;
+                    break;
+                }
+
+                self.emitted += 1;
+                self.empty = 0;
+                return Some(occurrence);
+            }
+
+            // a rule whose periods never match, e.g. built by hand, would search forever
+            if self.empty > MAX_EMPTY_PERIODS {
+                self.done = true;
+                break;
+            }
+
+            match self.rule.period(self.start, self.period) {
+                // periods may have no occurrences, so `UNTIL` is checked against the periods too
+                Some((begin, _)) if self.rule.until.is_some_and(|until| begin > until) => {
+                    self.done = true
+                }
+                Some((_, candidates)) => {
+                    self.batch = candidates
+                        .into_iter()
+                        .filter(|candidate| *candidate > self.start)
+                        .rev()
+                        .collect();
+                    self.period += 1;
+                    self.empty += 1;
+                }
+                None => self.done = true,
+            }
+        }
+
+        None
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceError;
+
+    /// Parses a rule like `FREQ=DAILY;INTERVAL=2;COUNT=10`, an `RRULE:` prefix is allowed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_uppercase();
+        let s = s.strip_prefix("RRULE:").unwrap_or(&s);
+
+        let mut frequency = None;
+        let mut interval = None;
+        let mut by_day = None;
+        let mut until = None;
+        let mut count = None;
+
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| RecurrenceError::MalformedPart(part.into()))?;
+            let invalid = || RecurrenceError::InvalidValue {
+                part: key.into(),
+                value: value.into(),
+            };
+
+            let duplicate = match key {
+                "FREQ" => frequency
+                    .replace(parse_frequency(value).ok_or_else(invalid)?)
+                    .is_some(),
+                "INTERVAL" => interval
+                    .replace(value.parse().map_err(|_| invalid())?)
+                    .is_some(),
+                "COUNT" => count
+                    .replace(value.parse().map_err(|_| invalid())?)
+                    .is_some(),
+                "UNTIL" => until
+                    .replace(parse_until(value).ok_or_else(invalid)?)
+                    .is_some(),
+                "BYDAY" => by_day
+                    .replace(
+                        value
+                            .split(',')
+                            .map(parse_weekday)
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(invalid)?,
+                    )
+                    .is_some(),
+                _ => return Err(RecurrenceError::UnsupportedPart(key.into())),
+            };
+
+            if duplicate {
+                return Err(RecurrenceError::DuplicatePart(key.into()));
+            }
+        }
+
+        Self::try_new(
+            frequency.ok_or(RecurrenceError::MissingFrequency)?,
+            interval.unwrap_or(1),
+            by_day.unwrap_or_default(),
+            until,
+            count,
+        )
+    }
+}
+
+impl Display for RecurrenceRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_frequency(value: &str) -> Option<Frequency> {
+    match value {
+        "DAILY" => Some(Frequency::Daily),
+        "WEEKLY" => Some(Frequency::Weekly),
+        "MONTHLY" => Some(Frequency::Monthly),
+        "YEARLY" => Some(Frequency::Yearly),
+        _ => None,
+    }
+}
+
+/// Parses `YYYYMMDD` (until the end of that day) or `YYYYMMDDTHHMMSS[Z]`
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some(at(date, NaiveTime::from_hms_opt(23, 59, 59)?));
+    }
+
+    NaiveDateTime::parse_from_str(value.strip_suffix('Z').unwrap_or(value), "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|datetime| Utc.from_utc_datetime(&datetime))
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frequency, RecurrenceError, RecurrenceRule};
+    use chrono::{DateTime, TimeZone, Utc, Weekday};
+
+    fn dt(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn parse(s: &str) -> RecurrenceRule {
+        s.parse().unwrap()
+    }
+
+    fn take(rule: &str, start: DateTime<Utc>, n: usize) -> Vec<DateTime<Utc>> {
+        parse(rule).occurrences(start).take(n).collect()
+    }
+
+    #[test]
+    fn parse_full() {
+        let rule = parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20241231T120000Z");
+
+        assert_eq!(rule.frequency(), Frequency::Weekly);
+        assert_eq!(rule.interval(), 2);
+        assert_eq!(rule.by_day(), [Weekday::Mon, Weekday::Wed]);
+        assert_eq!(rule.until(), Some(dt(2024, 12, 31, 12, 0)));
+        assert_eq!(rule.count(), None);
+    }
+
+    #[test]
+    fn parse_defaults() {
+        assert_eq!(parse("FREQ=DAILY"), RecurrenceRule::new(Frequency::Daily));
+    }
+
+    #[test]
+    fn parse_prefix_and_case() {
+        assert_eq!(
+            parse("RRULE:freq=monthly;count=3"),
+            RecurrenceRule::try_new(Frequency::Monthly, 1, vec![], None, Some(3)).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_trailing_separator() {
+        assert_eq!(
+            parse("FREQ=YEARLY;"),
+            RecurrenceRule::new(Frequency::Yearly)
+        );
+    }
+
+    #[test]
+    fn parse_until_date() {
+        assert_eq!(
+            parse("FREQ=DAILY;UNTIL=20240630").until,
+            Some(Utc.with_ymd_and_hms(2024, 6, 30, 23, 59, 59).unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_until_floating() {
+        assert_eq!(
+            parse("FREQ=DAILY;UNTIL=20240630T080000").until,
+            Some(dt(2024, 6, 30, 8, 0))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |s: &str| s.parse::<RecurrenceRule>().unwrap_err();
+
+        assert_eq!(err(""), RecurrenceError::MissingFrequency);
+        assert_eq!(err("INTERVAL=2"), RecurrenceError::MissingFrequency);
+        assert_eq!(err("FREQ"), RecurrenceError::MalformedPart("FREQ".into()));
+        assert_eq!(
+            err("FREQ=DAILY;FREQ=WEEKLY"),
+            RecurrenceError::DuplicatePart("FREQ".into())
+        );
+        assert_eq!(
+            err("FREQ=DAILY;BYMONTH=1"),
+            RecurrenceError::UnsupportedPart("BYMONTH".into())
+        );
+        assert_eq!(
+            err("FREQ=HOURLY"),
+            RecurrenceError::InvalidValue {
+                part: "FREQ".into(),
+                value: "HOURLY".into()
+            }
+        );
+        assert!(matches!(
+            err("FREQ=DAILY;INTERVAL=0"),
+            RecurrenceError::InvalidValue { .. }
+        ));
+        assert!(matches!(
+            err("FREQ=DAILY;COUNT=-1"),
+            RecurrenceError::InvalidValue { .. }
+        ));
+        assert!(matches!(
+            err("FREQ=WEEKLY;BYDAY=1MO"),
+            RecurrenceError::InvalidValue { .. }
+        ));
+        assert!(matches!(
+            err("FREQ=DAILY;UNTIL=tomorrow"),
+            RecurrenceError::InvalidValue { .. }
+        ));
+        assert_eq!(
+            err("FREQ=DAILY;COUNT=2;UNTIL=20240101"),
+            RecurrenceError::CountAndUntil
+        );
+        assert_eq!(
+            err("FREQ=MONTHLY;BYDAY=MO"),
+            RecurrenceError::UnsupportedByDay
+        );
+        assert_eq!(
+            err("FREQ=DAILY;INTERVAL=7;BYDAY=TU"),
+            RecurrenceError::ByDayInterval
+        );
+        assert_eq!(
+            err("FREQ=DAILY;INTERVAL=14;BYDAY=MO"),
+            RecurrenceError::ByDayInterval
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(
+            RecurrenceRule::try_new(Frequency::Weekly, 2, vec![Weekday::Mon], None, None),
+            Ok(parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"))
+        );
+        assert_eq!(
+            RecurrenceRule::try_new(Frequency::Daily, 0, vec![], None, None),
+            Err(RecurrenceError::InvalidValue {
+                part: "INTERVAL".into(),
+                value: "0".into()
+            })
+        );
+        assert_eq!(
+            RecurrenceRule::try_new(Frequency::Daily, 1, vec![], None, Some(0)),
+            Err(RecurrenceError::InvalidValue {
+                part: "COUNT".into(),
+                value: "0".into()
+            })
+        );
+        assert_eq!(
+            RecurrenceRule::try_new(Frequency::Daily, 7, vec![Weekday::Tue], None, None),
+            Err(RecurrenceError::ByDayInterval)
+        );
+        assert_eq!(
+            RecurrenceRule::try_new(Frequency::Yearly, 1, vec![Weekday::Tue], None, None),
+            Err(RecurrenceError::UnsupportedByDay)
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for rule in [
+            "FREQ=DAILY",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE",
+            "FREQ=MONTHLY;UNTIL=20241231T235959Z",
+            "FREQ=YEARLY;INTERVAL=4;COUNT=3",
+        ] {
+            assert_eq!(parse(rule).to_string(), rule);
+        }
+    }
+
+    #[test]
+    fn daily() {
+        assert_eq!(
+            take("FREQ=DAILY", dt(2024, 2, 28, 8, 30), 3),
+            [
+                dt(2024, 2, 28, 8, 30),
+                dt(2024, 2, 29, 8, 30),
+                dt(2024, 3, 1, 8, 30)
+            ]
+        );
+    }
+
+    #[test]
+    fn daily_interval() {
+        assert_eq!(
+            take("FREQ=DAILY;INTERVAL=10", dt(2024, 1, 25, 0, 0), 3),
+            [
+                dt(2024, 1, 25, 0, 0),
+                dt(2024, 2, 4, 0, 0),
+                dt(2024, 2, 14, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn daily_by_day() {
+        // Friday, the weekend is skipped
+        assert_eq!(
+            take("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", dt(2024, 6, 21, 9, 0), 3),
+            [
+                dt(2024, 6, 21, 9, 0),
+                dt(2024, 6, 24, 9, 0),
+                dt(2024, 6, 25, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly() {
+        assert_eq!(
+            take("FREQ=WEEKLY", dt(2024, 12, 25, 10, 0), 3),
+            [
+                dt(2024, 12, 25, 10, 0),
+                dt(2025, 1, 1, 10, 0),
+                dt(2025, 1, 8, 10, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_by_day() {
+        // Wednesday, the Monday of the first week is before the start
+        assert_eq!(
+            take("FREQ=WEEKLY;BYDAY=FR,MO", dt(2024, 6, 19, 9, 0), 4),
+            [
+                dt(2024, 6, 19, 9, 0),
+                dt(2024, 6, 21, 9, 0),
+                dt(2024, 6, 24, 9, 0),
+                dt(2024, 6, 28, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_interval_by_day() {
+        assert_eq!(
+            take(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE",
+                dt(2024, 6, 17, 9, 0),
+                5
+            ),
+            [
+                dt(2024, 6, 17, 9, 0),
+                dt(2024, 6, 19, 9, 0),
+                dt(2024, 7, 1, 9, 0),
+                dt(2024, 7, 3, 9, 0),
+                dt(2024, 7, 15, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_sunday_start() {
+        // Weeks start on Monday, so a Sunday start is the last day of its week
+        assert_eq!(
+            take(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=SA,SU",
+                dt(2024, 6, 23, 9, 0),
+                3
+            ),
+            [
+                dt(2024, 6, 23, 9, 0),
+                dt(2024, 7, 6, 9, 0),
+                dt(2024, 7, 7, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly() {
+        assert_eq!(
+            take("FREQ=MONTHLY", dt(2024, 11, 15, 9, 0), 3),
+            [
+                dt(2024, 11, 15, 9, 0),
+                dt(2024, 12, 15, 9, 0),
+                dt(2025, 1, 15, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_skips_short_months() {
+        assert_eq!(
+            take("FREQ=MONTHLY", dt(2024, 1, 31, 9, 0), 4),
+            [
+                dt(2024, 1, 31, 9, 0),
+                dt(2024, 3, 31, 9, 0),
+                dt(2024, 5, 31, 9, 0),
+                dt(2024, 7, 31, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_interval() {
+        assert_eq!(
+            take("FREQ=MONTHLY;INTERVAL=5", dt(2024, 10, 1, 0, 0), 3),
+            [
+                dt(2024, 10, 1, 0, 0),
+                dt(2025, 3, 1, 0, 0),
+                dt(2025, 8, 1, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn yearly() {
+        assert_eq!(
+            take("FREQ=YEARLY;INTERVAL=2", dt(2024, 7, 4, 12, 0), 3),
+            [
+                dt(2024, 7, 4, 12, 0),
+                dt(2026, 7, 4, 12, 0),
+                dt(2028, 7, 4, 12, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn yearly_leap_day() {
+        assert_eq!(
+            take("FREQ=YEARLY", dt(2024, 2, 29, 0, 0), 3),
+            [
+                dt(2024, 2, 29, 0, 0),
+                dt(2028, 2, 29, 0, 0),
+                dt(2032, 2, 29, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn count() {
+        let rule = parse("FREQ=DAILY;COUNT=3");
+        let start = dt(2024, 6, 1, 0, 0);
+
+        assert_eq!(rule.occurrences(start).count(), 3);
+        assert_eq!(rule.occurrences(start).last(), Some(dt(2024, 6, 3, 0, 0)));
+    }
+
+    #[test]
+    fn count_one() {
+        let start = dt(2024, 6, 1, 0, 0);
+
+        assert_eq!(parse("FREQ=DAILY;COUNT=1").next_after(start, start), None);
+    }
+
+    #[test]
+    fn count_includes_start_by_day() {
+        // The start counts as an occurrence even when it doesn't match BYDAY
+        assert_eq!(
+            take("FREQ=WEEKLY;BYDAY=FR;COUNT=2", dt(2024, 6, 19, 9, 0), 5),
+            [dt(2024, 6, 19, 9, 0), dt(2024, 6, 21, 9, 0)]
+        );
+    }
+
+    #[test]
+    fn until_inclusive() {
+        assert_eq!(
+            take(
+                "FREQ=DAILY;UNTIL=20240603T090000Z",
+                dt(2024, 6, 1, 9, 0),
+                10
+            ),
+            [
+                dt(2024, 6, 1, 9, 0),
+                dt(2024, 6, 2, 9, 0),
+                dt(2024, 6, 3, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn until_before_start() {
+        assert_eq!(
+            take("FREQ=DAILY;UNTIL=20240101", dt(2024, 6, 1, 9, 0), 10),
+            Vec::<DateTime<Utc>>::new()
+        );
+    }
+
+    #[test]
+    fn until_without_matching_periods() {
+        // Monday, built by hand as parsing rejects it, every period is empty
+        let rule = RecurrenceRule {
+            interval: 7,
+            by_day: vec![Weekday::Tue],
+            until: Some(dt(2024, 12, 31, 0, 0)),
+            ..RecurrenceRule::new(Frequency::Daily)
+        };
+        let start = dt(2024, 6, 17, 9, 0);
+
+        assert_eq!(rule.occurrences(start).collect::<Vec<_>>(), [start]);
+        assert_eq!(rule.next_after(start, start), None);
+    }
+
+    #[test]
+    fn empty_periods_end_series() {
+        // built by hand as the constructor rejects them, neither has an `UNTIL` to stop at
+        let start = dt(2024, 6, 17, 9, 0);
+        let zero = RecurrenceRule {
+            interval: 0,
+            ..RecurrenceRule::new(Frequency::Daily)
+        };
+        assert_eq!(zero.occurrences(start).collect::<Vec<_>>(), [start]);
+
+        let weeks = RecurrenceRule {
+            interval: 7,
+            by_day: vec![Weekday::Tue],
+            ..RecurrenceRule::new(Frequency::Daily)
+        };
+        assert_eq!(weeks.next_after(start, start), None);
+    }
+
+    #[test]
+    fn sparse_periods() {
+        // February 29th, seven empty periods between 2096 & 2104
+        assert_eq!(
+            take("FREQ=YEARLY", dt(2096, 2, 29, 0, 0), 2),
+            [dt(2096, 2, 29, 0, 0), dt(2104, 2, 29, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn next_after() {
+        let rule = parse("FREQ=WEEKLY;BYDAY=TU,TH");
+        let start = dt(2024, 6, 18, 9, 0);
+
+        assert_eq!(rule.next_after(start, start), Some(dt(2024, 6, 20, 9, 0)));
+        assert_eq!(
+            rule.next_after(start, dt(2024, 6, 20, 9, 0)),
+            Some(dt(2024, 6, 25, 9, 0))
+        );
+        assert_eq!(rule.next_after(start, dt(2024, 6, 10, 0, 0)), Some(start));
+        assert_eq!(
+            rule.next_after(start, dt(2025, 1, 1, 0, 0)),
+            Some(dt(2025, 1, 2, 9, 0))
+        );
+    }
+
+    #[test]
+    fn next_after_exhausted() {
+        let rule = parse("FREQ=MONTHLY;UNTIL=20240801");
+
+        assert_eq!(
+            rule.next_after(dt(2024, 6, 1, 0, 0), dt(2024, 8, 1, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn overflow_ends_series() {
+        let rule = parse("FREQ=YEARLY;INTERVAL=100000");
+
+        assert_eq!(rule.occurrences(dt(2024, 1, 1, 0, 0)).count(), 3);
+    }
+}